@@ -0,0 +1,166 @@
+//! Peak memory of parsing a large `getProgramAccounts` response, buffered vs streamed.
+//!
+//! Serves a synthetic response (`STAKE_ACCOUNTS` jsonParsed accounts, 50 000 by
+//! default) two ways. The baseline is the pre-streaming path: the whole body as
+//! `response.text()` returned it, parsed at once into raw accounts and then
+//! converted. The streamed path fetches through `RpcClient` from a transport that
+//! generates the body chunk by chunk, as `HttpTransport` delivers it. A counting
+//! allocator records the peak heap growth of each.
+//!
+//! ```text
+//! cargo bench --bench parse_memory
+//! STAKE_ACCOUNTS=200000 cargo bench --bench parse_memory
+//! ```
+
+use futures::future::{BoxFuture, FutureExt};
+use futures::stream::StreamExt;
+use serde_json::Value;
+use solana_pools_data_lib::bench::{parse_stake_accounts_buffered, RpcClient};
+use solana_pools_data_lib::{AuthorityRole, BodyChunks, Result, RpcTransport, StakeAccountInfo};
+use std::future::Future;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// System allocator tracking current and peak heap usage
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn record_alloc(size: usize) {
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(current, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Staker and withdrawer of every generated account
+const AUTHORITY: &str = "6iQKfEyhr3bZMotVkW6beNZz5CPAkiwvgV2CTje9pVSS";
+
+/// Accounts per generated body chunk
+const ACCOUNTS_PER_CHUNK: usize = 500;
+
+/// Synthetic jsonParsed delegated stake account
+fn account_json(index: usize) -> String {
+    format!(
+        r#"{{"pubkey":"Acct{index:0>40}","account":{{"lamports":5002282880,"executable":false,"owner":"Stake11111111111111111111111111111111111111","rentEpoch":18446744073709551615,"space":200,"data":{{"program":"stake","space":200,"parsed":{{"type":"delegated","info":{{"meta":{{"authorized":{{"staker":"6iQKfEyhr3bZMotVkW6beNZz5CPAkiwvgV2CTje9pVSS","withdrawer":"6iQKfEyhr3bZMotVkW6beNZz5CPAkiwvgV2CTje9pVSS"}},"lockup":{{"custodian":"11111111111111111111111111111111","epoch":0,"unixTimestamp":0}},"rentExemptReserve":"2282880"}},"stake":{{"creditsObserved":123456,"delegation":{{"activationEpoch":"600","deactivationEpoch":"18446744073709551615","stake":"5000000000","voter":"Vote{voter:0>40}","warmupCooldownRate":0.25}}}}}}}}}}}}}}"#,
+        voter = index % 400
+    )
+}
+
+/// Response body for request `id`, in chunks of `ACCOUNTS_PER_CHUNK` accounts
+fn response_chunks(id: Value, accounts: usize) -> impl Iterator<Item = Vec<u8>> {
+    let head = format!(r#"{{"jsonrpc":"2.0","id":{id},"result":["#).into_bytes();
+    let body = (0..accounts).step_by(ACCOUNTS_PER_CHUNK).map(move |start| {
+        let end = (start + ACCOUNTS_PER_CHUNK).min(accounts);
+        let mut chunk = String::new();
+        for index in start..end {
+            if index > 0 {
+                chunk.push(',');
+            }
+            chunk.push_str(&account_json(index));
+        }
+        chunk.into_bytes()
+    });
+    std::iter::once(head).chain(body).chain(std::iter::once(b"]}".to_vec()))
+}
+
+/// JSON-RPC id of the request `body`
+fn request_id(body: &str) -> Value {
+    serde_json::from_str::<Value>(body).map(|request| request["id"].clone()).unwrap_or_default()
+}
+
+/// Whole response body for the request `body`
+fn response_body(body: &str, accounts: usize) -> String {
+    let bytes = response_chunks(request_id(body), accounts).flatten().collect();
+    String::from_utf8(bytes).expect("generated body is UTF-8")
+}
+
+/// Generates the response body chunk by chunk as it is consumed
+#[derive(Debug)]
+struct StreamedTransport {
+    accounts: usize,
+}
+
+impl RpcTransport for StreamedTransport {
+    fn send(&self, body: String) -> BoxFuture<'_, Result<String>> {
+        let body = response_body(&body, self.accounts);
+        async move { Ok(body) }.boxed()
+    }
+
+    fn send_chunked(&self, body: String) -> BoxFuture<'_, Result<BodyChunks>> {
+        let chunks = futures::stream::iter(response_chunks(request_id(&body), self.accounts).map(Ok));
+        async move { Ok(chunks.boxed()) }.boxed()
+    }
+}
+
+/// Run `parse`, returning (peak heap growth, accounts, seconds)
+fn measure(runtime: &tokio::runtime::Runtime, parse: impl Future<Output = Vec<StakeAccountInfo>>) -> (usize, usize, f64) {
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let started = Instant::now();
+    let accounts = runtime.block_on(parse);
+    let elapsed = started.elapsed().as_secs_f64();
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+    (peak, accounts.len(), elapsed)
+}
+
+#[allow(clippy::cast_precision_loss)] // Byte counts are only displayed
+fn mib(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+fn main() {
+    let accounts = std::env::var("STAKE_ACCOUNTS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(50_000);
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+
+    println!("Parsing one getProgramAccounts response with {accounts} stake accounts");
+    let (buffered, count, buffered_secs) = measure(&runtime, async {
+        let body = response_body(r#"{"id":1}"#, accounts);
+        parse_stake_accounts_buffered(&body).expect("valid response")
+    });
+    assert_eq!(count, accounts);
+    println!("  buffered body, full parse: peak {:>8.1} MiB  ({buffered_secs:.2}s)", mib(buffered));
+
+    let rpc = RpcClient::with_transport(Arc::new(StreamedTransport { accounts }), Duration::from_secs(300));
+    let (streamed, count, streamed_secs) = measure(&runtime, async {
+        rpc.fetch_stake_accounts_for_authority(AUTHORITY, AuthorityRole::Staker)
+            .await
+            .expect("valid response")
+    });
+    assert_eq!(count, accounts);
+    println!("  streamed body and parse:   peak {:>8.1} MiB  ({streamed_secs:.2}s)", mib(streamed));
+    #[allow(clippy::cast_precision_loss)]
+    let reduction = 100.0 * (1.0 - streamed as f64 / buffered as f64);
+    println!("  peak reduction: {reduction:.0}%");
+}
@@ -1,34 +1,16 @@
 use solana_pools_data_lib::*;
 
-async fn fetch_current_epoch(rpc_url: &str) -> solana_pools_data_lib::Result<u64> {
-    let client = reqwest::Client::new();
-    let body = serde_json::json!({
-        "jsonrpc": "2.0",
-        "id": 1,
-        "method": "getEpochInfo",
-        "params": []
-    });
-    let resp = client.post(rpc_url)
-        .json(&body)
-        .send()
-        .await?;
-    let resp_json: serde_json::Value = resp.json().await?;
-    let epoch = resp_json["result"]["epoch"].as_u64()
-        .ok_or_else(|| PoolsDataError::ParseError { message: "No epoch in response".to_string() })?;
-    Ok(epoch)
-}
-
 #[tokio::main]
 async fn main() -> solana_pools_data_lib::Result<()> {
     let rpc_url = "https://api.mainnet-beta.solana.com";
-    let current_epoch = fetch_current_epoch(rpc_url).await?;
-    println!("Current epoch: {}", current_epoch);
-
     // PoolsDataClient autodetects RPC type and configures optimal rate limits, timeouts, and concurrency
     // for public or private endpoints. No manual tuning required for best performance and reliability.
     let config = PoolsDataClientBuilder::new().build(rpc_url)?;
     let client = PoolsDataClient::from_config(config)?;
 
+    let current_epoch = client.fetch_epoch_info().await?.epoch;
+    println!("Current epoch: {}", current_epoch);
+
     let pool_stats = client.fetch_all_pools_with_stats(current_epoch).await?;
     println!("Fetched {} pools.", pool_stats.len());
 
@@ -2,16 +2,16 @@ use solana_pools_data_lib::*;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Engineers must fetch current_epoch from RPC or other source
     let rpc_url = "https://api.mainnet-beta.solana.com";
-    let current_epoch = fetch_current_epoch(rpc_url).await?;
-    println!("Current epoch: {}", current_epoch);
-
     let client = PoolsDataClient::builder()
         .auto_config(rpc_url)
         .build(rpc_url)
         .and_then(PoolsDataClient::from_config)?;
 
+    // current_epoch comes from getEpochInfo, fetched through the client's rate limiter
+    let current_epoch = client.fetch_epoch_info().await?.epoch;
+    println!("Current epoch: {}", current_epoch);
+
     let pool_stats = client.fetch_all_pools_with_stats(current_epoch).await?;
     for (pool_name, stats) in pool_stats.iter() {
         println!("Pool: {}", pool_name);
@@ -24,17 +24,3 @@ async fn main() -> Result<()> {
     }
     Ok(())
 }
-
-async fn fetch_current_epoch(rpc_url: &str) -> Result<u64> {
-    let client = reqwest::Client::new();
-    let body = serde_json::json!({
-        "jsonrpc": "2.0",
-        "id": 1,
-        "method": "getEpochInfo",
-        "params": []
-    });
-    let resp = client.post(rpc_url).json(&body).send().await?;
-    let resp_json: serde_json::Value = resp.json().await?;
-    let epoch = resp_json["result"]["epoch"].as_u64().ok_or_else(|| PoolsDataError::ParseError { message: "No epoch in response".to_string() })?;
-    Ok(epoch)
-}
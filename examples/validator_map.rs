@@ -3,13 +3,12 @@ use solana_pools_data_lib::*;
 #[tokio::main]
 async fn main() -> solana_pools_data_lib::Result<()> {
     let rpc_url = "https://api.mainnet-beta.solana.com";
-    let current_epoch = fetch_current_epoch(rpc_url).await?;
 
     // PoolsDataClient autodetects RPC type and configures optimal settings
     let config = PoolsDataClientBuilder::new().build(rpc_url)?;
     let client = PoolsDataClient::from_config(config)?;
 
-    let pool_stats = client.fetch_all_pools_with_stats(current_epoch).await?;
+    let pool_stats = client.fetch_all_pools_with_stats_auto().await?;
     if let Some(stats) = pool_stats.get("jito") {
         println!("Pool: jito");
         println!("  Total Accounts: {}", stats.summary().total_accounts);
@@ -41,22 +40,3 @@ async fn main() -> solana_pools_data_lib::Result<()> {
     }
     Ok(())
 }
-
-// Helper to fetch current epoch from RPC
-async fn fetch_current_epoch(rpc_url: &str) -> solana_pools_data_lib::Result<u64> {
-    let client = reqwest::Client::new();
-    let body = serde_json::json!({
-        "jsonrpc": "2.0",
-        "id": 1,
-        "method": "getEpochInfo",
-        "params": []
-    });
-    let resp = client.post(rpc_url)
-        .json(&body)
-        .send()
-        .await?;
-    let resp_json: serde_json::Value = resp.json().await?;
-    let epoch = resp_json["result"]["epoch"].as_u64()
-        .ok_or_else(|| PoolsDataError::ParseError { message: "No epoch in response".to_string() })?;
-    Ok(epoch)
-}
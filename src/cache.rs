@@ -0,0 +1,212 @@
+//! Per-pool response cache with request coalescing.
+//!
+//! Entries are keyed by pool authority and authority role (the inputs of the
+//! `getProgramAccounts` queries). Concurrent misses for the same key share one fetch.
+
+use crate::error::PoolError;
+use crate::pools::{AuthorityRole, PoolInfo};
+use crate::types::PoolData;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+type SharedFetch = Shared<BoxFuture<'static, Result<PoolData, PoolError>>>;
+
+/// Cache key of a pool's stake accounts
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    authority: String,
+    authority_role: AuthorityRole,
+}
+
+impl From<&PoolInfo> for CacheKey {
+    fn from(pool: &PoolInfo) -> Self {
+        Self {
+            authority: pool.authority.clone(),
+            authority_role: pool.authority_role,
+        }
+    }
+}
+
+enum Slot {
+    Ready { data: Box<PoolData>, stored_at: Instant },
+    Pending { id: u64, fetch: SharedFetch },
+}
+
+/// Pool data by authority, with one fetch in flight per key
+pub(crate) struct PoolCache {
+    ttl: Duration,
+    slots: Mutex<HashMap<CacheKey, Slot>>,
+    next_id: AtomicU64,
+}
+
+impl PoolCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            slots: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Lock the slots, evicting data older than the TTL
+    fn slots(&self) -> std::sync::MutexGuard<'_, HashMap<CacheKey, Slot>> {
+        let mut slots = self.slots.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        slots.retain(|_, slot| !matches!(slot, Slot::Ready { stored_at, .. } if stored_at.elapsed() >= self.ttl));
+        slots
+    }
+
+    /// Cached data younger than the TTL, or the result of a fetch shared with concurrent callers
+    ///
+    /// `fetch` is only called when there is neither fresh data nor a fetch in flight.
+    /// Errors are returned to every waiter but not cached.
+    pub(crate) async fn get_or_fetch(
+        &self,
+        key: CacheKey,
+        fetch: impl FnOnce() -> BoxFuture<'static, Result<PoolData, PoolError>>,
+    ) -> Result<PoolData, PoolError> {
+        let (id, shared) = {
+            let mut slots = self.slots();
+            match slots.get(&key) {
+                Some(Slot::Ready { data, .. }) => return Ok(data.as_ref().clone()),
+                Some(Slot::Pending { id, fetch }) => (*id, fetch.clone()),
+                None => {
+                    let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                    let shared = fetch().shared();
+                    slots.insert(key.clone(), Slot::Pending { id, fetch: shared.clone() });
+                    (id, shared)
+                }
+            }
+        };
+
+        let result = shared.await;
+
+        // The first waiter to finish settles the slot, unless it was invalidated meanwhile
+        let mut slots = self.slots();
+        if matches!(slots.get(&key), Some(Slot::Pending { id: pending, .. }) if *pending == id) {
+            match &result {
+                Ok(data) if !self.ttl.is_zero() => {
+                    slots.insert(key, Slot::Ready { data: Box::new(data.clone()), stored_at: Instant::now() });
+                }
+                _ => {
+                    slots.remove(&key);
+                }
+            }
+        }
+        result
+    }
+
+    /// Replace the cached data of a key with freshly fetched data
+    ///
+    /// Only keys holding data are refreshed: pools never fetched through the cache
+    /// (or whose data expired) are not added, nor are fetches in flight replaced.
+    pub(crate) fn refresh(&self, key: CacheKey, data: &PoolData) {
+        let mut slots = self.slots();
+        if let Some(Slot::Ready { data: cached, stored_at }) = slots.get_mut(&key) {
+            **cached = data.clone();
+            *stored_at = Instant::now();
+        }
+    }
+
+    /// Drop the entry of a key, returning whether there was one
+    ///
+    /// A fetch in flight still completes for its waiters but is not stored.
+    pub(crate) fn invalidate(&self, key: &CacheKey) -> bool {
+        self.slots().remove(key).is_some()
+    }
+
+    /// Drop every entry
+    pub(crate) fn clear(&self) {
+        self.slots().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PoolsDataError;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    fn key() -> CacheKey {
+        CacheKey {
+            authority: "authority".to_string(),
+            authority_role: AuthorityRole::Staker,
+        }
+    }
+
+    fn counting_fetch(calls: &Arc<AtomicUsize>, fail: bool) -> BoxFuture<'static, Result<PoolData, PoolError>> {
+        let calls = Arc::clone(calls);
+        async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            if fail {
+                let error = PoolsDataError::NetworkError { message: "refused".to_string() };
+                Err(PoolError::new("jito".to_string(), "authority".to_string(), error, 1))
+            } else {
+                Ok(PoolData::new("jito".to_string(), "authority".to_string()))
+            }
+        }
+        .boxed()
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_fetches_are_coalesced() {
+        let cache = Arc::new(PoolCache::new(Duration::from_secs(60)));
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let readers: Vec<_> = (0..10)
+            .map(|_| {
+                let (cache, calls) = (Arc::clone(&cache), Arc::clone(&calls));
+                tokio::spawn(async move { cache.get_or_fetch(key(), || counting_fetch(&calls, false)).await })
+            })
+            .collect();
+        for reader in readers {
+            assert_eq!(reader.await.unwrap().unwrap().pool_name, "jito");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Served from cache until invalidated
+        cache.get_or_fetch(key(), || counting_fetch(&calls, false)).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(cache.invalidate(&key()));
+        cache.get_or_fetch(key(), || counting_fetch(&calls, false)).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_errors_and_expired_entries_are_refetched() {
+        let cache = PoolCache::new(Duration::ZERO);
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        assert!(cache.get_or_fetch(key(), || counting_fetch(&calls, true)).await.is_err());
+        cache.get_or_fetch(key(), || counting_fetch(&calls, false)).await.unwrap();
+        // A zero TTL only coalesces concurrent fetches
+        cache.get_or_fetch(key(), || counting_fetch(&calls, false)).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert!(cache.slots().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_only_updates_cached_keys() {
+        let cache = PoolCache::new(Duration::from_millis(100));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut data = PoolData::new("jito".to_string(), "authority".to_string());
+
+        // Direct fetches do not populate the cache
+        cache.refresh(key(), &data);
+        assert!(cache.slots().is_empty());
+
+        cache.get_or_fetch(key(), || counting_fetch(&calls, false)).await.unwrap();
+        data.pool_name = "refreshed".to_string();
+        cache.refresh(key(), &data);
+        let cached = cache.get_or_fetch(key(), || counting_fetch(&calls, false)).await.unwrap();
+        assert_eq!(cached.pool_name, "refreshed");
+
+        // Expired data is evicted on the next access
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(cache.slots().is_empty());
+    }
+}
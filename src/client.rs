@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pools::AuthorityRole;
     use crate::types::{StakeDelegation, StakeAuthorized, StakeLockup};
     #[test]
     fn test_calculate_pool_statistics_basic() {
@@ -19,6 +20,7 @@ mod tests {
                 }),
                 authorized: StakeAuthorized { staker: "staker1".to_string(), withdrawer: "withdrawer1".to_string() },
                 lockup: StakeLockup { unix_timestamp: 0, epoch: 0, custodian: "".to_string() },
+                matched_authority: AuthorityRole::Staker,
             },
             StakeAccountInfo {
                 pubkey: "account2".to_string(),
@@ -34,6 +36,7 @@ mod tests {
                 }),
                 authorized: StakeAuthorized { staker: "staker2".to_string(), withdrawer: "withdrawer2".to_string() },
                 lockup: StakeLockup { unix_timestamp: 0, epoch: 0, custodian: "".to_string() },
+                matched_authority: AuthorityRole::Staker,
             },
         ];
         let stats = PoolsDataClient::calculate_pool_statistics(&stake_accounts);
@@ -49,31 +52,97 @@ mod tests {
         assert_eq!(stats.deactivated_stake_lamports, 0); // No deactivated stake without epoch
         assert_eq!(stats.validator_count, 2);
     }
+
+    #[test]
+    fn test_merge_stake_accounts_by_role() {
+        let account = |pubkey: &str, role: AuthorityRole| StakeAccountInfo {
+            pubkey: pubkey.to_string(),
+            lamports: 1000,
+            rent_exempt_reserve: 0,
+            delegation: None,
+            authorized: StakeAuthorized { staker: "staker".to_string(), withdrawer: "withdrawer".to_string() },
+            lockup: StakeLockup { unix_timestamp: 0, epoch: 0, custodian: String::new() },
+            matched_authority: role,
+        };
+
+        let merged = PoolsDataClient::merge_stake_accounts(vec![
+            vec![account("a", AuthorityRole::Staker), account("b", AuthorityRole::Staker)],
+            vec![account("b", AuthorityRole::Withdrawer), account("c", AuthorityRole::Withdrawer)],
+        ]);
+
+        assert_eq!(merged.len(), 3);
+        let role_of = |pubkey: &str| merged.iter().find(|a| a.pubkey == pubkey).unwrap().matched_authority;
+        assert_eq!(role_of("a"), AuthorityRole::Staker);
+        assert_eq!(role_of("b"), AuthorityRole::Both);
+        assert_eq!(role_of("c"), AuthorityRole::Withdrawer);
+    }
+
+    #[tokio::test]
+    async fn test_stake_pool_discovery_is_skipped_and_cached() {
+        use crate::MockTransport;
+
+        let transport = Arc::new(MockTransport::new());
+        let client = crate::test_support::mock_client(Arc::clone(&transport));
+        let result = |name: &str| {
+            let mut result = PoolsDataResult::new();
+            result.successful.insert(name.to_string(), PoolData::new(name.to_string(), String::new()));
+            result
+        };
+
+        // No SPL stake pool fetched: nothing to discover
+        client.attach_pool_state(&mut result("marinade_native")).await;
+        assert_eq!(transport.calls("getProgramAccounts"), 0);
+
+        // One listing per stake pool program, reused by the next fetch
+        client.attach_pool_state(&mut result("jito")).await;
+        client.attach_pool_state(&mut result("jito")).await;
+        assert_eq!(transport.calls("getProgramAccounts"), SPL_STAKE_POOL_PROGRAM_IDS.len());
+    }
 }
 /// Client for fetching pools data.
-use std::collections::HashMap;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::Semaphore;
-use tokio_retry::{strategy::ExponentialBackoff, Retry};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Semaphore};
+use tokio_retry::strategy::ExponentialBackoff;
 
-use crate::config::{ClientConfig, PoolsDataClientBuilder};
+use crate::cache::{CacheKey, PoolCache};
+use crate::config::{ClientConfig, FetchMode, PoolsDataClientBuilder};
+use crate::endpoints::{EndpointHealth, EndpointPool, Served};
 use crate::error::{PoolError, PoolsDataError, Result};
-use crate::pools::{get_all_pools, get_pools_by_names, PoolInfo};
-use crate::rpc::RpcClient;
+use crate::exchange_rate::{ExchangeRate, TokenSupply};
+use crate::pools::{get_all_pools, AuthorityRole, PoolInfo, PoolProgram};
+use crate::marinade::{MarinadeState, MARINADE_PROGRAM_ID, MARINADE_STATE_ADDRESS};
+use crate::registry::PoolRegistry;
+use crate::rpc::{RpcClient, MAX_MULTIPLE_ACCOUNTS};
+use crate::spl_stake_pool::{SplStakePoolState, ACCOUNT_TYPE_STAKE_POOL, RESERVE_STAKE_OFFSET, SPL_STAKE_POOL_PROGRAM_IDS};
+use crate::stake_math::{StakeHistory, REDUCE_STAKE_WARMUP_COOLDOWN_FEATURE};
 use crate::types::{
-    FieldAnalysis, PoolData, PoolStatistics, PoolsDataResult, ProductionPoolData, StakeAccountInfo,
-    ValidatorStake,
+    calculate_effective_pool_statistics, EpochInfo, EpochSchedule, FieldAnalysis, PoolData, PoolStatistics, PoolsDataResult, ProductionPoolData, StakeAccountInfo,
+    StakeAuthorized, ValidatorStake,
 };
 // Use absolute path for modules in src/
 use crate::statistics;
 use crate::statistics_calc;
 
+/// How long discovered SPL stake pool addresses are reused
+const STAKE_POOL_DISCOVERY_TTL: Duration = Duration::from_secs(3600);
+
+/// Map of reserve stake account to `StakePool` address, with when it was discovered
+type StakePoolsByReserve = Mutex<Option<(Instant, Arc<HashMap<String, String>>)>>;
+
+/// Result of one batch or sweep, by pool name (shared by the pools it covers)
+type BatchFetch = Shared<BoxFuture<'static, Arc<HashMap<String, std::result::Result<PoolData, PoolError>>>>>;
+
 /// Main client for fetching Solana pools data
 pub struct PoolsDataClient {
     config: ClientConfig,
-    rpc_client: RpcClient,
+    endpoints: Arc<EndpointPool>,
     semaphore: Arc<Semaphore>,
+    cache: PoolCache,
+    stake_pools_by_reserve: StakePoolsByReserve,
 }
 
 impl PoolsDataClient {
@@ -88,9 +157,7 @@ impl PoolsDataClient {
                 message: format!("Invalid current_epoch passed to fetch_all_pools_with_stats: {current_epoch}"),
             });
         }
-        let all_pools = crate::pools::get_all_pools();
-        let pool_names: Vec<&str> = all_pools.iter().map(|p| p.name.as_str()).collect();
-        let pools = self.fetch_pools(&pool_names).await?;
+        let pools = self.fetch_all_pools().await?;
         let mut result = std::collections::HashMap::new();
         for (pool_name, pool) in &pools {
             let stats = statistics_calc::calculate_pool_statistics_full(pool, current_epoch)?;
@@ -98,6 +165,324 @@ impl PoolsDataClient {
         }
         Ok(result)
     }
+
+    /// Fetch all pools with canonical statistics, resolving the current epoch from RPC
+    ///
+    /// Same as [`Self::fetch_all_pools_with_stats`], but calls `getEpochInfo` first
+    /// (subject to the same rate limiting, concurrency and retry policy as stake fetches).
+    ///
+    /// # Errors
+    /// Returns an error if the epoch cannot be fetched or pool statistics cannot be calculated.
+    pub async fn fetch_all_pools_with_stats_auto(&self) -> Result<HashMap<String, statistics::PoolStatisticsFull>> {
+        let epoch_info = self.fetch_epoch_info().await?;
+        self.fetch_all_pools_with_stats(epoch_info.epoch).await
+    }
+
+    /// Fetch all pools with canonical statistics classified by effective stake
+    ///
+    /// Resolves the current epoch and the `StakeHistory` sysvar from RPC, so accounts
+    /// that are partially warmed up or cooled down are reported as activating/deactivating
+    /// exactly as the runtime sees them.
+    ///
+    /// # Errors
+    /// Returns an error if the epoch, stake history or pool data cannot be fetched.
+    pub async fn fetch_all_pools_with_effective_stats(&self) -> Result<HashMap<String, statistics::PoolStatisticsFull>> {
+        let epoch_info = self.fetch_epoch_info().await?;
+        let stake_history = self.fetch_stake_history().await?;
+        let new_rate_activation_epoch = self.fetch_new_rate_activation_epoch().await?;
+        let pools = self.fetch_all_pools().await?;
+
+        let mut result = HashMap::new();
+        for (pool_name, pool) in &pools {
+            let stats = statistics_calc::calculate_pool_statistics_full_with_history(
+                pool,
+                epoch_info.epoch,
+                &stake_history,
+                new_rate_activation_epoch,
+            )?;
+            result.insert(pool_name.clone(), stats);
+        }
+        Ok(result)
+    }
+
+    /// Fetch pool statistics with effective, activating and deactivating lamports
+    ///
+    /// Totals match what explorers and `solana stakes` report mid-epoch.
+    ///
+    /// # Errors
+    /// Returns an error if the epoch, stake history or pool data cannot be fetched.
+    pub async fn fetch_effective_pool_statistics(&self, pool_names: &[&str]) -> Result<HashMap<String, PoolStatistics>> {
+        let epoch_info = self.fetch_epoch_info().await?;
+        let stake_history = self.fetch_stake_history().await?;
+        let new_rate_activation_epoch = self.fetch_new_rate_activation_epoch().await?;
+        let pools = self.fetch_pools_debug(pool_names).await?;
+
+        Ok(pools
+            .successful
+            .iter()
+            .map(|(name, pool)| {
+                let stats = calculate_effective_pool_statistics(
+                    &pool.stake_accounts,
+                    epoch_info.epoch,
+                    &stake_history,
+                    new_rate_activation_epoch,
+                );
+                (name.clone(), stats)
+            })
+            .collect())
+    }
+
+    /// Fetch the `StakeHistory` sysvar from RPC
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the RPC endpoint is unreachable or the sysvar data is malformed.
+    pub async fn fetch_stake_history(&self) -> Result<StakeHistory> {
+        self.call_with_policy(|rpc| async move { rpc.get_stake_history().await }).await
+    }
+
+    /// Resolve the epoch from which the reduced warmup/cooldown rate applies
+    ///
+    /// Returns the builder override when set. Otherwise reads the activation slot of
+    /// the `reduce_stake_warmup_cooldown` feature account and maps it to an epoch
+    /// with the cluster epoch schedule; `None` while the feature is not active.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the feature account or epoch schedule cannot be fetched.
+    pub async fn fetch_new_rate_activation_epoch(&self) -> Result<Option<u64>> {
+        if let Some(epoch) = self.config.new_rate_activation_epoch {
+            return Ok(Some(epoch));
+        }
+        let activation_slot = self
+            .call_with_policy(|rpc| async move { rpc.get_feature_activation_slot(REDUCE_STAKE_WARMUP_COOLDOWN_FEATURE).await })
+            .await?;
+        match activation_slot {
+            Some(slot) => Ok(Some(self.fetch_epoch_schedule().await?.get_epoch(slot))),
+            None => Ok(None),
+        }
+    }
+
+    /// Fetch current epoch information from RPC
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the RPC endpoint is unreachable or returns invalid responses.
+    pub async fn fetch_epoch_info(&self) -> Result<EpochInfo> {
+        self.call_with_policy(|rpc| async move { rpc.get_epoch_info().await }).await
+    }
+
+    /// Fetch the cluster epoch schedule from RPC
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the RPC endpoint is unreachable or returns invalid responses.
+    pub async fn fetch_epoch_schedule(&self) -> Result<EpochSchedule> {
+        self.call_with_policy(|rpc| async move { rpc.get_epoch_schedule().await }).await
+    }
+
+    /// Fetch and decode an SPL stake pool's `StakePool` and `ValidatorList` accounts
+    ///
+    /// # Errors
+    ///
+    /// Returns error if either account cannot be fetched, is not owned by a known
+    /// stake pool program, or cannot be decoded.
+    pub async fn fetch_spl_stake_pool_state(&self, address: &str) -> Result<SplStakePoolState> {
+        let (owner, data) = self
+            .call_with_policy(|rpc| async move { rpc.get_account_with_owner(address).await })
+            .await?;
+        if !SPL_STAKE_POOL_PROGRAM_IDS.contains(&owner.as_str()) {
+            return Err(PoolsDataError::InvalidStakeData {
+                message: format!("{address} is owned by {owner}, not a stake pool program"),
+            });
+        }
+
+        let state = SplStakePoolState::decode_stake_pool(address, &owner, &data)?;
+        let validator_list = state.validator_list.as_str();
+        let list_data = self
+            .call_with_policy(|rpc| async move { rpc.get_account_data(validator_list).await })
+            .await?;
+        state.with_validator_list(&list_data)
+    }
+
+    /// Fetch and decode Marinade's `State` account with its validator and stake lists
+    ///
+    /// # Errors
+    ///
+    /// Returns error if any account cannot be fetched, the state is not owned by the
+    /// Marinade program, or the data cannot be decoded.
+    pub async fn fetch_marinade_state(&self) -> Result<MarinadeState> {
+        let (owner, state_data) = self
+            .call_with_policy(|rpc| async move { rpc.get_account_with_owner(MARINADE_STATE_ADDRESS).await })
+            .await?;
+        if owner != MARINADE_PROGRAM_ID {
+            return Err(PoolsDataError::InvalidStakeData {
+                message: format!("{MARINADE_STATE_ADDRESS} is owned by {owner}, not the Marinade program"),
+            });
+        }
+
+        let state = MarinadeState::decode_state(MARINADE_STATE_ADDRESS, &state_data)?;
+        let validator_list = state.validator_list.as_str();
+        let stake_list = state.stake_list.as_str();
+        let validator_list_data = self
+            .call_with_policy(|rpc| async move { rpc.get_account_data(validator_list).await })
+            .await?;
+        let stake_list_data = self
+            .call_with_policy(|rpc| async move { rpc.get_account_data(stake_list).await })
+            .await?;
+
+        MarinadeState::from_account_data(MARINADE_STATE_ADDRESS, &state_data, &validator_list_data, &stake_list_data)
+    }
+
+    /// Fetch the supply of an SPL token mint
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the RPC endpoint is unreachable or the mint does not exist.
+    pub async fn fetch_token_supply(&self, mint: &str) -> Result<TokenSupply> {
+        self.call_with_policy(|rpc| async move { rpc.get_token_supply(mint).await }).await
+    }
+
+    /// Fetch SOL/LST exchange rates for pools with a known LST mint
+    ///
+    /// Uses on-chain pool lamports when the client includes pool state, and the
+    /// sum of stake account balances otherwise. Pools without an LST mint are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the pool data cannot be fetched.
+    pub async fn fetch_exchange_rates(&self, pool_names: &[&str]) -> Result<HashMap<String, ExchangeRate>> {
+        let mut result = self.fetch_pools_debug(pool_names).await?;
+        if !self.config.include_exchange_rates {
+            self.attach_exchange_rates(&mut result).await;
+        }
+
+        Ok(result
+            .successful
+            .into_iter()
+            .filter_map(|(name, pool)| pool.exchange_rate.map(|rate| (name, rate)))
+            .collect())
+    }
+
+    /// Attach exchange rates to successfully fetched pools that have an LST mint
+    ///
+    /// Failures are logged and leave the rate empty.
+    async fn attach_exchange_rates(&self, result: &mut PoolsDataResult) {
+        let epoch = match self.fetch_epoch_info().await {
+            Ok(info) => Some(info.epoch),
+            Err(e) => {
+                log::warn!("Failed to fetch epoch for exchange rates: {e}");
+                None
+            }
+        };
+
+        let mints: Vec<(String, String)> = result
+            .successful
+            .keys()
+            .filter_map(|name| {
+                let mint = self.config.registry.get(name)?.lst_mint.clone()?;
+                Some((name.clone(), mint))
+            })
+            .collect();
+
+        // Supplies are fetched concurrently, bounded by the semaphore in `call_with_policy`
+        let supplies = futures::future::join_all(mints.iter().map(|(_, mint)| self.fetch_token_supply(mint))).await;
+        for ((pool_name, mint), supply) in mints.iter().zip(supplies) {
+            let Some(pool) = result.successful.get_mut(pool_name) else {
+                continue;
+            };
+            match supply {
+                Ok(supply) => pool.exchange_rate = ExchangeRate::for_pool(pool, mint, supply, epoch),
+                Err(e) => log::warn!("Failed to fetch token supply of {mint} for {pool_name}: {e}"),
+            }
+        }
+    }
+
+    /// Map of reserve stake account to `StakePool` address for every SPL stake pool
+    ///
+    /// The reserve stake account is owned by the pool's withdraw authority, so it
+    /// shows up among a pool's stake accounts and identifies the pool. A complete
+    /// discovery is reused for `STAKE_POOL_DISCOVERY_TTL`.
+    async fn discover_spl_stake_pools(&self) -> Arc<HashMap<String, String>> {
+        let mut cached = self.stake_pools_by_reserve.lock().await;
+        if let Some((discovered_at, pools_by_reserve)) = cached.as_ref() {
+            if discovered_at.elapsed() < STAKE_POOL_DISCOVERY_TTL {
+                return Arc::clone(pools_by_reserve);
+            }
+        }
+
+        let account_type = bs58::encode([ACCOUNT_TYPE_STAKE_POOL]).into_string();
+        let account_type = account_type.as_str();
+
+        let mut pools_by_reserve = HashMap::new();
+        let mut complete = true;
+        for &program_id in SPL_STAKE_POOL_PROGRAM_IDS {
+            let slices = self
+                .call_with_policy(|rpc| async move {
+                    rpc.get_program_account_slices(program_id, (0, account_type), (RESERVE_STAKE_OFFSET, 32))
+                        .await
+                })
+                .await;
+
+            match slices {
+                Ok(slices) => {
+                    for (address, reserve) in slices {
+                        pools_by_reserve.insert(bs58::encode(reserve).into_string(), address);
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Failed to list stake pools of {program_id}: {e}");
+                    complete = false;
+                }
+            }
+        }
+
+        let pools_by_reserve = Arc::new(pools_by_reserve);
+        if complete {
+            *cached = Some((Instant::now(), Arc::clone(&pools_by_reserve)));
+        }
+        pools_by_reserve
+    }
+
+    /// Attach on-chain pool state to successfully fetched pools
+    ///
+    /// Failures are logged and leave the state empty; stake account data is kept.
+    async fn attach_pool_state(&self, result: &mut PoolsDataResult) {
+        let program = |pool: &PoolData| self.config.registry.get(&pool.pool_name).map(|info| info.program);
+        let pools_by_reserve = if result.successful.values().any(|pool| program(pool) == Some(PoolProgram::SplStakePool)) {
+            self.discover_spl_stake_pools().await
+        } else {
+            Arc::default()
+        };
+
+        for pool in result.successful.values_mut() {
+            match program(pool) {
+                Some(PoolProgram::Marinade) => {
+                    match self.fetch_marinade_state().await {
+                        Ok(state) => pool.marinade_state = Some(state),
+                        Err(e) => log::warn!("Failed to fetch Marinade state for {}: {e}", pool.pool_name),
+                    }
+                    continue;
+                }
+                Some(PoolProgram::SplStakePool) => {}
+                _ => continue,
+            }
+
+            let Some(address) = pool
+                .stake_accounts
+                .iter()
+                .find_map(|account| pools_by_reserve.get(&account.pubkey))
+            else {
+                continue;
+            };
+
+            match self.fetch_spl_stake_pool_state(address).await {
+                Ok(state) => pool.spl_stake_pool = Some(state),
+                Err(e) => log::warn!("Failed to fetch stake pool state for {}: {e}", pool.pool_name),
+            }
+        }
+    }
+
     /// Create a new client builder
     #[must_use]
     pub fn builder() -> PoolsDataClientBuilder {
@@ -111,16 +496,44 @@ impl PoolsDataClient {
     /// Returns error if the configuration is invalid or if system resources cannot be allocated.
     pub fn from_config(config: ClientConfig) -> Result<Self> {
         let semaphore = Arc::new(Semaphore::new(config.max_concurrent));
-        let rpc_client = RpcClient::new(config.rpc_url.clone(), config.timeout);
+        let endpoints = Arc::new(EndpointPool::new(
+            &config.endpoints,
+            config.timeout,
+            config.account_encoding,
+            config.transport.as_ref(),
+        )?);
+
+        let cache = PoolCache::new(config.cache_ttl);
 
         Ok(Self {
             config,
-            rpc_client,
+            endpoints,
             semaphore,
+            cache,
+            stake_pools_by_reserve: Mutex::new(None),
         })
     }
 
-    /// Get list of all available pools
+    /// Health of each configured RPC endpoint, primary first
+    #[must_use]
+    pub fn endpoint_health(&self) -> Vec<EndpointHealth> {
+        self.endpoints.health()
+    }
+
+    /// Metrics recorded by this client, if configured
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    pub fn metrics(&self) -> Option<&Arc<crate::metrics::PoolMetrics>> {
+        self.config.metrics.as_ref()
+    }
+
+    /// Pool registry used by this client
+    #[must_use]
+    pub fn registry(&self) -> &PoolRegistry {
+        &self.config.registry
+    }
+
+    /// Get list of all embedded pools (see [`Self::registry`] for this client's pools)
     #[must_use]
     pub fn list_available_pools() -> Vec<PoolInfo> {
         get_all_pools().to_vec()
@@ -138,7 +551,7 @@ impl PoolsDataClient {
     ///
     /// Returns error if the RPC endpoint is unreachable or returns invalid responses.
     pub async fn test_connection(&self) -> Result<()> {
-        self.rpc_client.test_connection().await
+        self.call_with_policy(|rpc| async move { rpc.test_connection().await }).await
     }
 
     /// Fetch stake pool data for production use
@@ -174,8 +587,7 @@ impl PoolsDataClient {
     ///
     /// Returns error if any pool fails to fetch or if network issues occur.
     pub async fn fetch_all_pools(&self) -> Result<HashMap<String, ProductionPoolData>> {
-        let all_pools = get_all_pools();
-        let pool_names: Vec<&str> = all_pools.iter().map(|p| p.name.as_str()).collect();
+        let pool_names: Vec<&str> = self.config.registry.pools().iter().map(|p| p.name.as_str()).collect();
         self.fetch_pools(&pool_names).await
     }
 
@@ -193,7 +605,46 @@ impl PoolsDataClient {
     /// Panics if the result contains failed pools but the failed map is unexpectedly empty.
     /// This should never happen in normal operation.
     pub async fn fetch_pools_debug(&self, pool_names: &[&str]) -> Result<PoolsDataResult> {
-        let pools_to_fetch = get_pools_by_names(pool_names);
+        self.fetch_pools_result(pool_names, false).await
+    }
+
+    /// Fetch pools through the response cache
+    ///
+    /// Stake accounts younger than the cache TTL (see
+    /// [`PoolsDataClientBuilder::cache_ttl`]) are served from memory, and concurrent
+    /// calls for the same pool share one RPC fetch. Pool state and exchange rates, when
+    /// enabled, are fetched on every call.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if all requested pools fail to fetch.
+    pub async fn fetch_pools_cached(&self, pool_names: &[&str]) -> Result<HashMap<String, ProductionPoolData>> {
+        let result = self.fetch_pools_debug_cached(pool_names).await?;
+        Ok(result.successful.iter().map(|(name, pool)| (name.clone(), pool.into())).collect())
+    }
+
+    /// Debug format of [`Self::fetch_pools_cached`]
+    ///
+    /// # Errors
+    ///
+    /// Returns error if all requested pools fail to fetch.
+    pub async fn fetch_pools_debug_cached(&self, pool_names: &[&str]) -> Result<PoolsDataResult> {
+        self.fetch_pools_result(pool_names, true).await
+    }
+
+    /// Drop the cached stake accounts of a pool, returning whether any were cached
+    pub fn invalidate(&self, pool_name: &str) -> bool {
+        self.config.registry.get(pool_name).is_some_and(|pool| self.cache.invalidate(&CacheKey::from(pool)))
+    }
+
+    /// Drop all cached stake accounts
+    pub fn invalidate_all(&self) {
+        self.cache.clear();
+    }
+
+    /// Fetch pools concurrently, through the cache or directly (refreshing pools already cached)
+    async fn fetch_pools_result(&self, pool_names: &[&str], cached: bool) -> Result<PoolsDataResult> {
+        let pools_to_fetch = self.config.registry.get_many(pool_names);
 
         if pools_to_fetch.is_empty() {
             return Err(PoolsDataError::PoolNotFound {
@@ -203,56 +654,59 @@ impl PoolsDataClient {
 
         log::info!("Fetching {} pools", pools_to_fetch.len());
 
-        let mut tasks = Vec::new();
-        for pool_info in pools_to_fetch {
-            let rpc_client = self.rpc_client.clone();
-            let semaphore = Arc::clone(&self.semaphore);
-            let retry_attempts = self.config.retry_attempts;
-            let retry_base_delay = self.config.retry_base_delay;
-            let rate_limiter = self.config.rate_limiter.clone();
-
-            let task = tokio::spawn(async move {
-                Self::fetch_single_pool_impl(
-                    rpc_client,
-                    semaphore,
-                    pool_info,
-                    retry_attempts,
-                    retry_base_delay,
-                    rate_limiter,
-                )
-                .await
-            });
-            tasks.push(task);
-        }
+        let batches = self.plan_batches(&pools_to_fetch);
+        let epoch = self.fetch_epoch_once();
+        let fetches = pools_to_fetch.into_iter().map(|pool_info| {
+            let key = CacheKey::from(&pool_info);
+            let name = pool_info.name.clone();
+            let batch = batches.get(&name).cloned();
+            let epoch = epoch.clone();
+            // Fetched pools record the epoch of their own fetch (cached ones keep theirs)
+            let fetch = move || {
+                let fetch = match batch {
+                    Some(batch) => Self::pool_from_batch(batch, pool_info),
+                    None => self.spawn_pool_fetch(pool_info),
+                };
+                async move {
+                    let (fetched, epoch) = futures::future::join(fetch, epoch).await;
+                    fetched.map(|mut pool| {
+                        pool.epoch = epoch;
+                        pool
+                    })
+                }
+                .boxed()
+            };
+            async move {
+                let fetched = if cached {
+                    self.cache.get_or_fetch(key, fetch).await
+                } else {
+                    let fetched = fetch().await;
+                    if let Ok(pool) = &fetched {
+                        self.cache.refresh(key, pool);
+                    }
+                    fetched
+                };
+                // Pools sharing an authority share a cache entry
+                fetched.map(|mut pool| {
+                    pool.pool_name = name;
+                    pool
+                })
+            }
+        });
 
         let mut result = PoolsDataResult::new();
-
-        for task in tasks {
-            match task.await {
-                Ok(Ok(pool_data)) => {
+        for fetched in futures::future::join_all(fetches).await {
+            match fetched {
+                Ok(pool_data) => {
                     result
                         .successful
                         .insert(pool_data.pool_name.clone(), pool_data);
                 }
-                Ok(Err(pool_error)) => {
+                Err(pool_error) => {
                     result
                         .failed
                         .insert(pool_error.pool_name.clone(), pool_error);
                 }
-                Err(join_error) => {
-                    log::error!("Task join error: {join_error}");
-                    result.failed.insert(
-                        "unknown".to_string(),
-                        PoolError::new(
-                            "unknown".to_string(),
-                            "unknown".to_string(),
-                            PoolsDataError::InternalError {
-                                message: format!("Task failed: {join_error}"),
-                            },
-                            0,
-                        ),
-                    );
-                }
             }
         }
 
@@ -261,6 +715,18 @@ impl PoolsDataClient {
         result.summary.successful_pools = result.successful.len();
         result.summary.failed_pools = result.failed.len();
 
+        if self.config.include_pool_state {
+            self.attach_pool_state(&mut result).await;
+        }
+        if self.config.include_exchange_rates {
+            self.attach_exchange_rates(&mut result).await;
+        }
+
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.config.metrics {
+            metrics.observe_result(&result);
+        }
+
         if result.successful.is_empty() && !result.failed.is_empty() {
             let first_error = result.failed.values().next().unwrap();
             return Err(first_error.error.clone());
@@ -269,59 +735,478 @@ impl PoolsDataClient {
         Ok(result)
     }
 
-    /// Fetch data for a single pool with retries and rate limiting
+    /// Current epoch, read at most once and only if the returned future is awaited
+    fn fetch_epoch_once(&self) -> Shared<BoxFuture<'static, Option<u64>>> {
+        let (endpoints, semaphore) = (Arc::clone(&self.endpoints), Arc::clone(&self.semaphore));
+        let retry_strategy = Self::retry_strategy(self.config.retry_base_delay, self.config.retry_attempts);
+        async move {
+            let _permit = semaphore.acquire().await.ok()?;
+            match endpoints.call(retry_strategy, |rpc| async move { rpc.get_epoch_info().await }).await {
+                Ok(served) => Some(served.value.epoch),
+                Err(e) => {
+                    log::warn!("Failed to read the epoch of this fetch: {e}");
+                    None
+                }
+            }
+        }
+        .boxed()
+        .shared()
+    }
+
+    /// Fetch one pool in a background task (it completes even if the caller is dropped)
+    fn spawn_pool_fetch(&self, pool_info: PoolInfo) -> BoxFuture<'static, std::result::Result<PoolData, PoolError>> {
+        let endpoints = Arc::clone(&self.endpoints);
+        let semaphore = Arc::clone(&self.semaphore);
+        let retry_attempts = self.config.retry_attempts;
+        let retry_base_delay = self.config.retry_base_delay;
+        let (pool_name, authority) = (pool_info.name.clone(), pool_info.authority.clone());
+
+        #[cfg(feature = "metrics")]
+        let metrics = self.config.metrics.clone();
+
+        let task = tokio::spawn(async move {
+            #[cfg(feature = "metrics")]
+            let (pool_name, started) = (pool_info.name.clone(), std::time::Instant::now());
+
+            #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+            let (result, retries) = Self::fetch_single_pool_impl(
+                endpoints,
+                semaphore,
+                pool_info,
+                retry_attempts,
+                retry_base_delay,
+            )
+            .await;
+
+            #[cfg(feature = "metrics")]
+            if let Some(metrics) = &metrics {
+                metrics.record_fetch(&pool_name, started.elapsed(), retries, result.as_ref().err().map(|e| &e.error));
+            }
+            result
+        });
+
+        async move {
+            task.await.unwrap_or_else(|join_error| {
+                log::error!("Task join error: {join_error}");
+                let error = PoolsDataError::InternalError {
+                    message: format!("Task failed: {join_error}"),
+                };
+                Err(PoolError::new(pool_name, authority, error, 0))
+            })
+        }
+        .boxed()
+    }
+
+    /// Group pools into one stake program sweep, or JSON-RPC batches of at most `batch_size` queries
+    ///
+    /// Returns the sweep or batch of each grouped pool; pools alone in their batch
+    /// (and every pool when batching is disabled) are fetched one request at a time.
+    /// A group is only fetched once one of its pools is awaited, so pools served
+    /// from the cache cost nothing unless a group neighbour misses.
+    fn plan_batches(&self, pools: &[PoolInfo]) -> HashMap<String, BatchFetch> {
+        if matches!(self.config.fetch_mode, FetchMode::Sweep { .. }) {
+            let sweep = self.spawn_batch_fetch(pools.to_vec());
+            return pools.iter().map(|pool| (pool.name.clone(), sweep.clone())).collect();
+        }
+        if self.config.batch_size <= 1 {
+            return HashMap::new();
+        }
+
+        let mut chunks: Vec<Vec<PoolInfo>> = Vec::new();
+        let mut queries = 0;
+        for pool in pools {
+            let roles = pool.authority_role.roles().len();
+            match chunks.last_mut() {
+                Some(chunk) if queries + roles <= self.config.batch_size => {
+                    chunk.push(pool.clone());
+                    queries += roles;
+                }
+                _ => {
+                    chunks.push(vec![pool.clone()]);
+                    queries = roles;
+                }
+            }
+        }
+
+        chunks
+            .into_iter()
+            .filter(|chunk| chunk.len() > 1)
+            .flat_map(|chunk| {
+                let names: Vec<String> = chunk.iter().map(|pool| pool.name.clone()).collect();
+                let batch = self.spawn_batch_fetch(chunk);
+                names.into_iter().map(move |name| (name, batch.clone()))
+            })
+            .collect()
+    }
+
+    /// One pool's result out of its batch
+    fn pool_from_batch(batch: BatchFetch, pool_info: PoolInfo) -> BoxFuture<'static, std::result::Result<PoolData, PoolError>> {
+        async move {
+            batch.await.get(&pool_info.name).cloned().unwrap_or_else(|| {
+                let error = PoolsDataError::InternalError {
+                    message: format!("Pool {} missing from its batch", pool_info.name),
+                };
+                Err(PoolError::new(pool_info.name, pool_info.authority, error, 0))
+            })
+        }
+        .boxed()
+    }
+
+    /// Fetch several pools with one batch request or sweep in a background task, started on first poll
+    fn spawn_batch_fetch(&self, pools: Vec<PoolInfo>) -> BatchFetch {
+        let endpoints = Arc::clone(&self.endpoints);
+        let semaphore = Arc::clone(&self.semaphore);
+        let retry_attempts = self.config.retry_attempts;
+        let retry_base_delay = self.config.retry_base_delay;
+        let fetch_mode = self.config.fetch_mode;
+
+        #[cfg(feature = "metrics")]
+        let metrics = self.config.metrics.clone();
+
+        async move {
+            let pool_keys: Vec<(String, String)> = pools.iter().map(|pool| (pool.name.clone(), pool.authority.clone())).collect();
+            let task = tokio::spawn(async move {
+                #[cfg(feature = "metrics")]
+                let started = std::time::Instant::now();
+
+                let results = match fetch_mode {
+                    FetchMode::Sweep { data_slice } => {
+                        Self::fetch_sweep_impl(endpoints, semaphore, pools, data_slice, retry_attempts, retry_base_delay).await
+                    }
+                    FetchMode::PerPool => {
+                        Self::fetch_batch_impl(endpoints, semaphore, pools, retry_attempts, retry_base_delay).await
+                    }
+                };
+
+                #[cfg(feature = "metrics")]
+                if let Some(metrics) = &metrics {
+                    for (pool_name, (result, retries)) in &results {
+                        metrics.record_fetch(pool_name, started.elapsed(), *retries, result.as_ref().err().map(|e| &e.error));
+                    }
+                }
+                results.into_iter().map(|(name, (result, _))| (name, result)).collect()
+            });
+
+            Arc::new(task.await.unwrap_or_else(|join_error| {
+                log::error!("Task join error: {join_error}");
+                pool_keys
+                    .into_iter()
+                    .map(|(pool_name, authority)| {
+                        let error = PoolsDataError::InternalError {
+                            message: format!("Task failed: {join_error}"),
+                        };
+                        (pool_name.clone(), Err(PoolError::new(pool_name, authority, error, 0)))
+                    })
+                    .collect()
+            }))
+        }
+        .boxed()
+        .shared()
+    }
+
+    /// Fetch several pools with one batch request, with retries and endpoint failover
+    ///
+    /// Pools whose queries failed with a retryable error inside the batch, and every
+    /// pool when the endpoint rejects batches, are refetched one request at a time.
+    /// Also returns the number of RPC retries made for each pool.
+    async fn fetch_batch_impl(
+        endpoints: Arc<EndpointPool>,
+        semaphore: Arc<Semaphore>,
+        pools: Vec<PoolInfo>,
+        retry_attempts: u32,
+        retry_base_delay: Duration,
+    ) -> HashMap<String, (std::result::Result<PoolData, PoolError>, u32)> {
+        let queries: Vec<(String, AuthorityRole)> = pools
+            .iter()
+            .flat_map(|pool| pool.authority_role.roles().iter().map(|&role| (pool.authority.clone(), role)))
+            .collect();
+
+        log::debug!("Fetching {} pools in one batch of {} queries", pools.len(), queries.len());
+
+        let mut attempts: u32 = 0;
+        let batch = match semaphore.acquire().await {
+            Ok(_permit) => {
+                endpoints
+                    .call(Self::retry_strategy(retry_base_delay, retry_attempts), |rpc| {
+                        attempts += 1;
+                        let queries = &queries;
+                        async move { rpc.fetch_stake_accounts_batch(queries).await }
+                    })
+                    .await
+            }
+            Err(e) => Err(PoolsDataError::InternalError {
+                message: format!("Failed to acquire semaphore: {e}"),
+            }),
+        };
+
+        // The batch is one query
+        let retries = attempts.saturating_sub(1);
+        let mut results = HashMap::new();
+        let mut fallback = Vec::new();
+        match batch {
+            Ok(Served { value: Some(items), endpoint }) => {
+                let mut items = items.into_iter();
+                for pool_info in pools {
+                    let by_role: Vec<Result<Vec<StakeAccountInfo>>> =
+                        items.by_ref().take(pool_info.authority_role.roles().len()).collect();
+                    match by_role.into_iter().collect::<Result<Vec<_>>>() {
+                        Err(e) if e.is_retryable() => {
+                            log::warn!("Batched fetch of pool {} failed ({e}), retrying on its own", pool_info.name);
+                            fallback.push(pool_info);
+                        }
+                        by_role => {
+                            let pool_name = pool_info.name.clone();
+                            let result = Self::pool_data_from_accounts(pool_info, by_role, vec![endpoint.clone()], attempts);
+                            results.insert(pool_name, (result, retries));
+                        }
+                    }
+                }
+            }
+            Ok(Served { value: None, .. }) => fallback = pools,
+            // The batch itself was refused (e.g. an invalid request): single queries may still succeed
+            Err(e) if !e.is_retryable() => {
+                log::warn!("Batch of {} pools failed ({e}), retrying one pool at a time", pools.len());
+                fallback = pools;
+            }
+            Err(e) => {
+                log::error!("Failed to fetch batch of {} pools: {e}", pools.len());
+                for pool_info in pools {
+                    let error = PoolError::new(pool_info.name.clone(), pool_info.authority, e.clone(), attempts);
+                    results.insert(pool_info.name, (Err(error), retries));
+                }
+            }
+        }
+
+        let singles = fallback.into_iter().map(|pool_info| {
+            let pool_name = pool_info.name.clone();
+            let fetch = Self::fetch_single_pool_impl(Arc::clone(&endpoints), Arc::clone(&semaphore), pool_info, retry_attempts, retry_base_delay);
+            fetch.map(|fetched| (pool_name, fetched))
+        });
+        for (pool_name, (result, single_retries)) in futures::future::join_all(singles).await {
+            results.insert(pool_name, (result, retries + single_retries));
+        }
+        results
+    }
+
+    /// Fetch pools from one scan of the stake program, with retries and endpoint failover
+    ///
+    /// With `data_slice`, the scan only returns authorities and the pools' accounts
+    /// are then fetched by pubkey. Also returns the number of RPC retries made.
+    async fn fetch_sweep_impl(
+        endpoints: Arc<EndpointPool>,
+        semaphore: Arc<Semaphore>,
+        pools: Vec<PoolInfo>,
+        data_slice: bool,
+        retry_attempts: u32,
+        retry_base_delay: Duration,
+    ) -> HashMap<String, (std::result::Result<PoolData, PoolError>, u32)> {
+        log::debug!("Fetching {} pools from one stake program sweep", pools.len());
+
+        let acquire = || async {
+            semaphore.acquire().await.map_err(|e| PoolsDataError::InternalError {
+                message: format!("Failed to acquire semaphore: {e}"),
+            })
+        };
+        let retry_strategy = || Self::retry_strategy(retry_base_delay, retry_attempts);
+        let (queries, attempts) = (AtomicU32::new(0), AtomicU32::new(0));
+        // Accounts of other authorities are dropped while the scan is parsed
+        let pool_authorities: HashSet<String> = pools.iter().map(|pool| pool.authority.clone()).collect();
+        let pool_authorities = &pool_authorities;
+        let swept: Result<(Vec<StakeAccountInfo>, Vec<String>)> = async {
+            if !data_slice {
+                let _permit = acquire().await?;
+                queries.fetch_add(1, Ordering::Relaxed);
+                let served = endpoints
+                    .call(retry_strategy(), |rpc| {
+                        attempts.fetch_add(1, Ordering::Relaxed);
+                        async move { rpc.fetch_all_stake_accounts(pool_authorities).await }
+                    })
+                    .await?;
+                return Ok((served.value, vec![served.endpoint]));
+            }
+
+            let authorities = {
+                let _permit = acquire().await?;
+                queries.fetch_add(1, Ordering::Relaxed);
+                endpoints
+                    .call(retry_strategy(), |rpc| {
+                        attempts.fetch_add(1, Ordering::Relaxed);
+                        async move { rpc.fetch_all_stake_authorities(pool_authorities).await }
+                    })
+                    .await?
+            };
+            let pubkeys: Vec<String> = authorities
+                .value
+                .into_iter()
+                .filter(|(_, authorized)| pools.iter().any(|pool| Self::matched_roles(pool, authorized).next().is_some()))
+                .map(|(pubkey, _)| pubkey)
+                .collect();
+
+            // Chunks run concurrently, bounded by the semaphore and rate limiters
+            let (endpoints, queries, attempts) = (&endpoints, &queries, &attempts);
+            let chunks = pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS).map(|chunk| async move {
+                let _permit = acquire().await?;
+                queries.fetch_add(1, Ordering::Relaxed);
+                endpoints
+                    .call(retry_strategy(), |rpc| {
+                        attempts.fetch_add(1, Ordering::Relaxed);
+                        async move { rpc.fetch_stake_accounts_by_pubkey(chunk).await }
+                    })
+                    .await
+            });
+            let mut served_by = vec![authorities.endpoint];
+            let mut accounts = Vec::with_capacity(pubkeys.len());
+            for served in futures::future::try_join_all(chunks).await? {
+                if !served_by.contains(&served.endpoint) {
+                    served_by.push(served.endpoint);
+                }
+                accounts.extend(served.value);
+            }
+            Ok((accounts, served_by))
+        }
+        .await;
+        let attempts = attempts.into_inner();
+        let retries = attempts.saturating_sub(queries.into_inner());
+
+        match swept {
+            Ok((accounts, served_by)) => {
+                let mut by_pool = Self::partition_stake_accounts(&pools, accounts);
+                pools
+                    .into_iter()
+                    .map(|pool_info| {
+                        let accounts = by_pool.remove(&pool_info.name).unwrap_or_default();
+                        let pool_name = pool_info.name.clone();
+                        let result = Self::pool_data_from_accounts(pool_info, Ok(vec![accounts]), served_by.clone(), attempts);
+                        (pool_name, (result, retries))
+                    })
+                    .collect()
+            }
+            Err(e) => {
+                log::error!("Failed to sweep stake accounts for {} pools: {e}", pools.len());
+                pools
+                    .into_iter()
+                    .map(|pool_info| {
+                        let error = PoolError::new(pool_info.name.clone(), pool_info.authority, e.clone(), attempts);
+                        (pool_info.name, (Err(error), retries))
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Roles of `pool` matched by a stake account's authorities
+    fn matched_roles<'a>(pool: &'a PoolInfo, authorized: &'a StakeAuthorized) -> impl Iterator<Item = AuthorityRole> + 'a {
+        pool.authority_role.roles().iter().copied().filter(move |role| match role {
+            AuthorityRole::Withdrawer => authorized.withdrawer == pool.authority,
+            _ => authorized.staker == pool.authority,
+        })
+    }
+
+    /// Split swept stake accounts by pool
+    ///
+    /// An account matching several pools is assigned to each; one matching both
+    /// roles of a pool appears once per role (merged by `pool_data_from_accounts`).
+    fn partition_stake_accounts(pools: &[PoolInfo], accounts: Vec<StakeAccountInfo>) -> HashMap<String, Vec<StakeAccountInfo>> {
+        let mut by_authority: HashMap<&str, Vec<&PoolInfo>> = HashMap::new();
+        for pool in pools {
+            by_authority.entry(pool.authority.as_str()).or_default().push(pool);
+        }
+
+        let mut by_pool: HashMap<String, Vec<StakeAccountInfo>> = HashMap::new();
+        for account in accounts {
+            let authorized = &account.authorized;
+            let mut candidates = by_authority.get(authorized.staker.as_str()).cloned().unwrap_or_default();
+            if authorized.withdrawer != authorized.staker {
+                candidates.extend(by_authority.get(authorized.withdrawer.as_str()).into_iter().flatten());
+            }
+
+            for pool in candidates {
+                for role in Self::matched_roles(pool, authorized) {
+                    let mut matched = account.clone();
+                    matched.matched_authority = role;
+                    by_pool.entry(pool.name.clone()).or_default().push(matched);
+                }
+            }
+        }
+        by_pool
+    }
+
+    /// Fetch data for a single pool with retries, rate limiting and endpoint failover
+    ///
+    /// Also returns the number of RPC retries made (attempts beyond one per role query).
     async fn fetch_single_pool_impl(
-        rpc_client: RpcClient,
+        endpoints: Arc<EndpointPool>,
         semaphore: Arc<Semaphore>,
         pool_info: PoolInfo,
         retry_attempts: u32,
         retry_base_delay: Duration,
-        rate_limiter: Option<Arc<governor::RateLimiter<governor::state::direct::NotKeyed, governor::state::InMemoryState, governor::clock::DefaultClock>>>,
-    ) -> std::result::Result<PoolData, PoolError> {
-        let _permit = semaphore.acquire().await.map_err(|e| {
-            PoolError::new(
-                pool_info.name.clone(),
-                pool_info.authority.clone(),
-                PoolsDataError::InternalError {
+    ) -> (std::result::Result<PoolData, PoolError>, u32) {
+        let _permit = match semaphore.acquire().await {
+            Ok(permit) => permit,
+            Err(e) => {
+                let error = PoolsDataError::InternalError {
                     message: format!("Failed to acquire semaphore: {e}"),
-                },
-                0,
-            )
-        })?;
-
-        // Apply rate limiting if configured
-        if let Some(limiter) = &rate_limiter {
-            limiter.until_ready().await;
-        }
+                };
+                return (Err(PoolError::new(pool_info.name.clone(), pool_info.authority.clone(), error, 0)), 0);
+            }
+        };
 
         log::debug!("Fetching pool: {}", pool_info.name);
 
-        #[allow(clippy::cast_possible_truncation)]
-        // Duration as_millis() to u64 is intentional for retry delays
-        let retry_strategy = ExponentialBackoff::from_millis(retry_base_delay.as_millis() as u64)
-            .max_delay(std::time::Duration::from_secs(30))
-            .take(retry_attempts as usize);
+        // One getProgramAccounts query per authority role, each routed to the
+        // healthiest endpoint and retried with failover
+        let mut result = Ok(Vec::new());
+        let mut served_by: Vec<String> = Vec::new();
+        let (mut queries, mut attempts) = (0, 0);
+        for &role in pool_info.authority_role.roles() {
+            queries += 1;
+            let retry_strategy = Self::retry_strategy(retry_base_delay, retry_attempts);
+            let authority = &pool_info.authority;
+            let accounts = endpoints
+                .call(retry_strategy, |rpc| {
+                    attempts += 1;
+                    async move { rpc.fetch_stake_accounts_for_authority(authority, role).await }
+                })
+                .await;
 
+            match accounts {
+                Ok(served) => {
+                    if !served_by.contains(&served.endpoint) {
+                        served_by.push(served.endpoint);
+                    }
+                    if let Ok(by_role) = &mut result {
+                        by_role.push(served.value);
+                    }
+                }
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        (Self::pool_data_from_accounts(pool_info, result, served_by, attempts), attempts - queries)
+    }
+
+    /// Build a pool's data from its per-role query results
+    fn pool_data_from_accounts(
+        pool_info: PoolInfo,
+        by_role: Result<Vec<Vec<StakeAccountInfo>>>,
+        served_by: Vec<String>,
+        attempts: u32,
+    ) -> std::result::Result<PoolData, PoolError> {
         let pool_name = pool_info.name.clone();
         let authority = pool_info.authority.clone();
 
-        let result = Retry::spawn(retry_strategy, || async {
-            rpc_client
-                .fetch_stake_accounts_for_authority(&pool_info.authority)
-                .await
-        })
-        .await;
-
-        match result {
+        match by_role.map(Self::merge_stake_accounts) {
             Ok(stake_accounts) => {
                 if stake_accounts.is_empty() {
                     return Err(PoolError::new(
                         pool_name,
                         authority,
-                        PoolsDataError::NoStakeAccounts { 
-                            pool_name: pool_info.name.clone() 
+                        PoolsDataError::NoStakeAccounts {
+                            pool_name: pool_info.name.clone()
                         },
-                        0,
+                        attempts,
                     ));
                 }
 
@@ -336,15 +1221,71 @@ impl PoolsDataClient {
                     validator_distribution,
                     statistics,
                     fetched_at: chrono::Utc::now(),
+                    epoch: None,
+                    served_by,
+                    spl_stake_pool: None,
+                    marinade_state: None,
+                    exchange_rate: None,
                 })
             }
             Err(e) => {
                 log::error!("Failed to fetch pool {pool_name}: {e}");
-                Err(PoolError::new(pool_name, authority, e, 0))
+                Err(PoolError::new(pool_name, authority, e, attempts))
             }
         }
     }
 
+    /// Merge per-role query results, de-duplicating accounts by pubkey
+    ///
+    /// Accounts returned by more than one role query are marked as matching both.
+    fn merge_stake_accounts(by_role: Vec<Vec<StakeAccountInfo>>) -> Vec<StakeAccountInfo> {
+        let mut merged: Vec<StakeAccountInfo> = Vec::new();
+        let mut index_by_pubkey: HashMap<String, usize> = HashMap::new();
+
+        for account in by_role.into_iter().flatten() {
+            if let Some(&index) = index_by_pubkey.get(&account.pubkey) {
+                let existing = &mut merged[index];
+                existing.matched_authority = existing.matched_authority.merge(account.matched_authority);
+            } else {
+                index_by_pubkey.insert(account.pubkey.clone(), merged.len());
+                merged.push(account);
+            }
+        }
+
+        merged
+    }
+
+    /// Run an RPC call under the client's concurrency, rate limit, retry and failover policy
+    async fn call_with_policy<T, F, Fut>(&self, operation: F) -> Result<T>
+    where
+        F: FnMut(Arc<RpcClient>) -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let _permit = self.semaphore.acquire().await.map_err(|e| PoolsDataError::InternalError {
+            message: format!("Failed to acquire semaphore: {e}"),
+        })?;
+
+        self.endpoints
+            .call(
+                Self::retry_strategy(self.config.retry_base_delay, self.config.retry_attempts),
+                operation,
+            )
+            .await
+            .map(|served| served.value)
+    }
+
+    /// Exponential backoff strategy shared by all RPC calls
+    fn retry_strategy(
+        retry_base_delay: Duration,
+        retry_attempts: u32,
+    ) -> std::iter::Take<ExponentialBackoff> {
+        #[allow(clippy::cast_possible_truncation)]
+        // Duration as_millis() to u64 is intentional for retry delays
+        ExponentialBackoff::from_millis(retry_base_delay.as_millis() as u64)
+            .max_delay(std::time::Duration::from_secs(30))
+            .take(retry_attempts as usize)
+    }
+
     /// Calculate validator distribution from stake accounts
     ///
     /// Filtering logic:
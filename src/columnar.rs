@@ -0,0 +1,300 @@
+//! Arrow and Parquet output (`parquet` feature).
+//!
+//! Three tables share the row types of the flat exports:
+//!
+//! | Table             | Columns                                                                                   |
+//! |-------------------|-------------------------------------------------------------------------------------------|
+//! | `stake_accounts`  | `pool_name`, `fetched_at`, `pubkey`, `lamports`, `stake_type`, `validator`, `stake_lamports`, `activation_epoch`, `deactivation_epoch`, `staker`, `withdrawer`, `matched_authority`, `lockup_custodian`, `lockup_epoch`, `lockup_unix_timestamp` |
+//! | `validators`      | `pool_name`, `fetched_at`, `validator`, `account_count`, `total_delegated`                 |
+//! | `pool_statistics` | `pool_name`, `authority`, `fetched_at`, account counts and lamports per state, `validator_count` |
+//!
+//! Pubkeys and names are `Utf8`, lamports and counts `UInt64` (`account_count` is `UInt32`,
+//! `lockup_unix_timestamp` is `Int64`), `fetched_at` is a UTC millisecond timestamp.
+//! Activation, deactivation and lockup epochs are null when unset on chain (`u64::MAX`); delegation
+//! columns are null for undelegated accounts.
+
+use crate::error::Result;
+use crate::export::{export_error, PoolStatisticsRow, StakeAccountRow, ValidatorRow};
+use crate::types::ProductionPoolData;
+use arrow_array::{
+    ArrayRef, Int64Array, RecordBatch, StringArray, TimestampMillisecondArray, UInt32Array, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, Utc};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn timestamp_field(name: &str) -> Field {
+    Field::new(name, DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())), false)
+}
+
+/// Schema of the `stake_accounts` table
+#[must_use]
+pub fn stake_accounts_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("pool_name", DataType::Utf8, false),
+        timestamp_field("fetched_at"),
+        Field::new("pubkey", DataType::Utf8, false),
+        Field::new("lamports", DataType::UInt64, false),
+        Field::new("stake_type", DataType::Utf8, false),
+        Field::new("validator", DataType::Utf8, true),
+        Field::new("stake_lamports", DataType::UInt64, true),
+        Field::new("activation_epoch", DataType::UInt64, true),
+        Field::new("deactivation_epoch", DataType::UInt64, true),
+        Field::new("staker", DataType::Utf8, false),
+        Field::new("withdrawer", DataType::Utf8, false),
+        Field::new("matched_authority", DataType::Utf8, false),
+        Field::new("lockup_custodian", DataType::Utf8, false),
+        Field::new("lockup_epoch", DataType::UInt64, true),
+        Field::new("lockup_unix_timestamp", DataType::Int64, false),
+    ]))
+}
+
+/// Schema of the `validators` table
+#[must_use]
+pub fn validators_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("pool_name", DataType::Utf8, false),
+        timestamp_field("fetched_at"),
+        Field::new("validator", DataType::Utf8, false),
+        Field::new("account_count", DataType::UInt32, false),
+        Field::new("total_delegated", DataType::UInt64, false),
+    ]))
+}
+
+/// Schema of the `pool_statistics` table
+#[must_use]
+pub fn pool_statistics_schema() -> SchemaRef {
+    let mut fields = vec![
+        Field::new("pool_name", DataType::Utf8, false),
+        Field::new("authority", DataType::Utf8, false),
+        timestamp_field("fetched_at"),
+    ];
+    fields.extend(
+        [
+            "total_accounts",
+            "activating_accounts",
+            "active_accounts",
+            "deactivating_accounts",
+            "deactivated_accounts",
+            "total_lamports",
+            "activating_stake_lamports",
+            "active_stake_lamports",
+            "deactivating_stake_lamports",
+            "deactivated_stake_lamports",
+            "validator_count",
+        ]
+        .into_iter()
+        .map(|name| Field::new(name, DataType::UInt64, false)),
+    );
+    Arc::new(Schema::new(fields))
+}
+
+fn strings<'a>(values: impl Iterator<Item = &'a str>) -> ArrayRef {
+    Arc::new(StringArray::from_iter_values(values))
+}
+
+fn optional_strings<'a>(values: impl Iterator<Item = Option<&'a str>>) -> ArrayRef {
+    Arc::new(values.collect::<StringArray>())
+}
+
+fn u64s(values: impl Iterator<Item = u64>) -> ArrayRef {
+    Arc::new(UInt64Array::from_iter_values(values))
+}
+
+fn optional_u64s(values: impl Iterator<Item = Option<u64>>) -> ArrayRef {
+    Arc::new(values.collect::<UInt64Array>())
+}
+
+fn counts(values: impl Iterator<Item = usize>) -> ArrayRef {
+    u64s(values.map(|value| value as u64))
+}
+
+fn timestamps(values: impl Iterator<Item = DateTime<Utc>>) -> ArrayRef {
+    Arc::new(TimestampMillisecondArray::from_iter_values(values.map(|t| t.timestamp_millis())).with_timezone("UTC"))
+}
+
+/// `stake_accounts` rows of the pools as one batch
+///
+/// # Errors
+/// Returns `ExportError` if the arrays do not match the schema.
+pub fn stake_accounts_batch<'a>(pools: impl IntoIterator<Item = &'a ProductionPoolData>) -> Result<RecordBatch> {
+    let rows: Vec<StakeAccountRow> = pools.into_iter().flat_map(StakeAccountRow::rows).collect();
+    let columns = vec![
+        strings(rows.iter().map(|r| r.pool_name.as_str())),
+        timestamps(rows.iter().map(|r| r.fetched_at)),
+        strings(rows.iter().map(|r| r.pubkey.as_str())),
+        u64s(rows.iter().map(|r| r.lamports)),
+        strings(rows.iter().map(|r| r.stake_type.as_str())),
+        optional_strings(rows.iter().map(|r| r.validator.as_deref())),
+        optional_u64s(rows.iter().map(|r| r.stake_lamports)),
+        optional_u64s(rows.iter().map(|r| r.activation_epoch)),
+        optional_u64s(rows.iter().map(|r| r.deactivation_epoch)),
+        strings(rows.iter().map(|r| r.staker.as_str())),
+        strings(rows.iter().map(|r| r.withdrawer.as_str())),
+        strings(rows.iter().map(|r| r.matched_authority.as_str())),
+        strings(rows.iter().map(|r| r.lockup_custodian.as_str())),
+        optional_u64s(rows.iter().map(|r| r.lockup_epoch)),
+        Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.lockup_unix_timestamp))),
+    ];
+    Ok(RecordBatch::try_new(stake_accounts_schema(), columns)?)
+}
+
+/// `validators` rows of the pools as one batch
+///
+/// # Errors
+/// Returns `ExportError` if the arrays do not match the schema.
+pub fn validators_batch<'a>(pools: impl IntoIterator<Item = &'a ProductionPoolData>) -> Result<RecordBatch> {
+    let rows: Vec<ValidatorRow> = pools.into_iter().flat_map(ValidatorRow::rows).collect();
+    let columns = vec![
+        strings(rows.iter().map(|r| r.pool_name.as_str())),
+        timestamps(rows.iter().map(|r| r.fetched_at)),
+        strings(rows.iter().map(|r| r.validator.as_str())),
+        Arc::new(UInt32Array::from_iter_values(rows.iter().map(|r| r.account_count))),
+        u64s(rows.iter().map(|r| r.total_delegated)),
+    ];
+    Ok(RecordBatch::try_new(validators_schema(), columns)?)
+}
+
+/// `pool_statistics` rows of the pools as one batch
+///
+/// # Errors
+/// Returns `ExportError` if the arrays do not match the schema.
+pub fn pool_statistics_batch<'a>(pools: impl IntoIterator<Item = &'a ProductionPoolData>) -> Result<RecordBatch> {
+    let rows: Vec<PoolStatisticsRow> = pools.into_iter().map(PoolStatisticsRow::from).collect();
+    let columns = vec![
+        strings(rows.iter().map(|r| r.pool_name.as_str())),
+        strings(rows.iter().map(|r| r.authority.as_str())),
+        timestamps(rows.iter().map(|r| r.fetched_at)),
+        counts(rows.iter().map(|r| r.total_accounts)),
+        counts(rows.iter().map(|r| r.activating_accounts)),
+        counts(rows.iter().map(|r| r.active_accounts)),
+        counts(rows.iter().map(|r| r.deactivating_accounts)),
+        counts(rows.iter().map(|r| r.deactivated_accounts)),
+        u64s(rows.iter().map(|r| r.total_lamports)),
+        u64s(rows.iter().map(|r| r.activating_stake_lamports)),
+        u64s(rows.iter().map(|r| r.active_stake_lamports)),
+        u64s(rows.iter().map(|r| r.deactivating_stake_lamports)),
+        u64s(rows.iter().map(|r| r.deactivated_stake_lamports)),
+        counts(rows.iter().map(|r| r.validator_count)),
+    ];
+    Ok(RecordBatch::try_new(pool_statistics_schema(), columns)?)
+}
+
+/// Write a batch as a Snappy-compressed Parquet file
+///
+/// # Errors
+/// Returns `ExportError` if encoding or writing fails.
+pub fn write_parquet<W: Write + Send>(writer: W, batch: &RecordBatch) -> Result<()> {
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(writer, batch.schema(), Some(properties))?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Hive partition value for a null column
+const NULL_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Write each table of each pool to `root/<table>/pool=<name>/epoch=<epoch>/<fetched_at>.parquet`
+///
+/// The Hive-style layout is read as partition columns by `DuckDB`, Spark and Polars.
+/// `epoch` is each pool's own fetch epoch (the Hive null partition when unknown), and
+/// pool names are percent-encoded so they cannot leave `root`. Returns the written paths.
+///
+/// # Errors
+/// Returns `ExportError` if a directory or file cannot be written.
+pub fn write_partitioned_parquet<'a>(
+    root: impl AsRef<Path>,
+    pools: impl IntoIterator<Item = &'a ProductionPoolData>,
+) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for pool in pools {
+        let pool_partition = format!("pool={}", partition_value(&pool.pool_name));
+        let epoch_partition = format!("epoch={}", pool.epoch.map_or_else(|| NULL_PARTITION.to_string(), |epoch| epoch.to_string()));
+        let file_name = format!("{}.parquet", pool.fetched_at.format("%Y%m%dT%H%M%S%.3fZ"));
+        let tables = [
+            ("stake_accounts", stake_accounts_batch([pool])?),
+            ("validators", validators_batch([pool])?),
+            ("pool_statistics", pool_statistics_batch([pool])?),
+        ];
+        for (table, batch) in &tables {
+            let dir = root.as_ref().join(table).join(&pool_partition).join(&epoch_partition);
+            std::fs::create_dir_all(&dir).map_err(export_error)?;
+            let path = dir.join(&file_name);
+            let file = std::fs::File::create(&path).map_err(export_error)?;
+            write_parquet(file, batch)?;
+            written.push(path);
+        }
+    }
+    Ok(written)
+}
+
+/// Percent-encode every byte outside `[A-Za-z0-9_-]`, as Hive escapes partition values
+fn partition_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' => char::from(byte).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pools::AuthorityRole;
+    use crate::test_support::{self, undelegated_account};
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn pool() -> ProductionPoolData {
+        let mut account = undelegated_account("acc1");
+        account.matched_authority = AuthorityRole::Withdrawer;
+        let mut data = test_support::pool("jito", vec![account]);
+        data.statistics.total_accounts = 1;
+        data
+    }
+
+    #[test]
+    fn test_batches_match_schema() {
+        let data = pool();
+        let accounts = stake_accounts_batch([&data]).unwrap();
+        assert_eq!(accounts.num_rows(), 1);
+        assert!(accounts.column_by_name("deactivation_epoch").unwrap().is_null(0));
+        assert_eq!(validators_batch([&data]).unwrap().num_rows(), 0);
+        let stats = pool_statistics_batch([&data]).unwrap();
+        let total = stats.column_by_name("total_accounts").unwrap().as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(total.value(0), 1);
+    }
+
+    #[test]
+    fn test_partitioned_parquet_roundtrip() {
+        let root = std::env::temp_dir().join(format!("pools-parquet-{}", std::process::id()));
+        let mut data = pool();
+        data.epoch = Some(700);
+        let written = write_partitioned_parquet(&root, [&data]).unwrap();
+        assert_eq!(written.len(), 3);
+        assert!(written[0].starts_with(root.join("stake_accounts").join("pool=jito").join("epoch=700")));
+
+        // Names cannot escape the root, and an unknown epoch is the null partition
+        let mut escaping = pool();
+        escaping.pool_name = "../x/y".to_string();
+        let escaped = write_partitioned_parquet(&root, [&escaping]).unwrap();
+        let expected = root.join("validators").join("pool=%2E%2E%2Fx%2Fy").join("epoch=__HIVE_DEFAULT_PARTITION__");
+        assert!(escaped[1].starts_with(expected));
+
+        let file = std::fs::File::open(&written[0]).unwrap();
+        let batches: Vec<RecordBatch> =
+            ParquetRecordBatchReaderBuilder::try_new(file).unwrap().build().unwrap().map(|batch| batch.unwrap()).collect();
+        assert_eq!(batches[0].schema().fields(), stake_accounts_schema().fields());
+        let staker = batches[0].column_by_name("matched_authority").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(staker.value(0), "withdrawer");
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
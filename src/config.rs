@@ -4,6 +4,10 @@
 //! and use cases, from conservative public RPC settings to high-performance private RPC.
 
 use crate::error::{PoolsDataError, Result};
+#[cfg(feature = "metrics")]
+use crate::metrics::PoolMetrics;
+use crate::registry::PoolRegistry;
+use crate::transport::RpcTransport;
 use governor::{Quota, RateLimiter};
 use std::sync::Arc;
 use std::time::Duration;
@@ -72,6 +76,120 @@ impl RateLimitConfig {
     }
 }
 
+/// Shared direct (non-keyed) rate limiter
+pub type SharedRateLimiter = Arc<
+    RateLimiter<
+        governor::state::direct::NotKeyed,
+        governor::state::InMemoryState,
+        governor::clock::DefaultClock,
+    >,
+>;
+
+/// Additional RPC endpoint for multi-endpoint configurations
+#[derive(Debug, Clone)]
+pub struct EndpointConfig {
+    /// RPC URL
+    pub url: String,
+    /// Display name reported in health and `served_by` (defaults to the URL host)
+    pub name: Option<String>,
+    /// Relative routing weight (higher is preferred)
+    pub weight: u32,
+    /// Requests per second for this endpoint (`None` uses the builder's rate limit)
+    pub rate_limit: Option<u32>,
+}
+
+impl EndpointConfig {
+    /// Create an endpoint with weight 1 and the builder's rate limit
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            name: None,
+            weight: 1,
+            rate_limit: None,
+        }
+    }
+
+    /// Set display name
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set routing weight
+    #[must_use]
+    pub const fn weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Set rate limit in requests per second
+    #[must_use]
+    pub const fn rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.rate_limit = Some(requests_per_second);
+        self
+    }
+
+    /// Use the rate limit of the provider preset detected from the URL
+    #[must_use]
+    pub fn auto_config(mut self) -> Self {
+        self.rate_limit = PoolsDataClientBuilder::new().auto_config(&self.url).rate_limit;
+        self
+    }
+}
+
+/// Resolved endpoint settings
+#[derive(Debug)]
+pub struct EndpointSettings {
+    /// RPC URL
+    pub url: String,
+    /// Display name (never contains the URL path or query)
+    pub name: String,
+    /// Relative routing weight
+    pub weight: u32,
+    /// Per-endpoint rate limiter
+    pub rate_limiter: Option<SharedRateLimiter>,
+}
+
+/// Encoding requested for stake accounts in `getProgramAccounts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StakeAccountEncoding {
+    /// RPC-side parsed JSON (largest payload, not supported by every provider)
+    #[default]
+    JsonParsed,
+    /// Raw account data decoded in-crate
+    Base64,
+    /// Zstd-compressed raw account data (requires the `zstd` feature)
+    Base64Zstd,
+}
+
+impl StakeAccountEncoding {
+    /// Value of the RPC `encoding` parameter
+    #[must_use]
+    pub const fn as_rpc_str(self) -> &'static str {
+        match self {
+            Self::JsonParsed => "jsonParsed",
+            Self::Base64 => "base64",
+            Self::Base64Zstd => "base64+zstd",
+        }
+    }
+}
+
+/// How stake accounts are queried for a fetch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FetchMode {
+    /// One `memcmp`-filtered `getProgramAccounts` query per pool authority and role
+    #[default]
+    PerPool,
+    /// One scan of every stake account, partitioned locally by staker and withdrawer authority
+    ///
+    /// Faster than per-pool queries for full-registry snapshots on private or
+    /// dedicated nodes; public endpoints usually refuse unfiltered scans. With
+    /// `data_slice`, the scan only returns the authority bytes of each account and
+    /// the matching accounts are then fetched with `getMultipleAccounts`.
+    Sweep { data_slice: bool },
+}
+
 /// Configuration builder for `PoolsDataClient`
 #[derive(Debug, Clone)]
 pub struct PoolsDataClientBuilder {
@@ -81,6 +199,18 @@ pub struct PoolsDataClientBuilder {
     retry_base_delay_ms: u64,
     timeout_secs: u64,
     max_concurrent: usize,
+    new_rate_activation_epoch: Option<u64>,
+    account_encoding: StakeAccountEncoding,
+    endpoints: Vec<EndpointConfig>,
+    include_pool_state: bool,
+    include_exchange_rates: bool,
+    registry: Option<PoolRegistry>,
+    cache_ttl: Duration,
+    transport: Option<Arc<dyn RpcTransport>>,
+    batch_size: usize,
+    fetch_mode: FetchMode,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<PoolMetrics>>,
 }
 
 impl Default for PoolsDataClientBuilder {
@@ -92,6 +222,18 @@ impl Default for PoolsDataClientBuilder {
             retry_base_delay_ms: DefaultConfig::RETRY_BASE_DELAY_MS,
             timeout_secs: DefaultConfig::REQUEST_TIMEOUT_SECS,
             max_concurrent: DefaultConfig::MAX_CONCURRENT_REQUESTS,
+            new_rate_activation_epoch: None,
+            account_encoding: StakeAccountEncoding::JsonParsed,
+            endpoints: Vec::new(),
+            include_pool_state: false,
+            include_exchange_rates: false,
+            registry: None,
+            cache_ttl: Duration::from_secs(DefaultConfig::CACHE_TTL_SECS),
+            transport: None,
+            batch_size: 1,
+            fetch_mode: FetchMode::PerPool,
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
     }
 }
@@ -152,6 +294,118 @@ impl PoolsDataClientBuilder {
         self
     }
 
+    /// Override the epoch from which the reduced (9%) warmup/cooldown rate applies
+    ///
+    /// Used by effective stake calculations. When unset, the epoch is resolved from
+    /// the `reduce_stake_warmup_cooldown` feature account, as the runtime does.
+    #[must_use]
+    pub const fn new_warmup_cooldown_rate_epoch(mut self, epoch: u64) -> Self {
+        self.new_rate_activation_epoch = Some(epoch);
+        self
+    }
+
+    /// Set the stake account encoding used for `getProgramAccounts`
+    ///
+    /// Binary encodings are several times smaller on the wire and work with
+    /// providers that disable `jsonParsed` for `getProgramAccounts`.
+    #[must_use]
+    pub const fn account_encoding(mut self, encoding: StakeAccountEncoding) -> Self {
+        self.account_encoding = encoding;
+        self
+    }
+
+    /// Also fetch on-chain pool program state (SPL stake pool or Marinade state)
+    ///
+    /// Costs a few extra RPC calls per fetch; disabled by default.
+    #[must_use]
+    pub const fn include_pool_state(mut self, include: bool) -> Self {
+        self.include_pool_state = include;
+        self
+    }
+
+    /// Also fetch LST mint supply and report each pool's SOL/LST exchange rate
+    ///
+    /// One `getTokenSupply` call per pool with a known LST mint; disabled by default.
+    /// Combine with [`Self::include_pool_state`] to use on-chain pool lamports.
+    #[must_use]
+    pub const fn include_exchange_rates(mut self, include: bool) -> Self {
+        self.include_exchange_rates = include;
+        self
+    }
+
+    /// Use a custom pool registry instead of the embedded pool list
+    #[must_use]
+    pub fn registry(mut self, registry: PoolRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Record fetch latency, retries, errors and pool stake gauges in `metrics`
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    pub fn metrics(mut self, metrics: Arc<PoolMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// How long `fetch_pools_cached` serves a pool's stake accounts before refetching
+    ///
+    /// A zero TTL only coalesces concurrent fetches of the same pool.
+    #[must_use]
+    pub const fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Send RPC requests through a custom transport instead of HTTP
+    ///
+    /// Every endpoint uses the transport; their URLs only name them. Use
+    /// [`MockTransport`](crate::MockTransport) to test without a network.
+    #[must_use]
+    pub fn transport(mut self, transport: Arc<dyn RpcTransport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Group up to `size` `getProgramAccounts` queries into one JSON-RPC batch request
+    ///
+    /// Fetching many pools then costs a few HTTP requests against the rate limit
+    /// instead of one per pool. Endpoints that reject batches are remembered and
+    /// queried one request per pool. 1 (the default) disables batching.
+    #[must_use]
+    pub const fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size;
+        self
+    }
+
+    /// Query each pool separately (the default) or sweep every stake account at once
+    ///
+    /// [`FetchMode::Sweep`] overrides [`Self::batch_size`]. The scan runs under
+    /// [`Self::timeout`], which should leave room for the whole stake program.
+    #[must_use]
+    pub const fn fetch_mode(mut self, mode: FetchMode) -> Self {
+        self.fetch_mode = mode;
+        self
+    }
+
+    /// Add a fallback RPC endpoint
+    ///
+    /// The URL passed to [`Self::build`] is the primary endpoint (weight 1).
+    /// Each attempt is routed to the healthiest endpoint by weight, latency and
+    /// recent errors, failing over on retryable errors.
+    #[must_use]
+    pub fn endpoint(mut self, endpoint: EndpointConfig) -> Self {
+        self.endpoints.push(endpoint);
+        self
+    }
+
+    /// Add several fallback RPC endpoints
+    #[must_use]
+    pub fn endpoints(mut self, endpoints: impl IntoIterator<Item = EndpointConfig>) -> Self {
+        self.endpoints.extend(endpoints);
+        self
+    }
+
     /// Use preset configuration for private/premium RPC endpoints
     #[must_use]
     pub const fn private_rpc_config(mut self) -> Self {
@@ -283,7 +537,15 @@ impl PoolsDataClientBuilder {
     /// - Invalid RPC URL format
     /// - Timeout is 0 or greater than 300 seconds
     /// - Max concurrent requests is 0 or greater than 100
+    /// - Batch size is 0 or greater than 100
+    /// - `base64+zstd` encoding without the `zstd` feature
     pub fn build(self, rpc_url: &str) -> Result<ClientConfig> {
+        if self.account_encoding == StakeAccountEncoding::Base64Zstd && !cfg!(feature = "zstd") {
+            return Err(PoolsDataError::ConfigurationError {
+                message: "base64+zstd encoding requires the `zstd` feature".to_string(),
+            });
+        }
+
         if self.retry_attempts > 10 {
             return Err(PoolsDataError::ConfigurationError {
                 message: "Retry attempts cannot exceed 10".to_string(),
@@ -302,24 +564,42 @@ impl PoolsDataClientBuilder {
             });
         }
 
-        let rate_limiter = if let Some(rps) = self.rate_limit {
-            if rps == 0 || rps > 1000 {
+        if self.batch_size == 0 || self.batch_size > 100 {
+            return Err(PoolsDataError::ConfigurationError {
+                message: "Batch size must be between 1 and 100".to_string(),
+            });
+        }
+
+        let rate_limiter = self.rate_limit.map(Self::create_rate_limiter).transpose()?;
+
+        let mut endpoints = vec![EndpointSettings {
+            url: rpc_url.to_string(),
+            name: endpoint_name(rpc_url),
+            weight: 1,
+            rate_limiter: rate_limiter.clone(),
+        }];
+        for endpoint in &self.endpoints {
+            if endpoint.url.trim().is_empty() {
                 return Err(PoolsDataError::ConfigurationError {
-                    message: "Rate limit must be between 1 and 1000 requests per second"
-                        .to_string(),
+                    message: "Endpoint URL cannot be empty".to_string(),
                 });
             }
-            match std::num::NonZeroU32::new(rps) {
-                Some(nonzero_rps) => Some(Arc::new(RateLimiter::direct(Quota::per_second(nonzero_rps)))),
-                None => {
-                    return Err(PoolsDataError::ConfigurationError {
-                        message: "Rate limit must be greater than 0".to_string(),
-                    })
-                }
+            if endpoint.weight == 0 {
+                return Err(PoolsDataError::ConfigurationError {
+                    message: format!("Endpoint weight must be greater than 0: {}", endpoint.url),
+                });
             }
-        } else {
-            None
-        };
+            let endpoint_limiter = match endpoint.rate_limit {
+                Some(rps) => Some(Self::create_rate_limiter(rps)?),
+                None => self.rate_limit.map(Self::create_rate_limiter).transpose()?,
+            };
+            endpoints.push(EndpointSettings {
+                url: endpoint.url.clone(),
+                name: endpoint.name.clone().unwrap_or_else(|| endpoint_name(&endpoint.url)),
+                weight: endpoint.weight,
+                rate_limiter: endpoint_limiter,
+            });
+        }
 
         Ok(ClientConfig {
             rpc_url: rpc_url.to_string(),
@@ -328,25 +608,77 @@ impl PoolsDataClientBuilder {
             retry_base_delay: Duration::from_millis(self.retry_base_delay_ms),
             timeout: Duration::from_secs(self.timeout_secs),
             max_concurrent: self.max_concurrent,
+            new_rate_activation_epoch: self.new_rate_activation_epoch,
+            account_encoding: self.account_encoding,
+            endpoints,
+            include_pool_state: self.include_pool_state,
+            include_exchange_rates: self.include_exchange_rates,
+            registry: Arc::new(self.registry.unwrap_or_default()),
+            cache_ttl: self.cache_ttl,
+            transport: self.transport,
+            batch_size: self.batch_size,
+            fetch_mode: self.fetch_mode,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
         })
     }
+
+    /// Create a rate limiter, validating the requests per second
+    fn create_rate_limiter(rps: u32) -> Result<SharedRateLimiter> {
+        if rps == 0 || rps > 1000 {
+            return Err(PoolsDataError::ConfigurationError {
+                message: "Rate limit must be between 1 and 1000 requests per second".to_string(),
+            });
+        }
+        std::num::NonZeroU32::new(rps)
+            .map(|nonzero_rps| Arc::new(RateLimiter::direct(Quota::per_second(nonzero_rps))))
+            .ok_or_else(|| PoolsDataError::ConfigurationError {
+                message: "Rate limit must be greater than 0".to_string(),
+            })
+    }
+}
+
+/// Default endpoint display name: URL host (and port), never path or query (which may hold API keys)
+fn endpoint_name(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|parsed| {
+            parsed.host_str().map(|host| match parsed.port() {
+                Some(port) => format!("{host}:{port}"),
+                None => host.to_string(),
+            })
+        })
+        .unwrap_or_else(|| "rpc".to_string())
 }
 
 /// Internal configuration for the client
 #[derive(Debug)]
 pub struct ClientConfig {
     pub rpc_url: String,
-    pub rate_limiter: Option<
-        Arc<RateLimiter<
-            governor::state::direct::NotKeyed,
-            governor::state::InMemoryState,
-            governor::clock::DefaultClock,
-        >>,
-    >,
+    pub rate_limiter: Option<SharedRateLimiter>,
     pub retry_attempts: u32,
     pub retry_base_delay: Duration,
     pub timeout: Duration,
     pub max_concurrent: usize,
+    pub new_rate_activation_epoch: Option<u64>,
+    pub account_encoding: StakeAccountEncoding,
+    /// All endpoints, primary first
+    pub endpoints: Vec<EndpointSettings>,
+    pub include_pool_state: bool,
+    pub include_exchange_rates: bool,
+    /// Pools known to the client
+    pub registry: Arc<PoolRegistry>,
+    /// TTL of the response cache
+    pub cache_ttl: Duration,
+    /// Custom RPC transport shared by all endpoints (HTTP when `None`)
+    pub transport: Option<Arc<dyn RpcTransport>>,
+    /// Maximum `getProgramAccounts` queries per JSON-RPC batch (1 = no batching)
+    pub batch_size: usize,
+    /// Per-pool queries or a single stake program sweep
+    pub fetch_mode: FetchMode,
+    /// Metrics recorded by the client
+    #[cfg(feature = "metrics")]
+    pub metrics: Option<Arc<PoolMetrics>>,
 }
 
 /// Default configuration optimized for public Solana RPC
@@ -367,6 +699,9 @@ impl DefaultConfig {
 
     /// Request timeout - getProgramAccounts can be slow
     pub const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+    /// How long cached pool data is served
+    pub const CACHE_TTL_SECS: u64 = 60;
 }
 
 /// Configuration optimized for private/premium RPC endpoints
@@ -504,6 +839,74 @@ mod tests {
             .build("https://test.com");
 
         assert!(result.is_err());
+
+        assert!(PoolsDataClientBuilder::new().batch_size(0).build("https://test.com").is_err());
+        assert!(PoolsDataClientBuilder::new().batch_size(101).build("https://test.com").is_err());
+    }
+
+    #[test]
+    fn test_account_encoding() {
+        let config = PoolsDataClientBuilder::new().build("https://test.com").unwrap();
+        assert_eq!(config.account_encoding, StakeAccountEncoding::JsonParsed);
+
+        let config = PoolsDataClientBuilder::new()
+            .account_encoding(StakeAccountEncoding::Base64)
+            .build("https://test.com")
+            .unwrap();
+        assert_eq!(config.account_encoding, StakeAccountEncoding::Base64);
+
+        let zstd = PoolsDataClientBuilder::new()
+            .account_encoding(StakeAccountEncoding::Base64Zstd)
+            .build("https://test.com");
+        assert_eq!(zstd.is_ok(), cfg!(feature = "zstd"));
+    }
+
+    #[test]
+    fn test_multiple_endpoints() {
+        let config = PoolsDataClientBuilder::new()
+            .rate_limit(5)
+            .endpoint(EndpointConfig::new("https://backup.example.com/?api-key=secret").weight(3))
+            .endpoint(
+                EndpointConfig::new("https://mainnet.helius-rpc.com/?api-key=secret")
+                    .name("helius")
+                    .auto_config(),
+            )
+            .build("http://localhost:8899")
+            .unwrap();
+
+        assert_eq!(config.endpoints.len(), 3);
+        assert_eq!(config.endpoints[0].name, "localhost:8899");
+        assert_eq!(config.endpoints[1].name, "backup.example.com");
+        assert_eq!(config.endpoints[1].weight, 3);
+        assert!(config.endpoints[1].rate_limiter.is_some());
+        assert_eq!(config.endpoints[2].name, "helius");
+
+        let invalid = PoolsDataClientBuilder::new()
+            .endpoint(EndpointConfig::new("https://backup.example.com").weight(0))
+            .build("https://test.com");
+        assert!(invalid.is_err());
+
+        let invalid = PoolsDataClientBuilder::new()
+            .endpoint(EndpointConfig::new("https://backup.example.com").rate_limit(0))
+            .build("https://test.com");
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_fetch_mode() {
+        let config = PoolsDataClientBuilder::new().build("https://test.com").unwrap();
+        assert_eq!(config.fetch_mode, FetchMode::PerPool);
+
+        // Presets never switch to a full stake program scan
+        let config = PoolsDataClientBuilder::new().enterprise_config().build("https://test.com").unwrap();
+        assert_eq!(config.fetch_mode, FetchMode::PerPool);
+
+        let config = PoolsDataClientBuilder::new()
+            .enterprise_config()
+            .fetch_mode(FetchMode::Sweep { data_slice: true })
+            .build("https://test.com")
+            .unwrap();
+        assert_eq!(config.fetch_mode, FetchMode::Sweep { data_slice: true });
     }
 
     #[test]
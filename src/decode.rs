@@ -0,0 +1,278 @@
+//! Binary account data decoding.
+//!
+//! This module decodes raw (base64 / base64+zstd) account data returned by RPC
+//! into the library's types, without depending on the Solana SDK.
+
+use crate::error::{PoolsDataError, Result};
+use crate::pools::AuthorityRole;
+use crate::types::{StakeAccountInfo, StakeAuthorized, StakeDelegation, StakeLockup};
+use base64::Engine;
+
+/// Size of a stake account (`StakeStateV2`)
+pub const STAKE_ACCOUNT_SIZE: usize = 200;
+
+/// `StakeStateV2` discriminants
+const STAKE_STATE_UNINITIALIZED: u32 = 0;
+const STAKE_STATE_INITIALIZED: u32 = 1;
+const STAKE_STATE_STAKE: u32 = 2;
+const STAKE_STATE_REWARDS_POOL: u32 = 3;
+
+/// Sequential little-endian reader over account data
+pub(crate) struct ByteReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    /// Create a reader positioned at the start of `data`
+    pub(crate) const fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    /// Take the next `len` bytes
+    pub(crate) fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.offset.checked_add(len).filter(|end| *end <= self.data.len());
+        let Some(end) = end else {
+            return Err(PoolsDataError::ParseError {
+                message: format!(
+                    "Account data truncated: need {} bytes at offset {}, have {}",
+                    len,
+                    self.offset,
+                    self.data.len()
+                ),
+            });
+        };
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    /// Read a fixed-size byte array
+    pub(crate) fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<u64> {
+        self.read_array().map(u64::from_le_bytes)
+    }
+
+    pub(crate) fn read_i64(&mut self) -> Result<i64> {
+        self.read_array().map(i64::from_le_bytes)
+    }
+
+    pub(crate) fn read_f64(&mut self) -> Result<f64> {
+        self.read_array().map(f64::from_le_bytes)
+    }
+
+    /// Read a 32-byte public key as a base58 string
+    pub(crate) fn read_pubkey(&mut self) -> Result<String> {
+        self.read_bytes(32).map(|bytes| bs58::encode(bytes).into_string())
+    }
+}
+
+/// Decode account data returned with a binary encoding
+///
+/// Supports `base64`, and `base64+zstd` when built with the `zstd` feature.
+///
+/// # Errors
+///
+/// Returns `PoolsDataError::ParseError` for invalid data or unsupported encodings.
+pub(crate) fn decode_account_data(data: &str, encoding: &str) -> Result<Vec<u8>> {
+    let decode_base64 = |data: &str| {
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| PoolsDataError::ParseError {
+                message: format!("Invalid base64 account data: {e}"),
+            })
+    };
+
+    match encoding {
+        "base64" => decode_base64(data),
+        #[cfg(feature = "zstd")]
+        "base64+zstd" => zstd::decode_all(decode_base64(data)?.as_slice()).map_err(|e| {
+            PoolsDataError::ParseError {
+                message: format!("Invalid zstd account data: {e}"),
+            }
+        }),
+        _ => Err(PoolsDataError::ParseError {
+            message: format!("Unsupported account data encoding: {encoding}"),
+        }),
+    }
+}
+
+/// Decode a 200-byte `StakeStateV2` account into `StakeAccountInfo`
+///
+/// Layout: `u32` state tag, Meta (rent exempt reserve, staker, withdrawer, lockup),
+/// then for delegated accounts Delegation (voter, stake, activation/deactivation epoch,
+/// warmup/cooldown rate), credits observed and a stake flags byte.
+///
+/// # Errors
+///
+/// Returns `PoolsDataError::InvalidStakeData` for uninitialized or reward pool accounts,
+/// and `PoolsDataError::ParseError` for truncated data.
+pub(crate) fn decode_stake_account(pubkey: String, lamports: u64, data: &[u8]) -> Result<StakeAccountInfo> {
+    if data.len() != STAKE_ACCOUNT_SIZE {
+        return Err(PoolsDataError::InvalidStakeData {
+            message: format!(
+                "Invalid stake account space: {} (expected {STAKE_ACCOUNT_SIZE})",
+                data.len()
+            ),
+        });
+    }
+
+    let mut reader = ByteReader::new(data);
+    let state = reader.read_u32()?;
+    match state {
+        STAKE_STATE_INITIALIZED | STAKE_STATE_STAKE => {}
+        STAKE_STATE_UNINITIALIZED | STAKE_STATE_REWARDS_POOL => {
+            return Err(PoolsDataError::InvalidStakeData {
+                message: format!("Stake account is not initialized (state {state})"),
+            });
+        }
+        _ => {
+            return Err(PoolsDataError::InvalidStakeData {
+                message: format!("Unknown stake account state: {state}"),
+            });
+        }
+    }
+
+    let rent_exempt_reserve = reader.read_u64()?;
+    let authorized = StakeAuthorized {
+        staker: reader.read_pubkey()?,
+        withdrawer: reader.read_pubkey()?,
+    };
+    let lockup = StakeLockup {
+        unix_timestamp: reader.read_i64()?,
+        epoch: reader.read_u64()?,
+        custodian: reader.read_pubkey()?,
+    };
+
+    let delegation = if state == STAKE_STATE_STAKE {
+        let voter = reader.read_pubkey()?;
+        let stake = reader.read_u64()?;
+        let activation_epoch = reader.read_u64()?;
+        let deactivation_epoch = reader.read_u64()?;
+        let warmup_cooldown_rate = reader.read_f64()?;
+        let last_epoch_credits_cumulative = reader.read_u64()?;
+        // Stake flags are not part of StakeAccountInfo; read to validate the layout
+        let _stake_flags = reader.read_u8()?;
+
+        Some(StakeDelegation {
+            voter,
+            stake,
+            activation_epoch,
+            deactivation_epoch,
+            last_epoch_credits_cumulative,
+            warmup_cooldown_rate,
+        })
+    } else {
+        None
+    };
+
+    Ok(StakeAccountInfo {
+        pubkey,
+        lamports,
+        rent_exempt_reserve,
+        delegation,
+        authorized,
+        lockup,
+        matched_authority: AuthorityRole::Staker,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn pubkey_bytes(seed: u8) -> [u8; 32] {
+        [seed; 32]
+    }
+
+    pub(crate) fn encode_stake_account(state: u32) -> Vec<u8> {
+        let mut data = Vec::with_capacity(STAKE_ACCOUNT_SIZE);
+        data.extend_from_slice(&state.to_le_bytes());
+        data.extend_from_slice(&2_282_880u64.to_le_bytes());
+        data.extend_from_slice(&pubkey_bytes(1));
+        data.extend_from_slice(&pubkey_bytes(2));
+        data.extend_from_slice(&(-5i64).to_le_bytes());
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.extend_from_slice(&pubkey_bytes(3));
+        if state == STAKE_STATE_STAKE {
+            data.extend_from_slice(&pubkey_bytes(4));
+            data.extend_from_slice(&5_000_000_000u64.to_le_bytes());
+            data.extend_from_slice(&100u64.to_le_bytes());
+            data.extend_from_slice(&u64::MAX.to_le_bytes());
+            data.extend_from_slice(&0.25f64.to_le_bytes());
+            data.extend_from_slice(&1234u64.to_le_bytes());
+            data.push(0);
+        }
+        data.resize(STAKE_ACCOUNT_SIZE, 0);
+        data
+    }
+
+    #[test]
+    fn test_decode_delegated_stake_account() {
+        let data = encode_stake_account(STAKE_STATE_STAKE);
+        let account = decode_stake_account("acct".to_string(), 5_002_282_880, &data).unwrap();
+
+        assert_eq!(account.pubkey, "acct");
+        assert_eq!(account.lamports, 5_002_282_880);
+        assert_eq!(account.rent_exempt_reserve, 2_282_880);
+        assert_eq!(account.authorized.staker, bs58::encode(pubkey_bytes(1)).into_string());
+        assert_eq!(account.authorized.withdrawer, bs58::encode(pubkey_bytes(2)).into_string());
+        assert_eq!(account.lockup.unix_timestamp, -5);
+        assert_eq!(account.lockup.epoch, 7);
+        assert_eq!(account.lockup.custodian, bs58::encode(pubkey_bytes(3)).into_string());
+
+        let delegation = account.delegation.unwrap();
+        assert_eq!(delegation.voter, bs58::encode(pubkey_bytes(4)).into_string());
+        assert_eq!(delegation.stake, 5_000_000_000);
+        assert_eq!(delegation.activation_epoch, 100);
+        assert_eq!(delegation.deactivation_epoch, u64::MAX);
+        assert!((delegation.warmup_cooldown_rate - 0.25).abs() < f64::EPSILON);
+        assert_eq!(delegation.last_epoch_credits_cumulative, 1234);
+    }
+
+    #[test]
+    fn test_decode_initialized_stake_account() {
+        let data = encode_stake_account(STAKE_STATE_INITIALIZED);
+        let account = decode_stake_account("acct".to_string(), 2_282_880, &data).unwrap();
+        assert!(account.delegation.is_none());
+        assert_eq!(account.lockup.epoch, 7);
+    }
+
+    #[test]
+    fn test_decode_invalid_stake_accounts() {
+        let uninitialized = encode_stake_account(STAKE_STATE_UNINITIALIZED);
+        assert!(decode_stake_account("a".to_string(), 0, &uninitialized).is_err());
+
+        let truncated = &encode_stake_account(STAKE_STATE_STAKE)[..120];
+        assert!(decode_stake_account("a".to_string(), 0, truncated).is_err());
+    }
+
+    #[test]
+    fn test_decode_account_data_encodings() {
+        assert_eq!(decode_account_data("AQI=", "base64").unwrap(), vec![1, 2]);
+        assert!(decode_account_data("AQI=", "base58").is_err());
+        assert!(decode_account_data("not base64!", "base64").is_err());
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_decode_account_data_zstd() {
+        let data = encode_stake_account(STAKE_STATE_STAKE);
+        let compressed = zstd::encode_all(data.as_slice(), 3).unwrap();
+        let encoded = base64::engine::general_purpose::STANDARD.encode(compressed);
+        assert_eq!(decode_account_data(&encoded, "base64+zstd").unwrap(), data);
+    }
+}
@@ -0,0 +1,367 @@
+//! Differences between two fetches of the same pool.
+//!
+//! Accounts are matched by pubkey. All lists are sorted by pubkey (or vote account)
+//! so diffs of the same inputs serialize identically.
+
+use crate::types::{PoolData, PoolStatistics, ProductionPoolData, ProductionStakeAccountInfo, ProductionStakeLockup};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Balance or delegated stake change of an account present in both fetches
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakeChange {
+    /// Stake account pubkey
+    pub pubkey: String,
+    /// Validator in the current fetch
+    pub validator: Option<String>,
+    /// Balance before
+    pub lamports_before: u64,
+    /// Balance after
+    pub lamports_after: u64,
+    /// `lamports_after - lamports_before`
+    pub lamports_delta: i64,
+    /// Delegated stake before (0 if undelegated)
+    pub stake_before: u64,
+    /// Delegated stake after (0 if undelegated)
+    pub stake_after: u64,
+    /// `stake_after - stake_before`
+    pub stake_delta: i64,
+}
+
+/// Account whose delegation points to a different validator than before
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DelegationMove {
+    /// Stake account pubkey
+    pub pubkey: String,
+    /// Previous validator (`None` if the account was undelegated)
+    pub from_validator: Option<String>,
+    /// Current validator
+    pub to_validator: Option<String>,
+    /// Delegated stake after the move
+    pub stake_lamports: u64,
+}
+
+/// Staker or withdrawer change of an account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorityChange {
+    /// Stake account pubkey
+    pub pubkey: String,
+    /// Staker before
+    pub staker_before: String,
+    /// Staker after
+    pub staker_after: String,
+    /// Withdrawer before
+    pub withdrawer_before: String,
+    /// Withdrawer after
+    pub withdrawer_after: String,
+}
+
+/// Lockup change of an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockupChange {
+    /// Stake account pubkey
+    pub pubkey: String,
+    /// Lockup before
+    pub before: ProductionStakeLockup,
+    /// Lockup after
+    pub after: ProductionStakeLockup,
+}
+
+/// Change of every `PoolStatistics` field (current minus previous)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatisticsDelta {
+    /// Total accounts
+    pub total_accounts: i64,
+    /// Activating accounts
+    pub activating_accounts: i64,
+    /// Active accounts
+    pub active_accounts: i64,
+    /// Deactivating accounts
+    pub deactivating_accounts: i64,
+    /// Deactivated accounts
+    pub deactivated_accounts: i64,
+    /// Total lamports
+    pub total_lamports: i64,
+    /// Activating stake
+    pub activating_stake_lamports: i64,
+    /// Active stake
+    pub active_stake_lamports: i64,
+    /// Deactivating stake
+    pub deactivating_stake_lamports: i64,
+    /// Deactivated stake
+    pub deactivated_stake_lamports: i64,
+    /// Unique validators
+    pub validator_count: i64,
+}
+
+impl StatisticsDelta {
+    /// Difference between two statistics
+    #[must_use]
+    pub fn between(previous: &PoolStatistics, current: &PoolStatistics) -> Self {
+        Self {
+            total_accounts: delta(previous.total_accounts as u64, current.total_accounts as u64),
+            activating_accounts: delta(previous.activating_accounts as u64, current.activating_accounts as u64),
+            active_accounts: delta(previous.active_accounts as u64, current.active_accounts as u64),
+            deactivating_accounts: delta(previous.deactivating_accounts as u64, current.deactivating_accounts as u64),
+            deactivated_accounts: delta(previous.deactivated_accounts as u64, current.deactivated_accounts as u64),
+            total_lamports: delta(previous.total_lamports, current.total_lamports),
+            activating_stake_lamports: delta(previous.activating_stake_lamports, current.activating_stake_lamports),
+            active_stake_lamports: delta(previous.active_stake_lamports, current.active_stake_lamports),
+            deactivating_stake_lamports: delta(previous.deactivating_stake_lamports, current.deactivating_stake_lamports),
+            deactivated_stake_lamports: delta(previous.deactivated_stake_lamports, current.deactivated_stake_lamports),
+            validator_count: delta(previous.validator_count as u64, current.validator_count as u64),
+        }
+    }
+}
+
+/// Everything that changed in a pool between two fetches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolDiff {
+    /// Pool name (of the current fetch)
+    pub pool_name: String,
+    /// When the previous data was fetched
+    pub previous_fetched_at: DateTime<Utc>,
+    /// When the current data was fetched
+    pub current_fetched_at: DateTime<Utc>,
+    /// Accounts only in the current fetch
+    pub added_accounts: Vec<ProductionStakeAccountInfo>,
+    /// Accounts only in the previous fetch
+    pub removed_accounts: Vec<ProductionStakeAccountInfo>,
+    /// Accounts whose balance or delegated stake changed
+    pub stake_changes: Vec<StakeChange>,
+    /// Validators delegated to now but not before
+    pub validators_entered: Vec<String>,
+    /// Validators delegated to before but not now
+    pub validators_left: Vec<String>,
+    /// Accounts delegated to a different validator
+    pub moved_delegations: Vec<DelegationMove>,
+    /// Accounts whose staker or withdrawer changed
+    pub authority_changes: Vec<AuthorityChange>,
+    /// Accounts whose lockup changed
+    pub lockup_changes: Vec<LockupChange>,
+    /// Per-state account and lamport deltas
+    pub statistics_delta: StatisticsDelta,
+}
+
+impl PoolDiff {
+    /// Compare a previous and a current fetch
+    ///
+    /// Validators entered/left are taken from the delegations of all stake accounts.
+    #[must_use]
+    pub fn between(previous: &ProductionPoolData, current: &ProductionPoolData) -> Self {
+        let before: BTreeMap<&str, &ProductionStakeAccountInfo> =
+            previous.stake_accounts.iter().map(|account| (account.pubkey.as_str(), account)).collect();
+        let after: BTreeMap<&str, &ProductionStakeAccountInfo> =
+            current.stake_accounts.iter().map(|account| (account.pubkey.as_str(), account)).collect();
+
+        let mut diff = Self {
+            pool_name: current.pool_name.clone(),
+            previous_fetched_at: previous.fetched_at,
+            current_fetched_at: current.fetched_at,
+            added_accounts: after
+                .iter()
+                .filter(|(pubkey, _)| !before.contains_key(*pubkey))
+                .map(|(_, account)| (*account).clone())
+                .collect(),
+            removed_accounts: before
+                .iter()
+                .filter(|(pubkey, _)| !after.contains_key(*pubkey))
+                .map(|(_, account)| (*account).clone())
+                .collect(),
+            stake_changes: Vec::new(),
+            validators_entered: Vec::new(),
+            validators_left: Vec::new(),
+            moved_delegations: Vec::new(),
+            authority_changes: Vec::new(),
+            lockup_changes: Vec::new(),
+            statistics_delta: StatisticsDelta::between(&previous.statistics, &current.statistics),
+        };
+
+        for (pubkey, new) in &after {
+            let Some(old) = before.get(pubkey) else {
+                continue;
+            };
+            diff.compare_account(old, new);
+        }
+
+        let validators_before = validators(&previous.stake_accounts);
+        let validators_after = validators(&current.stake_accounts);
+        diff.validators_entered = validators_after.difference(&validators_before).map(|v| (*v).to_string()).collect();
+        diff.validators_left = validators_before.difference(&validators_after).map(|v| (*v).to_string()).collect();
+
+        diff
+    }
+
+    /// Compare the debug format of a previous and a current fetch
+    #[must_use]
+    pub fn between_debug(previous: &PoolData, current: &PoolData) -> Self {
+        Self::between(&previous.into(), &current.into())
+    }
+
+    fn compare_account(&mut self, old: &ProductionStakeAccountInfo, new: &ProductionStakeAccountInfo) {
+        let old_validator = old.delegation.as_ref().map(|d| d.validator.clone());
+        let new_validator = new.delegation.as_ref().map(|d| d.validator.clone());
+        let old_stake = old.delegation.as_ref().map_or(0, |d| d.stake_lamports);
+        let new_stake = new.delegation.as_ref().map_or(0, |d| d.stake_lamports);
+
+        if old.lamports != new.lamports || old_stake != new_stake {
+            self.stake_changes.push(StakeChange {
+                pubkey: new.pubkey.clone(),
+                validator: new_validator.clone(),
+                lamports_before: old.lamports,
+                lamports_after: new.lamports,
+                lamports_delta: delta(old.lamports, new.lamports),
+                stake_before: old_stake,
+                stake_after: new_stake,
+                stake_delta: delta(old_stake, new_stake),
+            });
+        }
+
+        if old_validator != new_validator {
+            self.moved_delegations.push(DelegationMove {
+                pubkey: new.pubkey.clone(),
+                from_validator: old_validator,
+                to_validator: new_validator,
+                stake_lamports: new_stake,
+            });
+        }
+
+        if old.authority.staker != new.authority.staker || old.authority.withdrawer != new.authority.withdrawer {
+            self.authority_changes.push(AuthorityChange {
+                pubkey: new.pubkey.clone(),
+                staker_before: old.authority.staker.clone(),
+                staker_after: new.authority.staker.clone(),
+                withdrawer_before: old.authority.withdrawer.clone(),
+                withdrawer_after: new.authority.withdrawer.clone(),
+            });
+        }
+
+        let (old_lockup, new_lockup) = (&old.lockup, &new.lockup);
+        if old_lockup.custodian != new_lockup.custodian
+            || old_lockup.epoch != new_lockup.epoch
+            || old_lockup.unix_timestamp != new_lockup.unix_timestamp
+        {
+            self.lockup_changes.push(LockupChange {
+                pubkey: new.pubkey.clone(),
+                before: old_lockup.clone(),
+                after: new_lockup.clone(),
+            });
+        }
+    }
+
+    /// Check if nothing changed
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added_accounts.is_empty()
+            && self.removed_accounts.is_empty()
+            && self.stake_changes.is_empty()
+            && self.validators_entered.is_empty()
+            && self.validators_left.is_empty()
+            && self.moved_delegations.is_empty()
+            && self.authority_changes.is_empty()
+            && self.lockup_changes.is_empty()
+            && self.statistics_delta == StatisticsDelta::default()
+    }
+}
+
+impl ProductionPoolData {
+    /// Changes since an earlier fetch of the same pool
+    #[must_use]
+    pub fn diff_since(&self, previous: &Self) -> PoolDiff {
+        PoolDiff::between(previous, self)
+    }
+}
+
+impl PoolData {
+    /// Changes since an earlier fetch of the same pool
+    #[must_use]
+    pub fn diff_since(&self, previous: &Self) -> PoolDiff {
+        PoolDiff::between_debug(previous, self)
+    }
+}
+
+/// Validators delegated to by any account
+fn validators(accounts: &[ProductionStakeAccountInfo]) -> BTreeSet<&str> {
+    accounts
+        .iter()
+        .filter_map(|account| account.delegation.as_ref().map(|d| d.validator.as_str()))
+        .collect()
+}
+
+/// `after - before`, saturating at the `i64` range
+fn delta(before: u64, after: u64) -> i64 {
+    let delta = i128::from(after) - i128::from(before);
+    i64::try_from(delta).unwrap_or(if delta < 0 { i64::MIN } else { i64::MAX })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, stake_account, undelegated_account, RENT_EXEMPT_RESERVE};
+
+    fn account(pubkey: &str, lamports: u64, validator: Option<&str>) -> ProductionStakeAccountInfo {
+        let mut account = validator.map_or_else(|| undelegated_account(pubkey), |validator| stake_account(pubkey, validator, u64::MAX));
+        account.lamports = lamports;
+        if let Some(delegation) = &mut account.delegation {
+            delegation.stake_lamports = lamports - RENT_EXEMPT_RESERVE;
+        }
+        account
+    }
+
+    fn pool(accounts: Vec<ProductionStakeAccountInfo>) -> ProductionPoolData {
+        let mut data = test_support::pool("jito", accounts);
+        data.statistics.total_accounts = data.stake_accounts.len();
+        data.statistics.total_lamports = data.stake_accounts.iter().map(|a| a.lamports).sum();
+        data
+    }
+
+    #[test]
+    fn test_identical_pools() {
+        let data = pool(vec![account("a", 10_000_000_000, Some("v1"))]);
+        assert!(data.diff_since(&data).is_empty());
+    }
+
+    #[test]
+    fn test_pool_diff() {
+        let previous = pool(vec![
+            account("a", 10_000_000_000, Some("v1")),
+            account("b", 5_000_000_000, Some("v2")),
+            account("c", 3_000_000_000, Some("v1")),
+            account("gone", 1_000_000_000, Some("v3")),
+        ]);
+        let mut rotated = account("c", 3_000_000_000, Some("v1"));
+        rotated.authority.withdrawer = "new_withdrawer".to_string();
+        rotated.lockup.epoch = 700;
+        let current = pool(vec![
+            account("a", 10_500_000_000, Some("v1")),
+            account("b", 5_000_000_000, Some("v4")),
+            rotated,
+            account("new", 2_000_000_000, None),
+        ]);
+
+        let diff = current.diff_since(&previous);
+        assert_eq!(diff.added_accounts.len(), 1);
+        assert_eq!(diff.added_accounts[0].pubkey, "new");
+        assert_eq!(diff.removed_accounts[0].pubkey, "gone");
+
+        assert_eq!(diff.stake_changes.len(), 1);
+        assert_eq!(diff.stake_changes[0].lamports_delta, 500_000_000);
+        assert_eq!(diff.stake_changes[0].stake_delta, 500_000_000);
+
+        assert_eq!(diff.moved_delegations.len(), 1);
+        assert_eq!(diff.moved_delegations[0].from_validator.as_deref(), Some("v2"));
+        assert_eq!(diff.moved_delegations[0].to_validator.as_deref(), Some("v4"));
+        assert_eq!(diff.validators_entered, vec!["v4"]);
+        assert_eq!(diff.validators_left, vec!["v2", "v3"]);
+
+        assert_eq!(diff.authority_changes[0].withdrawer_after, "new_withdrawer");
+        assert_eq!(diff.lockup_changes[0].after.epoch, 700);
+        assert_eq!(diff.statistics_delta.total_lamports, 1_500_000_000);
+        assert_eq!(diff.statistics_delta.total_accounts, 0);
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["validators_left"][1], "v3");
+        assert!(delta(u64::MAX, 0) == i64::MIN && delta(0, 7) == 7);
+    }
+}
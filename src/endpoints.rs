@@ -0,0 +1,379 @@
+//! Multi-endpoint RPC routing with health scoring and failover.
+//!
+//! Each configured endpoint keeps its own RPC client, rate limiter and health
+//! statistics. Every attempt is routed to the healthiest endpoint, and retryable
+//! errors fail over to the next best one.
+
+use crate::config::{EndpointSettings, SharedRateLimiter, StakeAccountEncoding};
+use crate::error::{PoolsDataError, Result};
+use crate::rpc::RpcClient;
+use crate::transport::RpcTransport;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Latency assumed for endpoints without any successful request yet
+const DEFAULT_LATENCY_MS: f64 = 250.0;
+
+/// Weight of the newest sample in the latency moving average
+const LATENCY_EWMA_ALPHA: f64 = 0.3;
+
+/// Consecutive errors after which an endpoint is considered unhealthy
+const UNHEALTHY_AFTER_ERRORS: u32 = 3;
+
+/// How long an unhealthy endpoint is avoided after its last failure
+const UNHEALTHY_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Health report for a single RPC endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    /// Endpoint display name
+    pub name: String,
+    /// Configured routing weight
+    pub weight: u32,
+    /// Total requests sent
+    pub requests: u64,
+    /// Total failed requests
+    pub errors: u64,
+    /// Failures since the last success
+    pub consecutive_errors: u32,
+    /// Moving average latency of successful requests
+    pub average_latency_ms: Option<f64>,
+    /// Whether the endpoint is currently eligible for routing
+    pub healthy: bool,
+    /// Routing score (higher is preferred)
+    pub score: f64,
+}
+
+/// Result of a call, with the endpoint that served it
+#[derive(Debug)]
+pub(crate) struct Served<T> {
+    pub value: T,
+    pub endpoint: String,
+}
+
+#[derive(Debug, Default)]
+struct EndpointStats {
+    latency_ewma_ms: Option<f64>,
+    requests: u64,
+    errors: u64,
+    consecutive_errors: u32,
+    last_failure: Option<Instant>,
+}
+
+impl EndpointStats {
+    fn is_healthy(&self) -> bool {
+        self.consecutive_errors < UNHEALTHY_AFTER_ERRORS
+            || self
+                .last_failure
+                .map_or(true, |at| at.elapsed() >= UNHEALTHY_COOLDOWN)
+    }
+
+    fn score(&self, weight: u32) -> f64 {
+        let latency = self.latency_ewma_ms.unwrap_or(DEFAULT_LATENCY_MS);
+        let error_penalty = 2f64.powi(self.consecutive_errors.min(10).try_into().unwrap_or(10));
+        f64::from(weight) * 1000.0 / (latency + 1.0) / error_penalty
+    }
+}
+
+struct Endpoint {
+    name: String,
+    weight: u32,
+    rpc_client: Arc<RpcClient>,
+    rate_limiter: Option<SharedRateLimiter>,
+    stats: Mutex<EndpointStats>,
+}
+
+/// Set of RPC endpoints routed by health score
+pub(crate) struct EndpointPool {
+    endpoints: Vec<Endpoint>,
+}
+
+impl EndpointPool {
+    /// Create a pool from resolved endpoint settings
+    ///
+    /// With a custom `transport`, every endpoint sends its requests through it
+    /// (endpoint URLs then only name the endpoints).
+    pub(crate) fn new(
+        settings: &[EndpointSettings],
+        timeout: Duration,
+        encoding: StakeAccountEncoding,
+        transport: Option<&Arc<dyn RpcTransport>>,
+    ) -> Result<Self> {
+        if settings.is_empty() {
+            return Err(PoolsDataError::ConfigurationError {
+                message: "At least one RPC endpoint is required".to_string(),
+            });
+        }
+
+        let endpoints = settings
+            .iter()
+            .map(|endpoint| {
+                let rpc_client = match transport {
+                    Some(transport) => RpcClient::with_transport(Arc::clone(transport), timeout),
+                    None => RpcClient::new(endpoint.url.clone(), timeout),
+                };
+                Endpoint {
+                    name: endpoint.name.clone(),
+                    weight: endpoint.weight,
+                    rpc_client: Arc::new(rpc_client.with_account_encoding(encoding)),
+                    rate_limiter: endpoint.rate_limiter.clone(),
+                    stats: Mutex::new(EndpointStats::default()),
+                }
+            })
+            .collect();
+
+        Ok(Self { endpoints })
+    }
+
+    /// Pick the best endpoint not in `exclude`
+    ///
+    /// Healthy endpoints are preferred; once every endpoint was tried the
+    /// exclusion list is ignored.
+    fn select(&self, exclude: &[usize]) -> usize {
+        let candidates: Vec<usize> = (0..self.endpoints.len())
+            .filter(|index| !exclude.contains(index))
+            .collect();
+        let candidates = if candidates.is_empty() {
+            (0..self.endpoints.len()).collect()
+        } else {
+            candidates
+        };
+
+        let scored: Vec<(usize, bool, f64)> = candidates
+            .into_iter()
+            .map(|index| {
+                let endpoint = &self.endpoints[index];
+                let stats = Self::lock(&endpoint.stats);
+                (index, stats.is_healthy(), stats.score(endpoint.weight))
+            })
+            .collect();
+
+        let any_healthy = scored.iter().any(|(_, healthy, _)| *healthy);
+        scored
+            .into_iter()
+            .filter(|(_, healthy, _)| *healthy || !any_healthy)
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+            .map_or(0, |(index, _, _)| index)
+    }
+
+    /// Whether a healthy endpoint is not in `tried`
+    fn has_untried_healthy(&self, tried: &[usize]) -> bool {
+        self.endpoints
+            .iter()
+            .enumerate()
+            .any(|(index, endpoint)| !tried.contains(&index) && Self::lock(&endpoint.stats).is_healthy())
+    }
+
+    fn record_success(&self, index: usize, latency: Duration) {
+        let mut stats = Self::lock(&self.endpoints[index].stats);
+        let sample = latency.as_secs_f64() * 1000.0;
+        stats.requests += 1;
+        stats.consecutive_errors = 0;
+        stats.latency_ewma_ms = Some(stats.latency_ewma_ms.map_or(sample, |average| {
+            LATENCY_EWMA_ALPHA.mul_add(sample, (1.0 - LATENCY_EWMA_ALPHA) * average)
+        }));
+    }
+
+    fn record_failure(&self, index: usize) {
+        let mut stats = Self::lock(&self.endpoints[index].stats);
+        stats.requests += 1;
+        stats.errors += 1;
+        stats.consecutive_errors += 1;
+        stats.last_failure = Some(Instant::now());
+    }
+
+    fn lock(stats: &Mutex<EndpointStats>) -> std::sync::MutexGuard<'_, EndpointStats> {
+        // Stats are plain counters, so a poisoned lock still holds usable data
+        stats.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Current health of every endpoint, in configuration order
+    pub(crate) fn health(&self) -> Vec<EndpointHealth> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let stats = Self::lock(&endpoint.stats);
+                EndpointHealth {
+                    name: endpoint.name.clone(),
+                    weight: endpoint.weight,
+                    requests: stats.requests,
+                    errors: stats.errors,
+                    consecutive_errors: stats.consecutive_errors,
+                    average_latency_ms: stats.latency_ewma_ms,
+                    healthy: stats.is_healthy(),
+                    score: stats.score(endpoint.weight),
+                }
+            })
+            .collect()
+    }
+
+    /// Run `operation` on the healthiest endpoint, failing over on retryable errors
+    ///
+    /// Each attempt waits for the chosen endpoint's rate limiter. A retryable error
+    /// fails over at once to the next healthy endpoint not yet tried; once none is
+    /// left, `retry_strategy` supplies the delays of further attempts (one per delay).
+    pub(crate) async fn call<T, F, Fut>(
+        &self,
+        retry_strategy: impl IntoIterator<Item = Duration>,
+        mut operation: F,
+    ) -> Result<Served<T>>
+    where
+        F: FnMut(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut delays = retry_strategy.into_iter();
+        let mut tried = Vec::new();
+
+        loop {
+            let index = self.select(&tried);
+            tried.push(index);
+            let endpoint = &self.endpoints[index];
+
+            if let Some(limiter) = &endpoint.rate_limiter {
+                limiter.until_ready().await;
+            }
+
+            let started = Instant::now();
+            match operation(Arc::clone(&endpoint.rpc_client)).await {
+                Ok(value) => {
+                    self.record_success(index, started.elapsed());
+                    return Ok(Served {
+                        value,
+                        endpoint: endpoint.name.clone(),
+                    });
+                }
+                Err(error) => {
+                    self.record_failure(index);
+                    if !error.is_retryable() {
+                        return Err(error);
+                    }
+                    if self.has_untried_healthy(&tried) {
+                        log::warn!("Endpoint {} failed ({error}), failing over", endpoint.name);
+                        continue;
+                    }
+                    let Some(delay) = delays.next() else {
+                        return Err(error);
+                    };
+                    log::warn!("Endpoint {} failed ({error}), retrying in {delay:?}", endpoint.name);
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(weights: &[u32]) -> EndpointPool {
+        let settings: Vec<EndpointSettings> = weights
+            .iter()
+            .enumerate()
+            .map(|(i, &weight)| EndpointSettings {
+                url: format!("http://endpoint{i}"),
+                name: format!("endpoint{i}"),
+                weight,
+                rate_limiter: None,
+            })
+            .collect();
+        EndpointPool::new(&settings, Duration::from_secs(1), StakeAccountEncoding::JsonParsed, None).unwrap()
+    }
+
+    #[test]
+    fn test_select_prefers_weight_and_latency() {
+        let endpoints = pool(&[1, 3]);
+        assert_eq!(endpoints.select(&[]), 1);
+
+        // Much lower latency outweighs the weight difference
+        endpoints.record_success(0, Duration::from_millis(10));
+        endpoints.record_success(1, Duration::from_millis(500));
+        assert_eq!(endpoints.select(&[]), 0);
+        assert_eq!(endpoints.select(&[0]), 1);
+    }
+
+    #[test]
+    fn test_unhealthy_endpoint_is_avoided() {
+        let endpoints = pool(&[5, 1]);
+        for _ in 0..UNHEALTHY_AFTER_ERRORS {
+            endpoints.record_failure(0);
+        }
+
+        assert_eq!(endpoints.select(&[]), 1);
+        let health = endpoints.health();
+        assert!(!health[0].healthy);
+        assert_eq!(health[0].errors, 3);
+        assert!(health[1].healthy);
+
+        // All tried: exclusion is ignored
+        assert_eq!(endpoints.select(&[0, 1]), 1);
+    }
+
+    #[tokio::test]
+    async fn test_call_fails_over_on_retryable_error() {
+        let endpoints = pool(&[2, 1]);
+        let mut attempts = 0;
+        let served = endpoints
+            .call(vec![Duration::ZERO], |_| {
+                attempts += 1;
+                let attempt = attempts;
+                async move {
+                    if attempt == 1 {
+                        Err(PoolsDataError::NetworkError {
+                            message: "connection refused".to_string(),
+                        })
+                    } else {
+                        Ok(42)
+                    }
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(served.value, 42);
+        assert_eq!(served.endpoint, "endpoint1");
+        assert_eq!(endpoints.health()[0].consecutive_errors, 1);
+    }
+
+    #[tokio::test]
+    async fn test_call_fails_over_without_retries() {
+        let endpoints = pool(&[3, 2, 1]);
+        let mut attempts = 0;
+        let result: Result<Served<()>> = endpoints
+            .call(Vec::new(), |_| {
+                attempts += 1;
+                async {
+                    Err(PoolsDataError::NetworkError {
+                        message: "connection refused".to_string(),
+                    })
+                }
+            })
+            .await;
+
+        // Every endpoint is tried once before the (empty) retry budget is consulted
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+        assert!(endpoints.health().iter().all(|health| health.errors == 1));
+    }
+
+    #[tokio::test]
+    async fn test_call_stops_on_non_retryable_error() {
+        let endpoints = pool(&[1, 1]);
+        let mut attempts = 0;
+        let result: Result<Served<()>> = endpoints
+            .call(vec![Duration::ZERO; 3], |_| {
+                attempts += 1;
+                async {
+                    Err(PoolsDataError::ParseError {
+                        message: "bad payload".to_string(),
+                    })
+                }
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+}
@@ -49,6 +49,14 @@ pub enum PoolsDataError {
     #[error("Batch operation failed: {successful} succeeded, {failed} failed")]
     BatchOperationFailed { successful: usize, failed: usize },
 
+    /// Local storage (database) operation failed
+    #[error("Storage error: {message}")]
+    StorageError { message: String },
+
+    /// Writing an export failed
+    #[error("Export error: {message}")]
+    ExportError { message: String },
+
     /// Generic error for unexpected issues
     #[error("Internal error: {message}")]
     InternalError { message: String },
@@ -90,23 +98,53 @@ impl PoolError {
 
     /// Determine if an error is retryable
     const fn is_retryable(error: &PoolsDataError) -> bool {
-        match error {
+        error.is_retryable()
+    }
+}
+
+impl PoolsDataError {
+    /// Variant name in `snake_case` (e.g. for metric labels)
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::NetworkError { .. } => "network_error",
+            Self::RpcError { .. } => "rpc_error",
+            Self::ParseError { .. } => "parse_error",
+            Self::RateLimitExceeded { .. } => "rate_limit_exceeded",
+            Self::RequestTimeout { .. } => "request_timeout",
+            Self::ConfigurationError { .. } => "configuration_error",
+            Self::PoolNotFound { .. } => "pool_not_found",
+            Self::NoStakeAccounts { .. } => "no_stake_accounts",
+            Self::InvalidStakeData { .. } => "invalid_stake_data",
+            Self::BatchOperationFailed { .. } => "batch_operation_failed",
+            Self::StorageError { .. } => "storage_error",
+            Self::ExportError { .. } => "export_error",
+            Self::InternalError { .. } => "internal_error",
+        }
+    }
+
+    /// Whether this error is likely to succeed if retried (possibly on another endpoint)
+    #[must_use]
+    pub const fn is_retryable(&self) -> bool {
+        match self {
             // Retryable errors - temporary issues that might succeed on retry
-            PoolsDataError::NetworkError { .. }
-            | PoolsDataError::RateLimitExceeded { .. }
-            | PoolsDataError::RequestTimeout { .. }
-            | PoolsDataError::InternalError { .. } => true,
+            Self::NetworkError { .. }
+            | Self::RateLimitExceeded { .. }
+            | Self::RequestTimeout { .. }
+            | Self::InternalError { .. } => true,
 
             // Non-retryable errors - permanent issues that cannot be resolved by retrying
-            PoolsDataError::ParseError { .. }
-            | PoolsDataError::ConfigurationError { .. }
-            | PoolsDataError::PoolNotFound { .. }
-            | PoolsDataError::NoStakeAccounts { .. }
-            | PoolsDataError::InvalidStakeData { .. }
-            | PoolsDataError::BatchOperationFailed { .. } => false,
+            Self::ParseError { .. }
+            | Self::ConfigurationError { .. }
+            | Self::PoolNotFound { .. }
+            | Self::NoStakeAccounts { .. }
+            | Self::InvalidStakeData { .. }
+            | Self::BatchOperationFailed { .. }
+            | Self::StorageError { .. }
+            | Self::ExportError { .. } => false,
 
             // RPC errors - depends on specific error code
-            PoolsDataError::RpcError { code, .. } => {
+            Self::RpcError { code, .. } => {
                 match code {
                     -32602 | -32601 => false, // Invalid params/method - not retryable
                     _ => true,                // Other RPC errors may be temporary
@@ -143,6 +181,50 @@ impl From<serde_json::Error> for PoolsDataError {
     }
 }
 
+impl From<csv::Error> for PoolsDataError {
+    fn from(error: csv::Error) -> Self {
+        Self::ExportError {
+            message: error.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<arrow_schema::ArrowError> for PoolsDataError {
+    fn from(error: arrow_schema::ArrowError) -> Self {
+        Self::ExportError {
+            message: error.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<parquet::errors::ParquetError> for PoolsDataError {
+    fn from(error: parquet::errors::ParquetError) -> Self {
+        Self::ExportError {
+            message: error.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl From<prometheus::Error> for PoolsDataError {
+    fn from(error: prometheus::Error) -> Self {
+        Self::ConfigurationError {
+            message: format!("Metrics: {error}"),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for PoolsDataError {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::StorageError {
+            message: error.to_string(),
+        }
+    }
+}
+
 // Note: Governor's NotUntil type is complex and version-dependent
 // Rate limiting errors are handled manually in the client code instead
 
@@ -0,0 +1,216 @@
+//! Liquid staking token exchange rates.
+//!
+//! The SOL/LST exchange rate is the pool's lamports divided by the LST mint supply.
+//! Rates are plain serializable values so they can be stored and compared between fetches.
+
+use crate::types::PoolData;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Seconds in a 365-day year, used to annualize rate changes
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+/// Lamports per SOL
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Supply of an SPL token mint (`getTokenSupply`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenSupply {
+    /// Raw supply in the mint's smallest unit
+    pub amount: u64,
+    /// Mint decimals
+    pub decimals: u8,
+}
+
+impl TokenSupply {
+    /// Supply in whole tokens
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn ui_amount(&self) -> f64 {
+        self.amount as f64 / 10f64.powi(i32::from(self.decimals))
+    }
+}
+
+/// Where the pool lamports behind an exchange rate come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LamportsSource {
+    /// Sum of the pool's stake account balances (excludes non-stake reserves)
+    StakeAccounts,
+    /// `total_lamports` from the on-chain pool state
+    PoolState,
+}
+
+/// SOL/LST exchange rate of a pool at a point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeRate {
+    /// Pool name
+    pub pool_name: String,
+    /// LST mint
+    pub lst_mint: String,
+    /// Pool lamports backing the supply
+    pub total_lamports: u64,
+    /// Where `total_lamports` comes from
+    pub lamports_source: LamportsSource,
+    /// LST mint supply
+    pub token_supply: TokenSupply,
+    /// SOL per LST
+    pub rate: f64,
+    /// Epoch of the measurement, if known
+    pub epoch: Option<u64>,
+    /// When the rate was measured
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Change of a pool's exchange rate between two measurements
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeRateChange {
+    /// Pool name
+    pub pool_name: String,
+    /// Earlier rate
+    pub previous_rate: f64,
+    /// Later rate
+    pub current_rate: f64,
+    /// `current_rate - previous_rate`
+    pub absolute_change: f64,
+    /// Change relative to the earlier rate
+    pub relative_change: f64,
+    /// Seconds between the measurements
+    pub elapsed_seconds: i64,
+    /// Epochs between the measurements, if both are known
+    pub elapsed_epochs: Option<u64>,
+    /// Relative change compounded to a year (`None` without elapsed time)
+    pub annualized_change: Option<f64>,
+}
+
+impl ExchangeRate {
+    /// Calculate a rate from pool lamports and mint supply
+    ///
+    /// Returns `None` when the supply is zero.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(
+        pool_name: impl Into<String>,
+        lst_mint: impl Into<String>,
+        total_lamports: u64,
+        lamports_source: LamportsSource,
+        token_supply: TokenSupply,
+        epoch: Option<u64>,
+    ) -> Option<Self> {
+        if token_supply.amount == 0 {
+            return None;
+        }
+
+        Some(Self {
+            pool_name: pool_name.into(),
+            lst_mint: lst_mint.into(),
+            total_lamports,
+            lamports_source,
+            token_supply,
+            rate: total_lamports as f64 / LAMPORTS_PER_SOL / token_supply.ui_amount(),
+            epoch,
+            fetched_at: Utc::now(),
+        })
+    }
+
+    /// Calculate a pool's rate, preferring on-chain pool state over stake account sums
+    ///
+    /// SPL stake pools use the pool's `total_lamports`; Marinade uses its total
+    /// virtual staked lamports, which include the liquid reserve.
+    #[must_use]
+    pub fn for_pool(pool: &PoolData, lst_mint: &str, token_supply: TokenSupply, epoch: Option<u64>) -> Option<Self> {
+        let (total_lamports, source) = match (&pool.spl_stake_pool, &pool.marinade_state) {
+            (Some(state), _) => (state.total_lamports, LamportsSource::PoolState),
+            (None, Some(state)) => (state.total_virtual_staked_lamports(), LamportsSource::PoolState),
+            (None, None) => (pool.total_lamports(), LamportsSource::StakeAccounts),
+        };
+        Self::new(&pool.pool_name, lst_mint, total_lamports, source, token_supply, epoch)
+    }
+
+    /// Compare with an earlier measurement of the same mint
+    ///
+    /// Returns `None` if the mints differ.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn change_since(&self, previous: &Self) -> Option<ExchangeRateChange> {
+        if self.lst_mint != previous.lst_mint {
+            return None;
+        }
+
+        let absolute_change = self.rate - previous.rate;
+        let relative_change = if previous.rate == 0.0 { 0.0 } else { absolute_change / previous.rate };
+        let elapsed_seconds = (self.fetched_at - previous.fetched_at).num_seconds();
+        let annualized_change = (elapsed_seconds > 0).then(|| {
+            (1.0 + relative_change).powf(SECONDS_PER_YEAR / elapsed_seconds as f64) - 1.0
+        });
+
+        Some(ExchangeRateChange {
+            pool_name: self.pool_name.clone(),
+            previous_rate: previous.rate,
+            current_rate: self.rate,
+            absolute_change,
+            relative_change,
+            elapsed_seconds,
+            elapsed_epochs: self.epoch.zip(previous.epoch).map(|(now, then)| now.saturating_sub(then)),
+            annualized_change,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supply(amount: u64, decimals: u8) -> TokenSupply {
+        TokenSupply { amount, decimals }
+    }
+
+    #[test]
+    fn test_exchange_rate() {
+        let rate = ExchangeRate::new("jito", "mint", 1_150_000_000_000, LamportsSource::PoolState, supply(1_000_000_000_000, 9), Some(600))
+            .unwrap();
+        assert!((rate.rate - 1.15).abs() < 1e-12);
+
+        // Decimals other than 9 are honored
+        let six = ExchangeRate::new("x", "mint", 2_000_000_000, LamportsSource::StakeAccounts, supply(1_000_000, 6), None).unwrap();
+        assert!((six.rate - 2.0).abs() < 1e-12);
+
+        assert!(ExchangeRate::new("x", "mint", 1, LamportsSource::StakeAccounts, supply(0, 9), None).is_none());
+    }
+
+    #[test]
+    fn test_change_since() {
+        let mut previous =
+            ExchangeRate::new("jito", "mint", 1_100_000_000, LamportsSource::PoolState, supply(1_000_000_000, 9), Some(600)).unwrap();
+        previous.fetched_at -= chrono::Duration::days(2);
+        let current =
+            ExchangeRate::new("jito", "mint", 1_100_330_000, LamportsSource::PoolState, supply(1_000_000_000, 9), Some(601)).unwrap();
+
+        let change = current.change_since(&previous).unwrap();
+        assert!((change.absolute_change - 0.000_33).abs() < 1e-12);
+        assert!((change.relative_change - 0.0003).abs() < 1e-9);
+        assert_eq!(change.elapsed_epochs, Some(1));
+        let annualized = change.annualized_change.unwrap();
+        assert!(annualized > 0.05 && annualized < 0.06);
+
+        let other = ExchangeRate::new("x", "other", 1, LamportsSource::PoolState, supply(1, 9), None).unwrap();
+        assert!(current.change_since(&other).is_none());
+    }
+
+    #[test]
+    fn test_marinade_rate_uses_state() {
+        use crate::marinade::{tests::encode_state, MarinadeState, MARINADE_STATE_ADDRESS};
+
+        let mut pool = PoolData::new("marinade".to_string(), "authority".to_string());
+        let without_state = ExchangeRate::for_pool(&pool, "mSOL", supply(2_500_000, 9), None).unwrap();
+        assert_eq!(without_state.lamports_source, LamportsSource::StakeAccounts);
+
+        // Reserve and cooling down lamports count towards the rate, unclaimed tickets do not
+        let state = MarinadeState::decode_state(MARINADE_STATE_ADDRESS, &encode_state()).unwrap();
+        pool.marinade_state = Some(state);
+        let rate = ExchangeRate::for_pool(&pool, "mSOL", supply(2_500_000, 9), Some(600)).unwrap();
+        assert_eq!(rate.lamports_source, LamportsSource::PoolState);
+        assert_eq!(rate.total_lamports, 3_004_000);
+        assert!((rate.rate - 3_004_000.0 / 2_500_000.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,432 @@
+//! Flat CSV and NDJSON exports.
+//!
+//! Nested `ProductionPoolData` is flattened into one row per stake account, one row
+//! per (pool, validator) or one row per pool. Columns follow the field order of the
+//! row types and never change between calls, even when there are no rows.
+
+use crate::error::{PoolsDataError, Result};
+use crate::pools::AuthorityRole;
+use crate::types::{ProductionPoolData, ProductionStakeAccountInfo};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::Write;
+use std::marker::PhantomData;
+
+/// Output format of an export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// Comma separated values with a header row
+    #[default]
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+}
+
+/// Row type with a fixed set of columns
+pub trait ExportRow: Serialize {
+    /// Column names, in field order
+    const COLUMNS: &'static [&'static str];
+}
+
+/// One stake account of a pool
+///
+/// Epochs are empty (`null`) when unset on chain (`u64::MAX`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StakeAccountRow {
+    /// Pool name
+    pub pool_name: String,
+    /// When the pool was fetched
+    pub fetched_at: DateTime<Utc>,
+    /// Stake account pubkey
+    pub pubkey: String,
+    /// Account balance
+    pub lamports: u64,
+    /// Stake account type
+    pub stake_type: String,
+    /// Delegated vote account
+    pub validator: Option<String>,
+    /// Delegated stake
+    pub stake_lamports: Option<u64>,
+    /// Activation epoch
+    pub activation_epoch: Option<u64>,
+    /// Deactivation epoch
+    pub deactivation_epoch: Option<u64>,
+    /// Staker authority
+    pub staker: String,
+    /// Withdrawer authority
+    pub withdrawer: String,
+    /// Authority role that matched the pool
+    pub matched_authority: AuthorityRole,
+    /// Lockup custodian
+    pub lockup_custodian: String,
+    /// Lockup epoch
+    pub lockup_epoch: Option<u64>,
+    /// Lockup unix timestamp
+    pub lockup_unix_timestamp: i64,
+}
+
+impl ExportRow for StakeAccountRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "pool_name",
+        "fetched_at",
+        "pubkey",
+        "lamports",
+        "stake_type",
+        "validator",
+        "stake_lamports",
+        "activation_epoch",
+        "deactivation_epoch",
+        "staker",
+        "withdrawer",
+        "matched_authority",
+        "lockup_custodian",
+        "lockup_epoch",
+        "lockup_unix_timestamp",
+    ];
+}
+
+impl StakeAccountRow {
+    /// Flatten an account of a pool
+    #[must_use]
+    pub fn new(pool: &ProductionPoolData, account: &ProductionStakeAccountInfo) -> Self {
+        let delegation = account.delegation.as_ref();
+        Self {
+            pool_name: pool.pool_name.clone(),
+            fetched_at: pool.fetched_at,
+            pubkey: account.pubkey.clone(),
+            lamports: account.lamports,
+            stake_type: account.stake_type.clone(),
+            validator: delegation.map(|d| d.validator.clone()),
+            stake_lamports: delegation.map(|d| d.stake_lamports),
+            activation_epoch: delegation.and_then(|d| epoch(d.activation_epoch)),
+            deactivation_epoch: delegation.and_then(|d| epoch(d.deactivation_epoch)),
+            staker: account.authority.staker.clone(),
+            withdrawer: account.authority.withdrawer.clone(),
+            matched_authority: account.matched_authority,
+            lockup_custodian: account.lockup.custodian.clone(),
+            lockup_epoch: epoch(account.lockup.epoch),
+            lockup_unix_timestamp: account.lockup.unix_timestamp,
+        }
+    }
+
+    /// Rows for every account of a pool, in account order
+    pub fn rows(pool: &ProductionPoolData) -> impl Iterator<Item = Self> + '_ {
+        pool.stake_accounts.iter().map(move |account| Self::new(pool, account))
+    }
+}
+
+/// Stake a pool delegates to one validator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidatorRow {
+    /// Pool name
+    pub pool_name: String,
+    /// When the pool was fetched
+    pub fetched_at: DateTime<Utc>,
+    /// Vote account
+    pub validator: String,
+    /// Stake accounts delegated to the validator
+    pub account_count: u32,
+    /// Delegated stake
+    pub total_delegated: u64,
+}
+
+impl ExportRow for ValidatorRow {
+    const COLUMNS: &'static [&'static str] =
+        &["pool_name", "fetched_at", "validator", "account_count", "total_delegated"];
+}
+
+impl ValidatorRow {
+    /// Rows for the validator distribution of a pool, sorted by validator
+    #[must_use]
+    pub fn rows(pool: &ProductionPoolData) -> Vec<Self> {
+        let mut rows: Vec<Self> = pool
+            .validator_distribution
+            .iter()
+            .map(|(validator, stake)| Self {
+                pool_name: pool.pool_name.clone(),
+                fetched_at: pool.fetched_at,
+                validator: validator.clone(),
+                account_count: stake.account_count,
+                total_delegated: stake.total_delegated,
+            })
+            .collect();
+        rows.sort_by(|a, b| a.validator.cmp(&b.validator));
+        rows
+    }
+}
+
+/// Statistics of one pool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PoolStatisticsRow {
+    /// Pool name
+    pub pool_name: String,
+    /// Pool authority
+    pub authority: String,
+    /// When the pool was fetched
+    pub fetched_at: DateTime<Utc>,
+    /// Total accounts
+    pub total_accounts: usize,
+    /// Activating accounts
+    pub activating_accounts: usize,
+    /// Active accounts
+    pub active_accounts: usize,
+    /// Deactivating accounts
+    pub deactivating_accounts: usize,
+    /// Deactivated accounts
+    pub deactivated_accounts: usize,
+    /// Total lamports
+    pub total_lamports: u64,
+    /// Activating stake
+    pub activating_stake_lamports: u64,
+    /// Active stake
+    pub active_stake_lamports: u64,
+    /// Deactivating stake
+    pub deactivating_stake_lamports: u64,
+    /// Deactivated stake
+    pub deactivated_stake_lamports: u64,
+    /// Unique validators
+    pub validator_count: usize,
+}
+
+impl ExportRow for PoolStatisticsRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "pool_name",
+        "authority",
+        "fetched_at",
+        "total_accounts",
+        "activating_accounts",
+        "active_accounts",
+        "deactivating_accounts",
+        "deactivated_accounts",
+        "total_lamports",
+        "activating_stake_lamports",
+        "active_stake_lamports",
+        "deactivating_stake_lamports",
+        "deactivated_stake_lamports",
+        "validator_count",
+    ];
+}
+
+impl From<&ProductionPoolData> for PoolStatisticsRow {
+    fn from(pool: &ProductionPoolData) -> Self {
+        let stats = &pool.statistics;
+        Self {
+            pool_name: pool.pool_name.clone(),
+            authority: pool.authority.clone(),
+            fetched_at: pool.fetched_at,
+            total_accounts: stats.total_accounts,
+            activating_accounts: stats.activating_accounts,
+            active_accounts: stats.active_accounts,
+            deactivating_accounts: stats.deactivating_accounts,
+            deactivated_accounts: stats.deactivated_accounts,
+            total_lamports: stats.total_lamports,
+            activating_stake_lamports: stats.activating_stake_lamports,
+            active_stake_lamports: stats.active_stake_lamports,
+            deactivating_stake_lamports: stats.deactivating_stake_lamports,
+            deactivated_stake_lamports: stats.deactivated_stake_lamports,
+            validator_count: stats.validator_count,
+        }
+    }
+}
+
+enum Sink<W: Write> {
+    Csv(Box<csv::Writer<W>>),
+    Ndjson(W),
+}
+
+/// Streaming writer of rows of one type
+///
+/// The CSV header is written on creation, so an export without rows still has its columns.
+pub struct ExportWriter<W: Write, R: ExportRow> {
+    sink: Sink<W>,
+    rows: usize,
+    row_type: PhantomData<R>,
+}
+
+impl<W: Write, R: ExportRow> ExportWriter<W, R> {
+    /// Start an export
+    ///
+    /// # Errors
+    /// Returns `ExportError` if the CSV header cannot be written.
+    pub fn new(writer: W, format: ExportFormat) -> Result<Self> {
+        let sink = match format {
+            ExportFormat::Csv => {
+                let mut csv = csv::WriterBuilder::new().has_headers(false).from_writer(writer);
+                csv.write_record(R::COLUMNS)?;
+                Sink::Csv(Box::new(csv))
+            }
+            ExportFormat::Ndjson => Sink::Ndjson(writer),
+        };
+        Ok(Self {
+            sink,
+            rows: 0,
+            row_type: PhantomData,
+        })
+    }
+
+    /// Write a row
+    ///
+    /// # Errors
+    /// Returns `ExportError` if the row cannot be serialized or written.
+    pub fn write(&mut self, row: &R) -> Result<()> {
+        match &mut self.sink {
+            Sink::Csv(csv) => csv.serialize(row)?,
+            Sink::Ndjson(writer) => {
+                serde_json::to_writer(&mut *writer, row).map_err(export_error)?;
+                writer.write_all(b"\n").map_err(export_error)?;
+            }
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Write all rows of an iterator
+    ///
+    /// # Errors
+    /// Returns `ExportError` on the first row that fails.
+    pub fn write_all(&mut self, rows: impl IntoIterator<Item = R>) -> Result<()> {
+        rows.into_iter().try_for_each(|row| self.write(&row))
+    }
+
+    /// Rows written so far
+    #[must_use]
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Flush and return the underlying writer
+    ///
+    /// # Errors
+    /// Returns `ExportError` if flushing fails.
+    pub fn finish(self) -> Result<W> {
+        match self.sink {
+            Sink::Csv(csv) => csv.into_inner().map_err(|e| export_error(e.error())),
+            Sink::Ndjson(mut writer) => {
+                writer.flush().map_err(export_error)?;
+                Ok(writer)
+            }
+        }
+    }
+}
+
+/// Write one row per stake account of the pools, returning the row count
+///
+/// # Errors
+/// Returns `ExportError` if writing fails.
+pub fn export_stake_accounts<'a, W: Write>(
+    writer: W,
+    format: ExportFormat,
+    pools: impl IntoIterator<Item = &'a ProductionPoolData>,
+) -> Result<usize> {
+    let mut export = ExportWriter::<W, StakeAccountRow>::new(writer, format)?;
+    for pool in pools {
+        export.write_all(StakeAccountRow::rows(pool))?;
+    }
+    let rows = export.rows();
+    export.finish()?;
+    Ok(rows)
+}
+
+/// Write one row per (pool, validator), returning the row count
+///
+/// # Errors
+/// Returns `ExportError` if writing fails.
+pub fn export_validator_distribution<'a, W: Write>(
+    writer: W,
+    format: ExportFormat,
+    pools: impl IntoIterator<Item = &'a ProductionPoolData>,
+) -> Result<usize> {
+    let mut export = ExportWriter::<W, ValidatorRow>::new(writer, format)?;
+    for pool in pools {
+        export.write_all(ValidatorRow::rows(pool))?;
+    }
+    let rows = export.rows();
+    export.finish()?;
+    Ok(rows)
+}
+
+/// Write one row per pool, returning the row count
+///
+/// # Errors
+/// Returns `ExportError` if writing fails.
+pub fn export_pool_statistics<'a, W: Write>(
+    writer: W,
+    format: ExportFormat,
+    pools: impl IntoIterator<Item = &'a ProductionPoolData>,
+) -> Result<usize> {
+    let mut export = ExportWriter::<W, PoolStatisticsRow>::new(writer, format)?;
+    export.write_all(pools.into_iter().map(PoolStatisticsRow::from))?;
+    let rows = export.rows();
+    export.finish()?;
+    Ok(rows)
+}
+
+const fn epoch(epoch: u64) -> Option<u64> {
+    if epoch == u64::MAX {
+        None
+    } else {
+        Some(epoch)
+    }
+}
+
+pub(crate) fn export_error(error: impl std::fmt::Display) -> PoolsDataError {
+    PoolsDataError::ExportError {
+        message: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, stake_account};
+    use crate::types::ValidatorStake;
+
+    fn pool() -> ProductionPoolData {
+        let mut data = test_support::pool("jito", vec![stake_account("acc1", "vote,1", u64::MAX)]);
+        let mut stake = ValidatorStake::new();
+        stake.total_delegated = 1_000_000_000;
+        stake.account_count = 1;
+        data.validator_distribution.insert("vote,1".to_string(), stake);
+        data
+    }
+
+    #[test]
+    fn test_columns_match_fields() {
+        fn header<R: ExportRow>(row: &R) -> String {
+            let mut csv = csv::Writer::from_writer(Vec::new());
+            csv.serialize(row).unwrap();
+            let bytes = csv.into_inner().unwrap();
+            String::from_utf8(bytes).unwrap().lines().next().unwrap().to_string()
+        }
+        let data = pool();
+        assert_eq!(header(&StakeAccountRow::new(&data, &data.stake_accounts[0])), StakeAccountRow::COLUMNS.join(","));
+        assert_eq!(header(&ValidatorRow::rows(&data)[0]), ValidatorRow::COLUMNS.join(","));
+        assert_eq!(header(&PoolStatisticsRow::from(&data)), PoolStatisticsRow::COLUMNS.join(","));
+    }
+
+    #[test]
+    fn test_csv_and_ndjson() {
+        let mut data = pool();
+        data.stake_accounts[0].lockup.epoch = u64::MAX;
+        let mut csv = Vec::new();
+        assert_eq!(export_stake_accounts(&mut csv, ExportFormat::Csv, [&data]).unwrap(), 1);
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        // Delimiters are quoted, unset epochs are empty
+        assert!(lines[1].contains(",\"vote,1\",1000000000,500,,staker,"));
+        assert!(lines[1].ends_with(",11111111111111111111111111111111,,0"));
+
+        let mut ndjson = Vec::new();
+        export_validator_distribution(&mut ndjson, ExportFormat::Ndjson, [&data, &data]).unwrap();
+        let rows: Vec<serde_json::Value> =
+            ndjson.split(|b| *b == b'\n').filter(|line| !line.is_empty()).map(|line| serde_json::from_slice(line).unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["total_delegated"], 1_000_000_000);
+
+        let mut empty = Vec::new();
+        export_pool_statistics(&mut empty, ExportFormat::Csv, []).unwrap();
+        assert_eq!(String::from_utf8(empty).unwrap().trim_end(), PoolStatisticsRow::COLUMNS.join(","));
+    }
+}
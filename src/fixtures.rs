@@ -0,0 +1,301 @@
+//! Recorded RPC fixtures for offline regression tests.
+//!
+//! [`RecordingTransport`] wraps another transport and saves every request/response
+//! pair to a fixtures directory; [`ReplayTransport`] serves them back without a
+//! network. Fixtures are keyed by RPC method and params (request ids are ignored),
+//! one JSON file per distinct request or batch, optionally zstd-compressed.
+
+use crate::error::{PoolsDataError, Result};
+use crate::transport::RpcTransport;
+use futures::future::{BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// One recorded request/response pair
+#[derive(Debug, Serialize, Deserialize)]
+struct Fixture {
+    /// `method` and `params` of the request
+    request: Value,
+    /// Response body
+    response: Value,
+}
+
+/// Request identity: method and params, without `jsonrpc` and `id` (per item for batches)
+fn fixture_request(request: &Value) -> Value {
+    match request {
+        Value::Array(requests) => requests.iter().map(fixture_request).collect(),
+        request => json!({ "method": request["method"], "params": request["params"] }),
+    }
+}
+
+/// Order batch responses like their requests (servers may answer in any order)
+fn order_batch_response(request: &Value, response: &mut Value) {
+    if let (Value::Array(requests), Value::Array(responses)) = (request, response) {
+        responses.sort_by_key(|item| requests.iter().position(|request| request["id"] == item["id"]));
+    }
+}
+
+/// Fixture file name (without extension) for a request
+///
+/// FNV-1a of the serialized request: stable across Rust releases, unlike `DefaultHasher`.
+fn fixture_name(request: &Value) -> String {
+    let hash = request.to_string().bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    let method = request["method"].as_str().unwrap_or(if request.is_array() { "batch" } else { "request" });
+    format!("{method}-{hash:016x}")
+}
+
+fn storage_error(path: &Path, error: impl std::fmt::Display) -> PoolsDataError {
+    PoolsDataError::StorageError {
+        message: format!("Fixture {}: {error}", path.display()),
+    }
+}
+
+/// Transport that records every successful exchange of an inner transport
+///
+/// Record once against a real endpoint, then run tests on [`ReplayTransport`]:
+///
+/// ```no_run
+/// use solana_pools_data_lib::{HttpTransport, PoolsDataClient, RecordingTransport};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// # async fn record() -> solana_pools_data_lib::Result<()> {
+/// let http = HttpTransport::new("https://api.mainnet-beta.solana.com".to_string(), Duration::from_secs(60));
+/// let client = PoolsDataClient::builder()
+///     .transport(Arc::new(RecordingTransport::new(Arc::new(http), "fixtures/rpc")))
+///     .build("https://api.mainnet-beta.solana.com")
+///     .and_then(PoolsDataClient::from_config)?;
+/// client.fetch_pools(&["jito"]).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RecordingTransport {
+    inner: Arc<dyn RpcTransport>,
+    dir: PathBuf,
+    #[cfg(feature = "zstd")]
+    compress: bool,
+}
+
+impl RecordingTransport {
+    /// Record the exchanges of `inner` into `dir` (created on first write)
+    pub fn new(inner: Arc<dyn RpcTransport>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            dir: dir.into(),
+            #[cfg(feature = "zstd")]
+            compress: false,
+        }
+    }
+
+    /// Write zstd-compressed fixtures (`.json.zst`)
+    #[cfg(feature = "zstd")]
+    #[must_use]
+    pub const fn compressed(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    async fn record(&self, request: &Value, response: &str) -> Result<()> {
+        let Ok(mut response) = serde_json::from_str(response) else {
+            log::warn!("Not recording non-JSON response to {}", request["method"]);
+            return Ok(());
+        };
+        order_batch_response(request, &mut response);
+        let request = fixture_request(request);
+        let name = fixture_name(&request);
+        let contents = serde_json::to_vec_pretty(&Fixture { request, response })?;
+        tokio::fs::create_dir_all(&self.dir).await.map_err(|e| storage_error(&self.dir, e))?;
+
+        #[cfg(feature = "zstd")]
+        if self.compress {
+            let path = self.dir.join(format!("{name}.json.zst"));
+            let compressed = zstd::encode_all(contents.as_slice(), 3).map_err(|e| storage_error(&path, e))?;
+            return tokio::fs::write(&path, compressed).await.map_err(|e| storage_error(&path, e));
+        }
+
+        let path = self.dir.join(format!("{name}.json"));
+        tokio::fs::write(&path, contents).await.map_err(|e| storage_error(&path, e))
+    }
+}
+
+impl RpcTransport for RecordingTransport {
+    fn send(&self, body: String) -> BoxFuture<'_, Result<String>> {
+        async move {
+            let request = serde_json::from_str(&body)?;
+            let response = self.inner.send(body).await?;
+            self.record(&request, &response).await?;
+            Ok(response)
+        }
+        .boxed()
+    }
+}
+
+/// Transport serving responses recorded by [`RecordingTransport`]
+///
+/// Requests without a fixture fail with a (non-retryable) `StorageError`.
+#[derive(Debug)]
+pub struct ReplayTransport {
+    dir: PathBuf,
+}
+
+impl ReplayTransport {
+    /// Replay the fixtures in `dir`
+    ///
+    /// # Errors
+    /// Returns `StorageError` if `dir` is not a directory.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        if !dir.is_dir() {
+            return Err(storage_error(&dir, "not a directory"));
+        }
+        Ok(Self { dir })
+    }
+
+    async fn load(&self, name: &str) -> Result<Fixture> {
+        let path = self.dir.join(format!("{name}.json"));
+        if let Ok(contents) = tokio::fs::read(&path).await {
+            return serde_json::from_slice(&contents).map_err(|e| storage_error(&path, e));
+        }
+
+        let compressed = self.dir.join(format!("{name}.json.zst"));
+        let Ok(contents) = tokio::fs::read(&compressed).await else {
+            return Err(storage_error(&path, "no recorded response for this request"));
+        };
+        #[cfg(feature = "zstd")]
+        {
+            let contents = zstd::decode_all(contents.as_slice()).map_err(|e| storage_error(&compressed, e))?;
+            serde_json::from_slice(&contents).map_err(|e| storage_error(&compressed, e))
+        }
+        #[cfg(not(feature = "zstd"))]
+        {
+            drop(contents);
+            Err(storage_error(&compressed, "compressed fixtures require the `zstd` feature"))
+        }
+    }
+}
+
+impl RpcTransport for ReplayTransport {
+    fn send(&self, body: String) -> BoxFuture<'_, Result<String>> {
+        async move {
+            let request: Value = serde_json::from_str(&body)?;
+            let mut fixture = self.load(&fixture_name(&fixture_request(&request))).await?;
+            match (&mut fixture.response, &request) {
+                (Value::Array(responses), Value::Array(requests)) => {
+                    for (response, request) in responses.iter_mut().zip(requests) {
+                        response["id"] = request["id"].clone();
+                    }
+                }
+                (response, _) if response.get("id").is_some() => response["id"] = request["id"].clone(),
+                _ => {}
+            }
+            Ok(fixture.response.to_string())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::mock_client_with;
+    use crate::transport::{MockReply, MockTransport};
+    use crate::{PoolsDataClient, ProductionPoolData};
+    use std::collections::BTreeMap;
+
+    /// Pools covered by the golden fixtures
+    const GOLDEN_POOLS: &[&str] = &["jito", "blazestake"];
+
+    /// Fixture sets: `synthetic` (generated, checked in) and `recorded` (captured
+    /// from a real endpoint by `record_fixtures`, used when present)
+    const FIXTURE_SETS: &[&str] = &["synthetic", "recorded"];
+
+    fn fixtures_dir(set: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures").join(set)
+    }
+
+    fn client(transport: Arc<impl RpcTransport + 'static>) -> PoolsDataClient {
+        mock_client_with(transport, |builder| builder.retry_attempts(0))
+    }
+
+    /// Statistics and validator distribution of a pool, in a stable order
+    fn golden(pool: &ProductionPoolData) -> Value {
+        json!({
+            "statistics": pool.statistics,
+            "validator_distribution": pool.validator_distribution.iter().collect::<BTreeMap<_, _>>(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let dir = std::env::temp_dir().join(format!("pools-fixtures-{}", std::process::id()));
+        let mock = Arc::new(MockTransport::new().reply("getEpochInfo", MockReply::Result(json!({
+            "absoluteSlot": 10, "blockHeight": 9, "epoch": 1, "slotIndex": 10, "slotsInEpoch": 32
+        }))));
+        let recorded = client(Arc::new(RecordingTransport::new(mock, &dir)))
+            .fetch_epoch_info()
+            .await
+            .unwrap();
+
+        // Request ids differ between runs; replay matches on method and params
+        let replay = client(Arc::new(ReplayTransport::open(&dir).unwrap()));
+        replay.fetch_epoch_info().await.unwrap();
+        assert_eq!(replay.fetch_epoch_info().await.unwrap(), recorded);
+        assert!(matches!(replay.fetch_epoch_schedule().await, Err(PoolsDataError::StorageError { .. })));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(ReplayTransport::open(&dir).is_err());
+    }
+
+    /// Fetch the golden pools from each fixture set's `rpc` and compare with its `golden`
+    ///
+    /// The `synthetic` set only exercises the fetch and statistics pipeline end to
+    /// end; regression coverage against mainnet data comes from the `recorded` set.
+    /// Missing sets are skipped, but the test fails if none was found.
+    /// Set `UPDATE_GOLDEN=1` to rewrite the golden files after re-recording.
+    #[tokio::test]
+    async fn test_golden_pools() {
+        let mut ran = 0;
+        for set in FIXTURE_SETS {
+            let rpc_dir = fixtures_dir(set).join("rpc");
+            if !rpc_dir.is_dir() {
+                eprintln!("Fixture set `{set}` not found at {}, skipped", rpc_dir.display());
+                continue;
+            }
+            let transport = ReplayTransport::open(&rpc_dir).unwrap();
+            let pools = client(Arc::new(transport)).fetch_pools(GOLDEN_POOLS).await.unwrap();
+            ran += 1;
+
+            let golden_dir = fixtures_dir(set).join("golden");
+            for name in GOLDEN_POOLS {
+                let actual = golden(&pools[*name]);
+                let path = golden_dir.join(format!("{name}.json"));
+                if std::env::var_os("UPDATE_GOLDEN").is_some() {
+                    std::fs::create_dir_all(&golden_dir).unwrap();
+                    std::fs::write(&path, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+                    continue;
+                }
+                let expected: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+                assert_eq!(actual, expected, "{set} {name} differs from {}", path.display());
+            }
+        }
+        assert!(ran > 0, "No fixture set found in {}", fixtures_dir("").display());
+    }
+
+    /// Record `fixtures/recorded/rpc` from `SOLANA_RPC_URL` (then run with `UPDATE_GOLDEN=1`)
+    #[tokio::test]
+    #[ignore = "requires network access"]
+    async fn record_fixtures() {
+        let url = std::env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
+        let http = crate::transport::HttpTransport::new(url, std::time::Duration::from_secs(120));
+        let dir = fixtures_dir("recorded").join("rpc");
+        client(Arc::new(RecordingTransport::new(Arc::new(http), dir)))
+            .fetch_pools(GOLDEN_POOLS)
+            .await
+            .unwrap();
+    }
+}
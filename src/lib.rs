@@ -29,23 +29,74 @@
 //! }
 //! ```
 
+mod cache;
 mod client;
+#[cfg(feature = "parquet")]
+mod columnar;
 mod config;
+mod decode;
+mod diff;
+mod endpoints;
 mod error;
+mod exchange_rate;
+mod export;
+mod fixtures;
+mod marinade;
+#[cfg(feature = "metrics")]
+mod metrics;
 mod pools;
+mod registry;
 mod rpc;
+#[cfg(feature = "server")]
+mod server;
+mod spl_stake_pool;
+#[cfg(feature = "sqlite")]
+mod store;
+mod transport;
 mod types;
+mod watcher;
+#[cfg(feature = "websocket")]
+mod websocket;
+pub mod stake_math;
 pub mod statistics;
 pub mod statistics_calc;
 
+/// Internals compared by the benchmarks; not a stable API
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::rpc::{parse_stake_accounts_buffered, RpcClient};
+}
+
 #[cfg(test)]
 mod statistics_calc_tests;
+#[cfg(test)]
+mod test_support;
 
 pub use client::*;
+#[cfg(feature = "parquet")]
+pub use columnar::*;
 pub use config::*;
+pub use diff::*;
+pub use endpoints::EndpointHealth;
 pub use error::*;
+pub use exchange_rate::*;
+pub use export::*;
+pub use fixtures::*;
+pub use marinade::*;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
 pub use pools::*;
+pub use registry::*;
+#[cfg(feature = "server")]
+pub use server::*;
+pub use spl_stake_pool::*;
+#[cfg(feature = "sqlite")]
+pub use store::*;
+pub use transport::*;
 pub use types::*;
+pub use watcher::*;
+#[cfg(feature = "websocket")]
+pub use websocket::*;
 
 // Re-export commonly used types
 pub use serde_json;
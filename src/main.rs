@@ -1,60 +1,556 @@
-//! Clean demonstration of the Pools Data Library
+//! Command line interface for the Pools Data Library
 
-use solana_pools_data_lib::PoolsDataClient;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::{json, Value};
+use solana_pools_data_lib::{
+    statistics_calc, PoolDiff, PoolRegistry, PoolsDataClient, PoolsDataClientBuilder, PoolsDataResult,
+    ProductionPoolData,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+type CliResult<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+#[derive(Parser)]
+#[command(name = "pools-data", version, about = "Fetch and inspect Solana stake pool data")]
+struct Cli {
+    #[command(flatten)]
+    global: GlobalArgs,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct GlobalArgs {
+    /// RPC endpoint URL
+    #[arg(long, global = true, env = "SOLANA_RPC_URL", default_value = "https://api.mainnet-beta.solana.com")]
+    rpc: String,
+    /// Fallback RPC endpoint (repeatable)
+    #[arg(long = "endpoint", global = true)]
+    endpoints: Vec<String>,
+    /// Builder preset
+    #[arg(long, global = true, value_enum, default_value_t = Preset::Auto)]
+    preset: Preset,
+    /// Pool registry overlay file (TOML or JSON)
+    #[arg(long, global = true)]
+    registry: Option<PathBuf>,
+    /// getProgramAccounts queries per JSON-RPC batch request (1 disables batching)
+    #[arg(long, global = true, default_value_t = 1)]
+    batch_size: usize,
+    /// Output format
+    #[arg(long, short, global = true, value_enum, default_value_t = Format::Table)]
+    format: Format,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Preset {
+    /// Detect from the RPC URL
+    Auto,
+    Public,
+    Private,
+    Alchemy,
+    Quicknode,
+    Helius,
+    Development,
+    Enterprise,
+    HighFrequency,
+    Batch,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Json,
+    Table,
+    Csv,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List pools in the registry
+    List {
+        /// Only pools of this operator
+        #[arg(long)]
+        operator: Option<String>,
+        /// Only active pools
+        #[arg(long)]
+        active: bool,
+    },
+    /// Fetch stake accounts of pools
+    Fetch {
+        /// Pool names (all pools if empty)
+        pools: Vec<String>,
+        /// Output the debug format with all RPC fields and failures
+        #[arg(long)]
+        debug: bool,
+        /// Include on-chain pool program state
+        #[arg(long)]
+        pool_state: bool,
+        /// Include SOL/LST exchange rates
+        #[arg(long)]
+        exchange_rates: bool,
+    },
+    /// Per-pool statistics classified at an epoch
+    Stats {
+        /// Pool names (all pools if empty)
+        pools: Vec<String>,
+        /// Epoch number or `auto` to resolve it from RPC
+        #[arg(long, default_value = "auto")]
+        epoch: String,
+        /// Use effective stake (runtime warmup/cooldown) at the current epoch instead of whole-account states
+        #[arg(long, conflicts_with = "epoch")]
+        effective: bool,
+    },
+    /// Validator distribution of a pool
+    Validators {
+        /// Pool name
+        pool: String,
+        /// Show only the largest N validators
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Compare two JSON outputs of `fetch`
+    Diff {
+        /// Earlier fetch (a pool, a map of pools or a `--debug` result)
+        previous: PathBuf,
+        /// Later fetch (a pool, a map of pools or a `--debug` result)
+        current: PathBuf,
+    },
+    /// Check RPC connectivity and endpoint health
+    Health,
+}
+
+/// Tabular output rendered as a table, CSV or JSON objects
+struct Report {
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl Report {
+    fn new(columns: &[&'static str]) -> Self {
+        Self {
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, row: Vec<Value>) {
+        debug_assert_eq!(row.len(), self.columns.len());
+        self.rows.push(row);
+    }
+
+    fn to_json(&self) -> Value {
+        Value::Array(
+            self.rows
+                .iter()
+                .map(|row| Value::Object(self.columns.iter().map(|c| (*c).to_string()).zip(row.iter().cloned()).collect()))
+                .collect(),
+        )
+    }
+
+    fn write_table(&self, out: &mut impl Write) -> io::Result<()> {
+        let cells: Vec<Vec<String>> = self.rows.iter().map(|row| row.iter().map(cell).collect()).collect();
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| cells.iter().map(|row| row[i].len()).chain([column.len()]).max().unwrap_or(0))
+            .collect();
+
+        let header: Vec<String> = self.columns.iter().zip(&widths).map(|(c, w)| format!("{c:<w$}")).collect();
+        writeln!(out, "{}", header.join("  ").trim_end())?;
+        let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        writeln!(out, "{}", rule.join("  "))?;
+        for (row, values) in cells.iter().zip(&self.rows) {
+            let line: Vec<String> = row
+                .iter()
+                .zip(values)
+                .zip(&widths)
+                .map(|((text, value), w)| if value.is_number() { format!("{text:>w$}") } else { format!("{text:<w$}") })
+                .collect();
+            writeln!(out, "{}", line.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+
+    fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        let header: Vec<String> = self.columns.iter().map(|c| csv_field(c)).collect();
+        writeln!(out, "{}", header.join(","))?;
+        for row in &self.rows {
+            let line: Vec<String> = row.iter().map(|value| csv_field(&cell(value))).collect();
+            writeln!(out, "{}", line.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(cell).collect::<Vec<_>>().join(";"),
+        other => other.to_string(),
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Print a report; JSON prints `full` when given, the report rows otherwise
+fn emit(format: Format, report: &Report, full: Option<Value>) -> CliResult {
+    let mut out = io::stdout().lock();
+    let written = match format {
+        Format::Json => writeln!(out, "{}", serde_json::to_string_pretty(&full.unwrap_or_else(|| report.to_json()))?),
+        Format::Table => report.write_table(&mut out),
+        Format::Csv => report.write_csv(&mut out),
+    };
+    match written {
+        // Output piped into `head` and friends
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        other => Ok(other?),
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn sol(lamports: u64) -> Value {
+    json!((lamports as f64 / LAMPORTS_PER_SOL * 1000.0).round() / 1000.0)
+}
+
+fn load_registry(global: &GlobalArgs) -> CliResult<PoolRegistry> {
+    let registry = PoolRegistry::embedded();
+    Ok(match &global.registry {
+        Some(path) => registry.merge_file(path)?,
+        None => registry,
+    })
+}
+
+fn build_client(global: &GlobalArgs, pool_state: bool, exchange_rates: bool) -> CliResult<PoolsDataClient> {
+    let builder = PoolsDataClientBuilder::new();
+    let builder = match global.preset {
+        Preset::Auto => builder.auto_config(&global.rpc),
+        Preset::Public => builder.public_rpc_config(),
+        Preset::Private => builder.private_rpc_config(),
+        Preset::Alchemy => builder.alchemy_config(),
+        Preset::Quicknode => builder.quicknode_config(),
+        Preset::Helius => builder.helius_config(),
+        Preset::Development => builder.development_config(),
+        Preset::Enterprise => builder.enterprise_config(),
+        Preset::HighFrequency => builder.high_frequency_config(),
+        Preset::Batch => builder.batch_processing_config(),
+    };
+    let config = builder
+        .registry(load_registry(global)?)
+        .endpoints(global.endpoints.iter().map(|url| solana_pools_data_lib::EndpointConfig::new(url).auto_config()))
+        .include_pool_state(pool_state)
+        .include_exchange_rates(exchange_rates)
+        .batch_size(global.batch_size)
+        .build(&global.rpc)?;
+    Ok(PoolsDataClient::from_config(config)?)
+}
+
+/// Requested pool names, or every registry pool when none are given
+fn pool_names(client: &PoolsDataClient, pools: &[String]) -> Vec<String> {
+    if pools.is_empty() {
+        client.registry().names()
+    } else {
+        pools.to_vec()
+    }
+}
+
+fn pool_summary_report<'a>(pools: impl IntoIterator<Item = &'a ProductionPoolData>) -> Report {
+    let mut report = Report::new(&[
+        "pool",
+        "accounts",
+        "validators",
+        "total_sol",
+        "active_sol",
+        "activating_sol",
+        "deactivating_sol",
+        "deactivated_sol",
+        "served_by",
+    ]);
+    for pool in pools {
+        let stats = &pool.statistics;
+        report.push(vec![
+            json!(pool.pool_name),
+            json!(stats.total_accounts),
+            json!(stats.validator_count),
+            sol(stats.total_lamports),
+            sol(stats.active_stake_lamports),
+            sol(stats.activating_stake_lamports),
+            sol(stats.deactivating_stake_lamports),
+            sol(stats.deactivated_stake_lamports),
+            json!(pool.served_by),
+        ]);
+    }
+    report
+}
+
+fn list(global: &GlobalArgs, operator: Option<&str>, active: bool) -> CliResult {
+    let registry = load_registry(global)?;
+    let mut report = Report::new(&[
+        "name",
+        "display_name",
+        "operator",
+        "program",
+        "status",
+        "authority",
+        "authority_role",
+        "lst_mint",
+        "tags",
+    ]);
+    let pools: Vec<_> = registry
+        .pools()
+        .iter()
+        .filter(|pool| operator.map_or(true, |op| pool.operator == op))
+        .filter(|pool| !active || pool.is_active())
+        .collect();
+    for pool in &pools {
+        report.push(vec![
+            json!(pool.name),
+            json!(pool.display_name),
+            json!(pool.operator),
+            serde_json::to_value(pool.program)?,
+            serde_json::to_value(pool.status)?,
+            json!(pool.authority),
+            serde_json::to_value(pool.authority_role)?,
+            json!(pool.lst_mint),
+            json!(pool.tags),
+        ]);
+    }
+    emit(global.format, &report, Some(serde_json::to_value(&pools)?))
+}
+
+async fn fetch(global: &GlobalArgs, pools: &[String], debug: bool, pool_state: bool, exchange_rates: bool) -> CliResult {
+    let client = build_client(global, pool_state, exchange_rates)?;
+    let names = pool_names(&client, pools);
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let result = client.fetch_pools_debug(&names).await?;
+
+    for (name, error) in &result.failed {
+        eprintln!("{name}: {} (after {} attempts)", error.error, error.attempts);
+    }
+
+    let production: BTreeMap<&String, ProductionPoolData> =
+        result.successful.iter().map(|(name, pool)| (name, ProductionPoolData::from(pool))).collect();
+    let report = pool_summary_report(production.values());
+    let full = if debug { serde_json::to_value(&result)? } else { serde_json::to_value(&production)? };
+    emit(global.format, &report, Some(full))
+}
+
+async fn stats(global: &GlobalArgs, pools: &[String], epoch: &str, effective: bool) -> CliResult {
+    let client = build_client(global, false, false)?;
+    let names = pool_names(&client, pools);
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+    let columns = [
+        "pool",
+        "epoch",
+        "accounts",
+        "active_accounts",
+        "activating_accounts",
+        "deactivating_accounts",
+        "deactivated_accounts",
+        "total_sol",
+        "active_sol",
+        "activating_sol",
+        "deactivating_sol",
+        "deactivated_sol",
+    ];
+    let mut report = Report::new(&columns);
+
+    if effective {
+        let epoch = client.fetch_epoch_info().await?.epoch;
+        let statistics: BTreeMap<String, _> = client.fetch_effective_pool_statistics(&names).await?.into_iter().collect();
+        for (name, stats) in &statistics {
+            report.push(vec![
+                json!(name),
+                json!(epoch),
+                json!(stats.total_accounts),
+                json!(stats.active_accounts),
+                json!(stats.activating_accounts),
+                json!(stats.deactivating_accounts),
+                json!(stats.deactivated_accounts),
+                sol(stats.total_lamports),
+                sol(stats.active_stake_lamports),
+                sol(stats.activating_stake_lamports),
+                sol(stats.deactivating_stake_lamports),
+                sol(stats.deactivated_stake_lamports),
+            ]);
+        }
+        return emit(global.format, &report, None);
+    }
+
+    let epoch = if epoch == "auto" {
+        client.fetch_epoch_info().await?.epoch
+    } else {
+        epoch.parse().map_err(|_| format!("Invalid epoch '{epoch}': expected a number or `auto`"))?
+    };
+    let fetched: BTreeMap<String, ProductionPoolData> = client.fetch_pools(&names).await?.into_iter().collect();
+    for (name, pool) in &fetched {
+        let summary = statistics_calc::calculate_pool_statistics_full(pool, epoch)?.summary();
+        report.push(vec![
+            json!(name),
+            json!(epoch),
+            json!(summary.total_accounts),
+            json!(summary.active_accounts),
+            json!(summary.activating_accounts),
+            json!(summary.deactivating_accounts),
+            json!(summary.deactivated_accounts),
+            sol(summary.total_lamports),
+            sol(summary.active_stake_lamports),
+            sol(summary.activating_stake_lamports),
+            sol(summary.deactivating_stake_lamports),
+            sol(summary.deactivated_stake_lamports),
+        ]);
+    }
+    emit(global.format, &report, None)
+}
+
+async fn validators(global: &GlobalArgs, pool: &str, limit: Option<usize>) -> CliResult {
+    let client = build_client(global, false, false)?;
+    let pools = client.fetch_pools(&[pool]).await?;
+    let data = pools.into_values().next().ok_or_else(|| format!("Pool '{pool}' not found"))?;
+
+    let total: u64 = data.validator_distribution.values().map(|stake| stake.total_delegated).sum();
+    let mut validators: Vec<_> = data.validator_distribution.iter().collect();
+    validators.sort_by(|a, b| b.1.total_delegated.cmp(&a.1.total_delegated).then_with(|| a.0.cmp(b.0)));
+    validators.truncate(limit.unwrap_or(usize::MAX));
+
+    let mut report = Report::new(&["validator", "accounts", "delegated_lamports", "delegated_sol", "share_percent"]);
+    for (validator, stake) in validators {
+        #[allow(clippy::cast_precision_loss)]
+        let share = if total == 0 { 0.0 } else { stake.total_delegated as f64 * 100.0 / total as f64 };
+        report.push(vec![
+            json!(validator),
+            json!(stake.account_count),
+            json!(stake.total_delegated),
+            sol(stake.total_delegated),
+            json!((share * 100.0).round() / 100.0),
+        ]);
+    }
+    emit(global.format, &report, None)
+}
+
+/// Read a `fetch` output: a single pool, a map of pools or a `fetch --debug` result
+fn read_pools(path: &Path) -> CliResult<HashMap<String, ProductionPoolData>> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    if let Ok(pool) = serde_json::from_str::<ProductionPoolData>(&content) {
+        return Ok(HashMap::from([(pool.pool_name.clone(), pool)]));
+    }
+    if let Ok(result) = serde_json::from_str::<PoolsDataResult>(&content) {
+        return Ok(result.production_pools().into_iter().map(|pool| (pool.pool_name.clone(), pool)).collect());
+    }
+    serde_json::from_str(&content).map_err(|e| format!("{} is not a fetch output: {e}", path.display()).into())
+}
+
+fn diff(global: &GlobalArgs, previous: &Path, current: &Path) -> CliResult {
+    let previous = read_pools(previous)?;
+    let current = read_pools(current)?;
+
+    let diffs: BTreeMap<&String, PoolDiff> = current
+        .iter()
+        .filter_map(|(name, pool)| previous.get(name).map(|old| (name, pool.diff_since(old))))
+        .collect();
+    for name in previous.keys().filter(|name| !current.contains_key(*name)) {
+        eprintln!("{name}: only in the previous file");
+    }
+    for name in current.keys().filter(|name| !previous.contains_key(*name)) {
+        eprintln!("{name}: only in the current file");
+    }
+
+    let mut report = Report::new(&[
+        "pool",
+        "added",
+        "removed",
+        "stake_changes",
+        "validators_entered",
+        "validators_left",
+        "moved",
+        "authority_changes",
+        "lockup_changes",
+        "total_sol_delta",
+    ]);
+    for (name, diff) in &diffs {
+        #[allow(clippy::cast_precision_loss)]
+        let total_sol_delta = diff.statistics_delta.total_lamports as f64 / LAMPORTS_PER_SOL;
+        report.push(vec![
+            json!(name),
+            json!(diff.added_accounts.len()),
+            json!(diff.removed_accounts.len()),
+            json!(diff.stake_changes.len()),
+            json!(diff.validators_entered.len()),
+            json!(diff.validators_left.len()),
+            json!(diff.moved_delegations.len()),
+            json!(diff.authority_changes.len()),
+            json!(diff.lockup_changes.len()),
+            json!((total_sol_delta * 1000.0).round() / 1000.0),
+        ]);
+    }
+    emit(global.format, &report, Some(serde_json::to_value(&diffs)?))
+}
+
+async fn health(global: &GlobalArgs) -> CliResult {
+    let client = Arc::new(build_client(global, false, false)?);
+    let connection = client.test_connection().await;
+    let epoch = if connection.is_ok() { client.fetch_epoch_info().await.ok() } else { None };
+
+    let mut report = Report::new(&["endpoint", "healthy", "weight", "requests", "errors", "average_latency_ms", "score"]);
+    for endpoint in client.endpoint_health() {
+        report.push(vec![
+            json!(endpoint.name),
+            json!(endpoint.healthy),
+            json!(endpoint.weight),
+            json!(endpoint.requests),
+            json!(endpoint.errors),
+            json!(endpoint.average_latency_ms.map(|ms| (ms * 10.0).round() / 10.0)),
+            json!((endpoint.score * 100.0).round() / 100.0),
+        ]);
+    }
+
+    let full = json!({
+        "connected": connection.is_ok(),
+        "error": connection.as_ref().err().map(ToString::to_string),
+        "epoch": epoch,
+        "endpoints": client.endpoint_health(),
+    });
+    emit(global.format, &report, Some(full))?;
+
+    if let Some(epoch) = &epoch {
+        eprintln!("epoch {}, slot {} ({} slots remaining)", epoch.epoch, epoch.absolute_slot, epoch.slots_remaining());
+    }
+    Ok(connection?)
+}
 
 #[tokio::main]
-async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    println!("Solana Pools Data Library - Demo");
-    println!("================================");
-
-    println!("\nTwo Output Formats Available:");
-    println!("   1. fetch_pools() - Production data (static fields removed)");
-    println!("   2. fetch_pools_debug() - Complete data (ALL fields for debugging)");
-
-    println!("\nUsage Examples:");
-    println!("   // Production use");
-    println!("   let production_data = client.fetch_pools(&[\"jito\"]).await?;");
-    println!("   database.store(production_data).await?; // Safe");
-    println!();
-    println!("   // Debugging");
-    println!("   let debug_data = client.fetch_pools_debug(&[\"jito\"]).await?;");
-    println!("   // Contains ALL RPC fields for analysis");
-
-    // Show static field analysis
-    let analysis = PoolsDataClient::get_static_field_analysis();
-
-    println!("\nStatic Fields Removed in Production Format:");
-    for field in &analysis.static_fields {
-        println!(
-            "   - {} = {} ({})",
-            field.name, field.value, field.description
-        );
-    }
-
-    println!("\nSize Optimization:");
-    println!(
-        "   - {} bytes saved per account",
-        analysis.size_analysis.estimated_bytes_saved_per_account
-    );
-    println!(
-        "   - {:.1}% size reduction",
-        analysis.size_analysis.estimated_size_reduction_percent
-    );
-
-    println!("\nInitialization:");
-    println!("   let client = PoolsDataClient::builder()");
-    println!("       .rate_limit(10)");
-    println!("       .build(\"your_rpc_url\")");
-    println!("       .and_then(PoolsDataClient::from_config)?;");
-
-    // Example with public RPC (no actual calls)
-    let _client = PoolsDataClient::builder()
-        .build("https://api.mainnet-beta.solana.com")
-        .and_then(PoolsDataClient::from_config)?;
-
-    println!("\nClient ready. Available methods:");
-    println!("   - client.fetch_pools(&pools) for production");
-    println!("   - client.fetch_pools_debug(&pools) for debugging");
-
-    Ok(())
+async fn main() {
+    let cli = Cli::parse();
+    let global = &cli.global;
+
+    let result = match &cli.command {
+        Command::List { operator, active } => list(global, operator.as_deref(), *active),
+        Command::Fetch {
+            pools,
+            debug,
+            pool_state,
+            exchange_rates,
+        } => fetch(global, pools, *debug, *pool_state, *exchange_rates).await,
+        Command::Stats { pools, epoch, effective } => stats(global, pools, epoch, *effective).await,
+        Command::Validators { pool, limit } => validators(global, pool, *limit).await,
+        Command::Diff { previous, current } => diff(global, previous, current),
+        Command::Health => health(global).await,
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,459 @@
+//! Marinade liquid staking program state.
+//!
+//! Decodes Marinade's `State` account together with its validator list and stake
+//! list, so Marinade-reported balances can be reconciled against the pool's
+//! stake accounts.
+
+use crate::decode::ByteReader;
+use crate::error::{PoolsDataError, Result};
+use crate::types::PoolStatistics;
+use serde::{Deserialize, Serialize};
+
+/// Marinade liquid staking program
+pub const MARINADE_PROGRAM_ID: &str = "MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD";
+
+/// Marinade mainnet `State` account
+pub const MARINADE_STATE_ADDRESS: &str = "8szGkuLTAux9XMgZ2vtY39jVSowEcpBfFfD8hXSEqdGC";
+
+/// Staker authority of Marinade liquid staking stake accounts (the `marinade` registry entry)
+pub const MARINADE_STAKE_AUTHORITY: &str = "4bZ6o3eUUNXhKuqjdCnCoPAoLgWiuLYixKaxoa8PpiKk";
+
+/// Denominator of `msol_price`
+pub const MARINADE_PRICE_DENOMINATOR: u64 = 0x1_0000_0000;
+
+/// Anchor account discriminator length
+const DISCRIMINATOR_SIZE: usize = 8;
+
+/// Minimum serialized size of a stake list item
+const STAKE_RECORD_SIZE: usize = 49;
+
+/// Minimum serialized size of a validator list item
+const VALIDATOR_RECORD_SIZE: usize = 53;
+
+/// Validator entry of Marinade's validator list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarinadeValidatorRecord {
+    /// Validator vote account
+    pub vote_account: String,
+    /// Stake delegated by Marinade to this validator
+    pub active_balance: u64,
+    /// Score used to distribute stake
+    pub score: u32,
+    /// Last epoch stake was moved to or from this validator
+    pub last_stake_delta_epoch: u64,
+}
+
+/// Stake account entry of Marinade's stake list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarinadeStakeRecord {
+    /// Stake account address
+    pub stake_account: String,
+    /// Delegated lamports as of `last_update_epoch`
+    pub last_update_delegated_lamports: u64,
+    /// Last epoch the record was updated
+    pub last_update_epoch: u64,
+    /// Whether the account is being emergency unstaked
+    pub is_emergency_unstaking: bool,
+}
+
+/// Decoded Marinade `State` with its validator and stake lists
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarinadeState {
+    /// `State` account address
+    pub address: String,
+    /// mSOL mint
+    pub msol_mint: String,
+    /// Admin authority
+    pub admin_authority: String,
+    /// Reward fee in basis points
+    pub reward_fee_bps: u32,
+    /// Stake list account
+    pub stake_list: String,
+    /// Validator list account
+    pub validator_list: String,
+    /// Validator manager authority
+    pub validator_manager_authority: String,
+    /// Sum of all validator scores
+    pub total_validator_score: u32,
+    /// Total stake delegated by Marinade
+    pub total_active_balance: u64,
+    /// Lamports in the reserve available for staking
+    pub available_reserve_balance: u64,
+    /// mSOL supply tracked by the program
+    pub msol_supply: u64,
+    /// Lamports per mSOL, scaled by [`MARINADE_PRICE_DENOMINATOR`]
+    pub msol_price: u64,
+    /// Delayed unstake tickets not yet claimed
+    pub circulating_ticket_count: u64,
+    /// Lamports owed to delayed unstake tickets
+    pub circulating_ticket_balance: u64,
+    /// Lamports lent from the reserve
+    pub lent_from_reserve: u64,
+    /// Maximum SOL that can be staked
+    pub staking_sol_cap: u64,
+    /// Lamports cooling down from emergency unstakes
+    pub emergency_cooling_down: u64,
+    /// Lamports cooling down from delayed unstakes
+    pub delayed_unstake_cooling_down: u64,
+    /// Whether the program is paused
+    pub paused: bool,
+    /// Validators in the validator list
+    pub validators: Vec<MarinadeValidatorRecord>,
+    /// Stake accounts in the stake list
+    pub stake_records: Vec<MarinadeStakeRecord>,
+}
+
+/// Comparison of Marinade-reported balances with observed stake accounts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarinadeReconciliation {
+    /// `total_active_balance` from the `State` account
+    pub reported_active_balance: u64,
+    /// Active and activating stake from the pool's stake accounts
+    pub observed_active_lamports: u64,
+    /// Reported minus observed lamports
+    pub difference_lamports: i128,
+    /// Stake accounts in Marinade's stake list
+    pub reported_stake_accounts: usize,
+    /// Delegated stake accounts observed on chain
+    pub observed_stake_accounts: usize,
+    /// Stake accounts flagged for emergency unstake
+    pub emergency_unstaking_accounts: usize,
+}
+
+/// Location of a Marinade list account
+struct ListRef {
+    account: String,
+    item_size: usize,
+    count: usize,
+}
+
+impl MarinadeState {
+    /// Decode `State`, validator list and stake list account data
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::ParseError` if any account is truncated.
+    pub fn from_account_data(
+        address: &str,
+        state_data: &[u8],
+        validator_list_data: &[u8],
+        stake_list_data: &[u8],
+    ) -> Result<Self> {
+        let (mut state, validator_list, stake_list) = Self::decode_state_with_lists(address, state_data)?;
+
+        state.validators = decode_list(validator_list_data, &validator_list, VALIDATOR_RECORD_SIZE, |reader| {
+            Ok(MarinadeValidatorRecord {
+                vote_account: reader.read_pubkey()?,
+                active_balance: reader.read_u64()?,
+                score: reader.read_u32()?,
+                last_stake_delta_epoch: reader.read_u64()?,
+            })
+        })?;
+        state.stake_records = decode_list(stake_list_data, &stake_list, STAKE_RECORD_SIZE, |reader| {
+            Ok(MarinadeStakeRecord {
+                stake_account: reader.read_pubkey()?,
+                last_update_delegated_lamports: reader.read_u64()?,
+                last_update_epoch: reader.read_u64()?,
+                is_emergency_unstaking: reader.read_u8()? != 0,
+            })
+        })?;
+
+        Ok(state)
+    }
+
+    /// Decode a `State` account; list contents are left empty
+    pub(crate) fn decode_state(address: &str, data: &[u8]) -> Result<Self> {
+        Self::decode_state_with_lists(address, data).map(|(state, _, _)| state)
+    }
+
+    /// Lamports per mSOL
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn msol_price_sol(&self) -> f64 {
+        self.msol_price as f64 / MARINADE_PRICE_DENOMINATOR as f64
+    }
+
+    /// Lamports backing the mSOL supply, as Marinade computes them for `msol_price`
+    ///
+    /// Active stake, stake cooling down and the liquid reserve, minus lamports owed
+    /// to unclaimed delayed unstake tickets.
+    #[must_use]
+    pub const fn total_virtual_staked_lamports(&self) -> u64 {
+        self.total_active_balance
+            .saturating_add(self.delayed_unstake_cooling_down)
+            .saturating_add(self.emergency_cooling_down)
+            .saturating_add(self.available_reserve_balance)
+            .saturating_sub(self.circulating_ticket_balance)
+    }
+
+    /// Find a validator record by vote account
+    #[must_use]
+    pub fn validator(&self, vote_account: &str) -> Option<&MarinadeValidatorRecord> {
+        self.validators.iter().find(|v| v.vote_account == vote_account)
+    }
+
+    /// Stake records flagged for emergency unstake
+    pub fn emergency_unstaking(&self) -> impl Iterator<Item = &MarinadeStakeRecord> {
+        self.stake_records.iter().filter(|r| r.is_emergency_unstaking)
+    }
+
+    /// Compare Marinade-reported stake with statistics computed from stake accounts
+    #[must_use]
+    pub fn reconcile(&self, statistics: &PoolStatistics) -> MarinadeReconciliation {
+        let observed_active_lamports =
+            statistics.active_stake_lamports + statistics.activating_stake_lamports;
+
+        MarinadeReconciliation {
+            reported_active_balance: self.total_active_balance,
+            observed_active_lamports,
+            difference_lamports: i128::from(self.total_active_balance) - i128::from(observed_active_lamports),
+            reported_stake_accounts: self.stake_records.len(),
+            observed_stake_accounts: statistics.total_accounts,
+            emergency_unstaking_accounts: self.emergency_unstaking().count(),
+        }
+    }
+
+    fn decode_state_with_lists(address: &str, data: &[u8]) -> Result<(Self, ListRef, ListRef)> {
+        let mut reader = ByteReader::new(data);
+        reader.read_bytes(DISCRIMINATOR_SIZE)?;
+
+        let msol_mint = reader.read_pubkey()?;
+        let admin_authority = reader.read_pubkey()?;
+        let _operational_sol_account = reader.read_pubkey()?;
+        let _treasury_msol_account = reader.read_pubkey()?;
+        let _bump_seeds = reader.read_bytes(2)?;
+        let _rent_exempt_for_token_acc = reader.read_u64()?;
+        let reward_fee_bps = reader.read_u32()?;
+
+        // StakeSystem
+        let stake_list = read_list(&mut reader)?;
+        let delayed_unstake_cooling_down = reader.read_u64()?;
+        let _bump_seeds = reader.read_bytes(2)?;
+        let _slots_for_stake_delta = reader.read_u64()?;
+        let _last_stake_delta_epoch = reader.read_u64()?;
+        let _min_stake = reader.read_u64()?;
+        let _extra_stake_delta_runs = reader.read_u32()?;
+
+        // ValidatorSystem
+        let validator_list = read_list(&mut reader)?;
+        let validator_manager_authority = reader.read_pubkey()?;
+        let total_validator_score = reader.read_u32()?;
+        let total_active_balance = reader.read_u64()?;
+        let _auto_add_validator_enabled = reader.read_u8()?;
+
+        // LiqPool: mints, bumps, fees and liquidity accounting
+        reader.read_bytes(32 + 3 + 32 + 8 + 4 * 3 + 8 * 3)?;
+
+        let available_reserve_balance = reader.read_u64()?;
+        let msol_supply = reader.read_u64()?;
+        let msol_price = reader.read_u64()?;
+        let circulating_ticket_count = reader.read_u64()?;
+        let circulating_ticket_balance = reader.read_u64()?;
+        let lent_from_reserve = reader.read_u64()?;
+        let _min_deposit = reader.read_u64()?;
+        let _min_withdraw = reader.read_u64()?;
+        let staking_sol_cap = reader.read_u64()?;
+        let emergency_cooling_down = reader.read_u64()?;
+        let _pause_authority = reader.read_pubkey()?;
+        let paused = reader.read_u8()? != 0;
+
+        let state = Self {
+            address: address.to_string(),
+            msol_mint,
+            admin_authority,
+            reward_fee_bps,
+            stake_list: stake_list.account.clone(),
+            validator_list: validator_list.account.clone(),
+            validator_manager_authority,
+            total_validator_score,
+            total_active_balance,
+            available_reserve_balance,
+            msol_supply,
+            msol_price,
+            circulating_ticket_count,
+            circulating_ticket_balance,
+            lent_from_reserve,
+            staking_sol_cap,
+            emergency_cooling_down,
+            delayed_unstake_cooling_down,
+            paused,
+            validators: Vec::new(),
+            stake_records: Vec::new(),
+        };
+        Ok((state, validator_list, stake_list))
+    }
+}
+
+fn read_list(reader: &mut ByteReader<'_>) -> Result<ListRef> {
+    let account = reader.read_pubkey()?;
+    let item_size = reader.read_u32()? as usize;
+    let count = reader.read_u32()? as usize;
+    let _reserved = reader.read_bytes(32 + 4)?;
+    Ok(ListRef {
+        account,
+        item_size,
+        count,
+    })
+}
+
+/// Decode `list.count` items of a Marinade list account
+fn decode_list<T>(
+    data: &[u8],
+    list: &ListRef,
+    min_item_size: usize,
+    decode_item: impl Fn(&mut ByteReader<'_>) -> Result<T>,
+) -> Result<Vec<T>> {
+    if list.item_size < min_item_size {
+        return Err(PoolsDataError::ParseError {
+            message: format!(
+                "List {} item size {} is smaller than {min_item_size}",
+                list.account, list.item_size
+            ),
+        });
+    }
+
+    let mut reader = ByteReader::new(data);
+    reader.read_bytes(DISCRIMINATOR_SIZE)?;
+
+    let mut items = Vec::with_capacity(list.count.min(data.len() / list.item_size));
+    for _ in 0..list.count {
+        let mut item_reader = ByteReader::new(reader.read_bytes(list.item_size)?);
+        items.push(decode_item(&mut item_reader)?);
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::decode::tests::pubkey_bytes;
+
+    fn list(data: &mut Vec<u8>, account: u8, item_size: u32, count: u32) {
+        data.extend_from_slice(&pubkey_bytes(account));
+        data.extend_from_slice(&item_size.to_le_bytes());
+        data.extend_from_slice(&count.to_le_bytes());
+        data.extend_from_slice(&[0u8; 36]);
+    }
+
+    pub(crate) fn encode_state() -> Vec<u8> {
+        let mut data = vec![0u8; DISCRIMINATOR_SIZE];
+        for seed in 1..=4 {
+            data.extend_from_slice(&pubkey_bytes(seed));
+        }
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(&2_039_280u64.to_le_bytes());
+        data.extend_from_slice(&600u32.to_le_bytes());
+        list(&mut data, 10, 57, 3);
+        data.extend_from_slice(&5_000u64.to_le_bytes());
+        data.extend_from_slice(&[0u8; 2 + 8 * 3 + 4]);
+        list(&mut data, 11, 61, 2);
+        data.extend_from_slice(&pubkey_bytes(12));
+        data.extend_from_slice(&100u32.to_le_bytes());
+        data.extend_from_slice(&3_000_000u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&[0u8; 32 + 3 + 32 + 8 + 12 + 24]);
+        for value in [
+            7_000u64,
+            2_500_000,
+            MARINADE_PRICE_DENOMINATOR * 5 / 4,
+            2,
+            9_000,
+            0,
+            1,
+            1,
+            u64::MAX,
+            1_000,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&pubkey_bytes(13));
+        data.push(0);
+        // Fields after `paused` plus spare account space
+        data.resize(data.len() + 64, 0);
+        data
+    }
+
+    fn encode_validator_list() -> Vec<u8> {
+        let mut data = vec![0u8; DISCRIMINATOR_SIZE];
+        for (seed, balance) in [(20u8, 1_000_000u64), (21, 2_000_000)] {
+            let start = data.len();
+            data.extend_from_slice(&pubkey_bytes(seed));
+            data.extend_from_slice(&balance.to_le_bytes());
+            data.extend_from_slice(&50u32.to_le_bytes());
+            data.extend_from_slice(&598u64.to_le_bytes());
+            data.resize(start + 61, 0);
+        }
+        data
+    }
+
+    fn encode_stake_list() -> Vec<u8> {
+        let mut data = vec![0u8; DISCRIMINATOR_SIZE];
+        for (seed, emergency) in [(30u8, 0u8), (31, 1), (32, 0)] {
+            let start = data.len();
+            data.extend_from_slice(&pubkey_bytes(seed));
+            data.extend_from_slice(&1_000_000u64.to_le_bytes());
+            data.extend_from_slice(&600u64.to_le_bytes());
+            data.push(emergency);
+            data.resize(start + 57, 0);
+        }
+        data
+    }
+
+    #[test]
+    fn test_decode_marinade_state() {
+        let state = MarinadeState::from_account_data(
+            MARINADE_STATE_ADDRESS,
+            &encode_state(),
+            &encode_validator_list(),
+            &encode_stake_list(),
+        )
+        .unwrap();
+
+        assert_eq!(state.msol_mint, bs58::encode(pubkey_bytes(1)).into_string());
+        assert_eq!(state.reward_fee_bps, 600);
+        assert_eq!(state.stake_list, bs58::encode(pubkey_bytes(10)).into_string());
+        assert_eq!(state.validator_list, bs58::encode(pubkey_bytes(11)).into_string());
+        assert_eq!(state.delayed_unstake_cooling_down, 5_000);
+        assert_eq!(state.total_validator_score, 100);
+        assert_eq!(state.total_active_balance, 3_000_000);
+        assert_eq!(state.available_reserve_balance, 7_000);
+        assert_eq!(state.msol_supply, 2_500_000);
+        assert!((state.msol_price_sol() - 1.25).abs() < f64::EPSILON);
+        assert_eq!(state.total_virtual_staked_lamports(), 3_000_000 + 5_000 + 1_000 + 7_000 - 9_000);
+        assert_eq!(state.circulating_ticket_count, 2);
+        assert_eq!(state.emergency_cooling_down, 1_000);
+        assert!(!state.paused);
+
+        // Corrupt balances saturate instead of overflowing
+        let corrupt = MarinadeState { total_active_balance: u64::MAX, ..state.clone() };
+        assert_eq!(corrupt.total_virtual_staked_lamports(), u64::MAX - 9_000);
+
+        assert_eq!(state.validators.len(), 2);
+        assert_eq!(state.validator(&bs58::encode(pubkey_bytes(21)).into_string()).unwrap().active_balance, 2_000_000);
+        assert_eq!(state.stake_records.len(), 3);
+        assert_eq!(state.emergency_unstaking().count(), 1);
+
+        let statistics = PoolStatistics {
+            total_accounts: 3,
+            active_stake_lamports: 2_500_000,
+            activating_stake_lamports: 400_000,
+            ..PoolStatistics::default()
+        };
+        let reconciliation = state.reconcile(&statistics);
+        assert_eq!(reconciliation.observed_active_lamports, 2_900_000);
+        assert_eq!(reconciliation.difference_lamports, 100_000);
+        assert_eq!(reconciliation.reported_stake_accounts, 3);
+        assert_eq!(reconciliation.emergency_unstaking_accounts, 1);
+    }
+
+    #[test]
+    fn test_decode_truncated_lists() {
+        let state = encode_state();
+        let stake_list = encode_stake_list();
+        let validator_list = encode_validator_list();
+
+        assert!(MarinadeState::decode_state("state", &state[..300]).is_err());
+        assert!(MarinadeState::from_account_data("state", &state, &validator_list, &stake_list[..100]).is_err());
+        assert!(MarinadeState::from_account_data("state", &state, &validator_list[..60], &stake_list).is_err());
+    }
+}
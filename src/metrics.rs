@@ -0,0 +1,276 @@
+//! Prometheus metrics (`metrics` feature).
+//!
+//! `PoolMetrics` holds per-pool stake gauges and fetch counters. Attach it with
+//! `PoolsDataClientBuilder::metrics` to record every pool fetch, and expose it with
+//! [`metrics_router`] or [`PoolMetrics::serve`].
+
+use crate::error::{PoolsDataError, Result};
+use crate::types::{PoolStatistics, PoolsDataResult, ProductionPoolData};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use chrono::{DateTime, Utc};
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Fetch latency buckets in seconds (`getProgramAccounts` can take tens of seconds)
+const FETCH_DURATION_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0];
+
+/// Per-pool stake gauges and fetch counters
+///
+/// | Metric                                       | Labels           |
+/// |----------------------------------------------|------------------|
+/// | `pools_data_pool_stake_lamports`             | `pool`, `state`  |
+/// | `pools_data_pool_accounts`                   | `pool`, `state`  |
+/// | `pools_data_pool_total_lamports`             | `pool`           |
+/// | `pools_data_pool_validators`                 | `pool`           |
+/// | `pools_data_pool_fetched_timestamp_seconds`  | `pool`           |
+/// | `pools_data_fetch_duration_seconds`          | `pool`           |
+/// | `pools_data_fetches_total`                   | `pool`, `outcome`|
+/// | `pools_data_fetch_retries_total`             | `pool`           |
+/// | `pools_data_fetch_errors_total`              | `pool`, `kind`   |
+///
+/// `state` is `activating`, `active`, `deactivating` or `deactivated`; `outcome` is
+/// `success` or `error`; `kind` is [`PoolsDataError::kind`].
+pub struct PoolMetrics {
+    registry: Registry,
+    stake_lamports: IntGaugeVec,
+    accounts: IntGaugeVec,
+    total_lamports: IntGaugeVec,
+    validators: IntGaugeVec,
+    fetched_timestamp: IntGaugeVec,
+    fetch_duration: HistogramVec,
+    fetches: IntCounterVec,
+    retries: IntCounterVec,
+    errors: IntCounterVec,
+}
+
+impl std::fmt::Debug for PoolMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PoolMetrics").finish_non_exhaustive()
+    }
+}
+
+impl PoolMetrics {
+    /// Create metrics in a new registry
+    ///
+    /// # Errors
+    /// Returns `ConfigurationError` if a metric cannot be created.
+    pub fn new() -> Result<Self> {
+        Self::with_registry(Registry::new())
+    }
+
+    /// Create metrics in an existing registry (e.g. shared with other application metrics)
+    ///
+    /// # Errors
+    /// Returns `ConfigurationError` if the registry already has metrics with these names.
+    pub fn with_registry(registry: Registry) -> Result<Self> {
+        let gauge = |name: &str, help: &str, labels: &[&str]| -> Result<IntGaugeVec> {
+            let gauge = IntGaugeVec::new(Opts::new(name, help), labels)?;
+            registry.register(Box::new(gauge.clone()))?;
+            Ok(gauge)
+        };
+        let counter = |name: &str, help: &str, labels: &[&str]| -> Result<IntCounterVec> {
+            let counter = IntCounterVec::new(Opts::new(name, help), labels)?;
+            registry.register(Box::new(counter.clone()))?;
+            Ok(counter)
+        };
+
+        let fetch_duration = HistogramVec::new(
+            HistogramOpts::new("pools_data_fetch_duration_seconds", "Duration of pool stake account fetches")
+                .buckets(FETCH_DURATION_BUCKETS.to_vec()),
+            &["pool"],
+        )?;
+        registry.register(Box::new(fetch_duration.clone()))?;
+
+        Ok(Self {
+            stake_lamports: gauge("pools_data_pool_stake_lamports", "Pool lamports by stake state", &["pool", "state"])?,
+            accounts: gauge("pools_data_pool_accounts", "Pool stake accounts by stake state", &["pool", "state"])?,
+            total_lamports: gauge("pools_data_pool_total_lamports", "Total lamports of pool stake accounts", &["pool"])?,
+            validators: gauge("pools_data_pool_validators", "Validators the pool delegates to", &["pool"])?,
+            fetched_timestamp: gauge("pools_data_pool_fetched_timestamp_seconds", "When the pool was last fetched", &["pool"])?,
+            fetch_duration,
+            fetches: counter("pools_data_fetches_total", "Pool fetches by outcome", &["pool", "outcome"])?,
+            retries: counter("pools_data_fetch_retries_total", "RPC retries of pool fetches", &["pool"])?,
+            errors: counter("pools_data_fetch_errors_total", "Failed pool fetches by error kind", &["pool", "kind"])?,
+            registry,
+        })
+    }
+
+    /// Registry holding the metrics
+    #[must_use]
+    pub const fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Set the stake gauges of a pool
+    pub fn observe_statistics(&self, pool_name: &str, statistics: &PoolStatistics, fetched_at: DateTime<Utc>) {
+        let states = [
+            ("activating", statistics.activating_stake_lamports, statistics.activating_accounts),
+            ("active", statistics.active_stake_lamports, statistics.active_accounts),
+            ("deactivating", statistics.deactivating_stake_lamports, statistics.deactivating_accounts),
+            ("deactivated", statistics.deactivated_stake_lamports, statistics.deactivated_accounts),
+        ];
+        for (state, lamports, accounts) in states {
+            self.stake_lamports.with_label_values(&[pool_name, state]).set(gauge_value(lamports));
+            self.accounts.with_label_values(&[pool_name, state]).set(gauge_value(accounts as u64));
+        }
+        self.total_lamports.with_label_values(&[pool_name]).set(gauge_value(statistics.total_lamports));
+        self.validators.with_label_values(&[pool_name]).set(gauge_value(statistics.validator_count as u64));
+        self.fetched_timestamp.with_label_values(&[pool_name]).set(fetched_at.timestamp());
+    }
+
+    /// Set the stake gauges of fetched pools
+    pub fn observe_pools<'a>(&self, pools: impl IntoIterator<Item = &'a ProductionPoolData>) {
+        for pool in pools {
+            self.observe_statistics(&pool.pool_name, &pool.statistics, pool.fetched_at);
+        }
+    }
+
+    /// Set the stake gauges of the successful pools of a result
+    pub fn observe_result(&self, result: &PoolsDataResult) {
+        for pool in result.successful.values() {
+            self.observe_statistics(&pool.pool_name, &pool.statistics, pool.fetched_at);
+        }
+    }
+
+    /// Record one pool fetch: its duration, RPC retries and error if it failed
+    ///
+    /// `retries` counts the attempts beyond the first of each RPC query.
+    pub fn record_fetch(&self, pool_name: &str, duration: Duration, retries: u32, error: Option<&PoolsDataError>) {
+        self.fetch_duration.with_label_values(&[pool_name]).observe(duration.as_secs_f64());
+        if retries > 0 {
+            self.retries.with_label_values(&[pool_name]).inc_by(u64::from(retries));
+        }
+        match error {
+            Some(error) => {
+                self.fetches.with_label_values(&[pool_name, "error"]).inc();
+                self.errors.with_label_values(&[pool_name, error.kind()]).inc();
+            }
+            None => self.fetches.with_label_values(&[pool_name, "success"]).inc(),
+        }
+    }
+
+    /// Metrics in the Prometheus text format
+    ///
+    /// # Errors
+    /// Returns `InternalError` if encoding fails.
+    pub fn encode(&self) -> Result<String> {
+        TextEncoder::new().encode_to_string(&self.registry.gather()).map_err(|e| PoolsDataError::InternalError {
+            message: format!("Failed to encode metrics: {e}"),
+        })
+    }
+
+    /// Serve `GET /metrics` on an address until the task is dropped
+    ///
+    /// # Errors
+    /// Returns `NetworkError` if the address cannot be bound or the server fails.
+    pub async fn serve(self: Arc<Self>, address: SocketAddr) -> Result<()> {
+        let listener = tokio::net::TcpListener::bind(address).await.map_err(network_error)?;
+        axum::serve(listener, metrics_router(self)).await.map_err(network_error)
+    }
+}
+
+/// Router with `GET /metrics`, to mount into an application's own axum server
+pub fn metrics_router(metrics: Arc<PoolMetrics>) -> Router {
+    Router::new().route(
+        "/metrics",
+        get(move || async move {
+            match metrics.encode() {
+                Ok(body) => ([(CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            }
+        }),
+    )
+}
+
+/// Lamports and counts as gauge values (saturating; total SOL supply fits easily)
+fn gauge_value(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+fn network_error(error: std::io::Error) -> PoolsDataError {
+    PoolsDataError::NetworkError {
+        message: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe_and_encode() {
+        let metrics = PoolMetrics::new().unwrap();
+        let statistics = PoolStatistics {
+            total_accounts: 3,
+            active_accounts: 2,
+            deactivated_accounts: 1,
+            total_lamports: 5_000_000_000,
+            active_stake_lamports: 4_000_000_000,
+            deactivated_stake_lamports: 1_000_000_000,
+            validator_count: 2,
+            ..PoolStatistics::default()
+        };
+        metrics.observe_statistics("jito", &statistics, Utc::now());
+        metrics.record_fetch("jito", Duration::from_millis(1500), 2, None);
+        metrics.record_fetch(
+            "jito",
+            Duration::from_millis(200),
+            0,
+            Some(&PoolsDataError::NetworkError { message: "refused".to_string() }),
+        );
+
+        let text = metrics.encode().unwrap();
+        assert!(text.contains(r#"pools_data_pool_stake_lamports{pool="jito",state="active"} 4000000000"#));
+        assert!(text.contains(r#"pools_data_pool_validators{pool="jito"} 2"#));
+        assert!(text.contains(r#"pools_data_fetches_total{outcome="success",pool="jito"} 1"#));
+        assert!(text.contains(r#"pools_data_fetch_retries_total{pool="jito"} 2"#));
+        assert!(text.contains(r#"pools_data_fetch_errors_total{kind="network_error",pool="jito"} 1"#));
+
+        // Metric names are taken once per registry
+        assert!(PoolMetrics::with_registry(metrics.registry().clone()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_client_records_failed_fetch() {
+        let metrics = Arc::new(PoolMetrics::new().unwrap());
+        let client = crate::test_support::offline_client_with(|builder| {
+            builder.retry_attempts(1).retry_base_delay(1).metrics(Arc::clone(&metrics))
+        });
+        assert!(client.fetch_pools(&["jito"]).await.is_err());
+
+        let text = metrics.encode().unwrap();
+        assert!(text.contains(r#"pools_data_fetches_total{outcome="error",pool="jito"} 1"#));
+        assert!(text.contains(r#"pools_data_fetch_retries_total{pool="jito"} 1"#));
+        assert!(text.contains(r#"pools_data_fetch_duration_seconds_count{pool="jito"} 1"#));
+    }
+
+    #[tokio::test]
+    async fn test_client_counts_retries_per_query() {
+        use crate::pools::{AuthorityRole, PoolInfo};
+        use crate::{MockReply, MockTransport};
+
+        let authority = bs58::encode(crate::decode::tests::pubkey_bytes(1)).into_string();
+        let mut registry = crate::PoolRegistry::empty();
+        registry.insert(PoolInfo::new("both", authority.clone()).with_authority_role(AuthorityRole::Both)).unwrap();
+        let transport = Arc::new(MockTransport::new().program_accounts(&authority, serde_json::json!([])));
+        let metrics = Arc::new(PoolMetrics::new().unwrap());
+        let client = crate::test_support::mock_client_with(Arc::clone(&transport), |builder| {
+            builder.registry(registry).metrics(Arc::clone(&metrics))
+        });
+
+        // Two clean role queries are not retries
+        assert!(client.fetch_pools(&["both"]).await.is_err());
+        assert_eq!(transport.calls("getProgramAccounts"), 2);
+        assert!(!metrics.encode().unwrap().contains("pools_data_fetch_retries_total{"));
+
+        transport.push_reply("getProgramAccounts", MockReply::Fail(PoolsDataError::NetworkError { message: "reset".to_string() }));
+        assert!(client.fetch_pools(&["both"]).await.is_err());
+        assert!(metrics.encode().unwrap().contains(r#"pools_data_fetch_retries_total{pool="both"} 1"#));
+    }
+}
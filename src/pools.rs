@@ -4,69 +4,349 @@
 //! and provides utilities for working with pool information.
 
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Stake account authority role(s) a pool authority is matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthorityRole {
+    /// Authorized staker (stake account data offset 12)
+    #[default]
+    Staker,
+    /// Authorized withdrawer (stake account data offset 44)
+    Withdrawer,
+    /// Either staker or withdrawer
+    Both,
+}
+
+impl AuthorityRole {
+    /// Byte offset of the staker pubkey in stake account data
+    pub const STAKER_OFFSET: usize = 12;
+
+    /// Byte offset of the withdrawer pubkey in stake account data
+    pub const WITHDRAWER_OFFSET: usize = 44;
+
+    /// Role name as serialized
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Staker => "staker",
+            Self::Withdrawer => "withdrawer",
+            Self::Both => "both",
+        }
+    }
+
+    /// Single roles to query for this role (`Both` expands to staker and withdrawer)
+    #[must_use]
+    pub const fn roles(self) -> &'static [Self] {
+        match self {
+            Self::Staker => &[Self::Staker],
+            Self::Withdrawer => &[Self::Withdrawer],
+            Self::Both => &[Self::Staker, Self::Withdrawer],
+        }
+    }
+
+    /// `memcmp` offset for a single role (`None` for `Both`)
+    #[must_use]
+    pub const fn memcmp_offset(self) -> Option<usize> {
+        match self {
+            Self::Staker => Some(Self::STAKER_OFFSET),
+            Self::Withdrawer => Some(Self::WITHDRAWER_OFFSET),
+            Self::Both => None,
+        }
+    }
+
+    /// Combine two matched roles
+    #[must_use]
+    pub const fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Staker, Self::Staker) => Self::Staker,
+            (Self::Withdrawer, Self::Withdrawer) => Self::Withdrawer,
+            _ => Self::Both,
+        }
+    }
+}
+
+/// On-chain program managing a pool's stake accounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolProgram {
+    /// SPL stake pool program (or a fork of it)
+    SplStakePool,
+    /// Marinade liquid staking program
+    Marinade,
+    /// Lido for Solana
+    Lido,
+    /// Stake accounts managed directly by the authority (native staking, delegation programs)
+    #[default]
+    Native,
+    /// Solana Foundation delegation program
+    Foundation,
+}
+
+/// Whether a pool is still operated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolStatus {
+    /// Pool is operated and accepting stake
+    #[default]
+    Active,
+    /// Pool is wound down; remaining stake accounts may still exist
+    Deprecated,
+}
 
 /// Information about a stake pool
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct PoolInfo {
     /// Human-readable name of the pool
     pub name: String,
     /// Base58-encoded authority public key
     pub authority: String,
+    /// Which stake account authority role(s) to match against `authority`
+    pub authority_role: AuthorityRole,
+    /// Liquid staking token mint, for pools that issue one
+    pub lst_mint: Option<String>,
+    /// Program managing the pool's stake accounts
+    pub program: PoolProgram,
+    /// Operator (group) id shared by pools with several authorities, e.g. `binance` and `binance_2`
+    pub operator: String,
+    /// Display name for reports
+    pub display_name: String,
+    /// Active or deprecated
+    pub status: PoolStatus,
+    /// Free-form tags, e.g. `exchange` or `delegation_program`
+    pub tags: Vec<String>,
 }
 
 impl PoolInfo {
-    /// Create a new `PoolInfo`
+    /// Create a new `PoolInfo` matching stake accounts by staker authority
+    ///
+    /// The operator and display name default to `name`.
     pub fn new(name: impl Into<String>, authority: impl Into<String>) -> Self {
+        let name = name.into();
         Self {
-            name: name.into(),
+            operator: name.clone(),
+            display_name: name.clone(),
+            name,
             authority: authority.into(),
+            authority_role: AuthorityRole::Staker,
+            lst_mint: None,
+            program: PoolProgram::Native,
+            status: PoolStatus::Active,
+            tags: Vec::new(),
         }
     }
+
+    /// Set which authority role(s) to match
+    #[must_use]
+    pub const fn with_authority_role(mut self, role: AuthorityRole) -> Self {
+        self.authority_role = role;
+        self
+    }
+
+    /// Set the liquid staking token mint
+    #[must_use]
+    pub fn with_lst_mint(mut self, mint: impl Into<String>) -> Self {
+        self.lst_mint = Some(mint.into());
+        self
+    }
+
+    /// Set the program managing the pool
+    #[must_use]
+    pub const fn with_program(mut self, program: PoolProgram) -> Self {
+        self.program = program;
+        self
+    }
+
+    /// Set the operator (group) id
+    #[must_use]
+    pub fn with_operator(mut self, operator: impl Into<String>) -> Self {
+        self.operator = operator.into();
+        self
+    }
+
+    /// Set the display name
+    #[must_use]
+    pub fn with_display_name(mut self, display_name: impl Into<String>) -> Self {
+        self.display_name = display_name.into();
+        self
+    }
+
+    /// Set the pool status
+    #[must_use]
+    pub const fn with_status(mut self, status: PoolStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Add a tag
+    #[must_use]
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Check if the pool is still operated
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.status == PoolStatus::Active
+    }
+
+    /// Check if the pool has a tag
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
+
+/// Group pools by operator id, keeping registry order within each group
+#[must_use]
+pub fn group_pools_by_operator(pools: &[PoolInfo]) -> BTreeMap<String, Vec<&PoolInfo>> {
+    let mut groups: BTreeMap<String, Vec<&PoolInfo>> = BTreeMap::new();
+    for pool in pools {
+        groups.entry(pool.operator.clone()).or_default().push(pool);
+    }
+    groups
 }
 
 /// Static registry of all known pools
 /// This replaces the external JSON file with embedded data
 static POOLS_REGISTRY: Lazy<Vec<PoolInfo>> = Lazy::new(|| {
     vec![
-        PoolInfo::new("foundation", "mpa4abUkjQoAvPzREkh5Mo75hZhPFQ2FSH6w7dWKuQ5"),
-        PoolInfo::new("firedancer_delegation", "FiRep26iRQbMaKbqhhs5CqXqy7YrHn462LbnQhXzB2ps",),
-        PoolInfo::new("double_zero", "4cpnpiwgBfUgELVwNYiecwGti45YHSH3R72CPkFTiwJt",),
-        PoolInfo::new("jpool", "HbJTxftxnXgpePCshA8FubsRj9MW4kfPscfuUfn44fnt"),
-        PoolInfo::new("jito", "6iQKfEyhr3bZMotVkW6beNZz5CPAkiwvgV2CTje9pVSS"),
-        PoolInfo::new("marinade", "4bZ6o3eUUNXhKuqjdCnCoPAoLgWiuLYixKaxoa8PpiKk"),
-        PoolInfo::new("marinade_native", "ex9CfkBZZd6Nv9XdnoDmmB45ymbu4arXVk7g5pWnt3N",),
-        PoolInfo::new("marinade_native_2", "stWirqFCf2Uts1JBL1Jsd3r6VBWhgnpdPxCTe1MFjrq",),
-        PoolInfo::new("socean", "AzZRvyyMHBm8EHEksWxq4ozFL7JxLMydCDMGhqM6BVck"),
-        PoolInfo::new("lido", "W1ZQRwUfSkDKy2oefRBUWph82Vr2zg9txWMA8RQazN5"),
-        PoolInfo::new("eversol", "C4NeuptywfXuyWB9A7H7g5jHVDE8L6Nj2hS53tA71KPn"),
-        PoolInfo::new("edgevana", "FZEaZMmrRC3PDPFMzqooKLS2JjoyVkKNd2MkHjr7Xvyq"),
-        PoolInfo::new("blazestake", "6WecYymEARvjG5ZyqkrVQ6YkhPfujNzWpSPwNKXHCbV2"),
-        PoolInfo::new("daopool", "BbyX1GwUNsfbcoWwnkZDo8sqGmwNDzs2765RpjyQ1pQb"),
-        PoolInfo::new("bonk", "9LcmMfufi8YUcx83RALwF9Y9BPWZ7SqGy4D9VLe2nhhA"),
-        PoolInfo::new("sanctum", "EjYFnQcNDmfYQqT5B2R2239i781D5wNXrqA2qx2gYJo1"),
-        PoolInfo::new("sanctum_2", "3rBnnH9TTgd3xwu48rnzGsaQkSr1hR64nY71DrDt6VrQ"),
-        PoolInfo::new("binance", "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM"),
-        PoolInfo::new("jupiter", "EMjuABxELpYWYEwjkKmQKBNCwdaFAy4QYAs6W9bDQDNw"),
-        PoolInfo::new("binance_2", "75NPzpxoh8sXGuSENFMREidq6FMzEx4g2AfcBEB6qjCV"),
-        PoolInfo::new("solayer", "H5rmot8ejBUWzMPt6E44h27xj5obbSz3jVuK4AsJpHmv"),
-        PoolInfo::new("bybit", "3pFTQjRVwcJHSpUNH5n1hx6Jwx7V3EzJDDHaKuwExyGJ"),
-        PoolInfo::new("shinobi", "EpH4ZKSeViL5qAHA9QANYVHxdmuzbUH2T79f32DmSCaM"),
-        PoolInfo::new("helius", "2rMuGTyXCqCHZBSu6NZR9Aq8MhZX9gLkCHoQsPhSj2YF"),
-        PoolInfo::new("marginfi", "3b7XQeZ8nSMyjcQGTFJS5kBw4pXS2SqtB9ooHCnF2xV9"),
-        PoolInfo::new("vault", "GdNXJobf8fbTR5JSE7adxa6niaygjx4EEbnnRaDCHMMW"),
-        PoolInfo::new("drift", "6727ZvQ2YEz8jky1Z9fqDFG5mYuAvC9G34o2MxwzmrUK"),
-        PoolInfo::new("aerosol", "AKJt3m2xJ6ANda9adBGqb5BMrheKJSwxyCfYkLuZNmjn"),
-        PoolInfo::new("ftx", "H4yiPhdSsmSMJTznXzmZvdqWuhxDRzzkoQMEWXZ6agFZ"),
-        PoolInfo::new("juicy", "FKDyJz5tPUy1ArAUba7ziQLbMKzaivRnHiW4FHzCSE9t"),
-        PoolInfo::new("picosol", "4At8nQXanWgRvjbrVXmxMBBdfz39txWVm4SiXEoP1kGh"),
-        PoolInfo::new("STKE", "5vzKiHVuZNx1XQWQZQEcuqKaq4nfDp6LhuSvowQK2ayd"),
-        PoolInfo::new("jag_pool", "Hodkwm8xf43JzRuKNYPGnYJ7V9cXZ7LJGNy96TWQiSGN"),
-        PoolInfo::new("shark_pool", "12bX3M9rnu1HWG87BwGfxeE5ouhWAJpdSqwBWiP8hnuQ"),
-        PoolInfo::new("dynosol", "BqPJdYKKpReEfXHv8kgdmRcBfLToBSHpt1qThtb52GSs"),
-        PoolInfo::new("definity", "5ugu8RogBq5ZdfGt4hKxKotRBkndiV1ndsqWCf7PBmST"),
-        PoolInfo::new("layer33", "FQS7JfBjCUiSj6JRHZWqnuM8FNxnDrbaoErCXXe6fAj8"),
-        PoolInfo::new("starpool", "JBV9qdbKkiz1WmszJuL7qGuAYE2suPP6YU5R7gHqCGRe"),
+        PoolInfo::new("foundation", "mpa4abUkjQoAvPzREkh5Mo75hZhPFQ2FSH6w7dWKuQ5")
+            .with_program(PoolProgram::Foundation)
+            .with_display_name("Solana Foundation")
+            .with_tag("delegation_program"),
+        PoolInfo::new("firedancer_delegation", "FiRep26iRQbMaKbqhhs5CqXqy7YrHn462LbnQhXzB2ps")
+            .with_display_name("Firedancer Delegation")
+            .with_tag("delegation_program"),
+        PoolInfo::new("double_zero", "4cpnpiwgBfUgELVwNYiecwGti45YHSH3R72CPkFTiwJt")
+            .with_display_name("DoubleZero")
+            .with_tag("delegation_program"),
+        PoolInfo::new("jpool", "HbJTxftxnXgpePCshA8FubsRj9MW4kfPscfuUfn44fnt")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("JPool")
+            .with_lst_mint("7Q2afV64in6N6SeZsAAB81TJzwDoD6zpqmHkzi9Dcavn"),
+        PoolInfo::new("jito", "6iQKfEyhr3bZMotVkW6beNZz5CPAkiwvgV2CTje9pVSS")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("Jito")
+            .with_lst_mint("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn"),
+        PoolInfo::new("marinade", "4bZ6o3eUUNXhKuqjdCnCoPAoLgWiuLYixKaxoa8PpiKk")
+            .with_program(PoolProgram::Marinade)
+            .with_display_name("Marinade")
+            .with_lst_mint("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So"),
+        PoolInfo::new("marinade_native", "ex9CfkBZZd6Nv9XdnoDmmB45ymbu4arXVk7g5pWnt3N")
+            .with_operator("marinade")
+            .with_display_name("Marinade Native")
+            .with_tag("native_staking"),
+        PoolInfo::new("marinade_native_2", "stWirqFCf2Uts1JBL1Jsd3r6VBWhgnpdPxCTe1MFjrq")
+            .with_operator("marinade")
+            .with_display_name("Marinade Native")
+            .with_tag("native_staking"),
+        PoolInfo::new("socean", "AzZRvyyMHBm8EHEksWxq4ozFL7JxLMydCDMGhqM6BVck")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("Socean")
+            .with_lst_mint("5oVNBeEEQvYi1cX3ir8Dx5n1P7pdxydbGF2X4TxVusJm")
+            .with_status(PoolStatus::Deprecated),
+        PoolInfo::new("lido", "W1ZQRwUfSkDKy2oefRBUWph82Vr2zg9txWMA8RQazN5")
+            .with_program(PoolProgram::Lido)
+            .with_display_name("Lido")
+            .with_lst_mint("7dHbWXmci3dT8UFYWYZweBLXgycu7Y3iL6trKn1Y7ARj")
+            .with_status(PoolStatus::Deprecated),
+        PoolInfo::new("eversol", "C4NeuptywfXuyWB9A7H7g5jHVDE8L6Nj2hS53tA71KPn")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("Eversol")
+            .with_lst_mint("Hg35Vd8K3BS2pLB3xwC2WqQV8pmpCm3oNRGYP1PEpmCM")
+            .with_status(PoolStatus::Deprecated),
+        PoolInfo::new("edgevana", "FZEaZMmrRC3PDPFMzqooKLS2JjoyVkKNd2MkHjr7Xvyq")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("Edgevana")
+            .with_lst_mint("edge86g9cVz87xcpKpy3J77vbp4wYd9idEV562CCntt"),
+        PoolInfo::new("blazestake", "6WecYymEARvjG5ZyqkrVQ6YkhPfujNzWpSPwNKXHCbV2")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("BlazeStake")
+            .with_lst_mint("bSo13r4TkiE4KumL71LsHTPpL2euBYLFx6h9HP3piy1"),
+        PoolInfo::new("daopool", "BbyX1GwUNsfbcoWwnkZDo8sqGmwNDzs2765RpjyQ1pQb")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("DAOPool")
+            .with_lst_mint("GEJpt3Wjmr628FqXxTgxMce1pLntcPV4uFi8ksxMyPQh"),
+        PoolInfo::new("bonk", "9LcmMfufi8YUcx83RALwF9Y9BPWZ7SqGy4D9VLe2nhhA")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("Bonk")
+            .with_lst_mint("BonK1YhkXEGLZzwtcvRTip3gAL9nCeQD7ppZBLXhtTs"),
+        PoolInfo::new("sanctum", "EjYFnQcNDmfYQqT5B2R2239i781D5wNXrqA2qx2gYJo1")
+            .with_display_name("Sanctum"),
+        PoolInfo::new("sanctum_2", "3rBnnH9TTgd3xwu48rnzGsaQkSr1hR64nY71DrDt6VrQ")
+            .with_operator("sanctum")
+            .with_display_name("Sanctum"),
+        PoolInfo::new("binance", "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("Binance")
+            .with_lst_mint("BNso1VUJnh4zcfpZa6986Ea66P6TCp59hvtNJ8b1X85")
+            .with_tag("exchange"),
+        PoolInfo::new("jupiter", "EMjuABxELpYWYEwjkKmQKBNCwdaFAy4QYAs6W9bDQDNw")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("Jupiter")
+            .with_lst_mint("jupSoLaHXQiZZTSfEWMTRRgpnyFm8f6sZdosWBjx93v"),
+        PoolInfo::new("binance_2", "75NPzpxoh8sXGuSENFMREidq6FMzEx4g2AfcBEB6qjCV")
+            .with_operator("binance")
+            .with_display_name("Binance")
+            .with_tag("exchange"),
+        PoolInfo::new("solayer", "H5rmot8ejBUWzMPt6E44h27xj5obbSz3jVuK4AsJpHmv")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("Solayer")
+            .with_lst_mint("sSo14endRuUbvQaJS3dq36Q829a3A6BEfoeeRGJywEh")
+            .with_tag("restaking"),
+        PoolInfo::new("bybit", "3pFTQjRVwcJHSpUNH5n1hx6Jwx7V3EzJDDHaKuwExyGJ")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("Bybit")
+            .with_lst_mint("Bybit2vBJGhPF52GBdNaQfUJ6ZpThSgHBobjWZpLPb4B")
+            .with_tag("exchange"),
+        PoolInfo::new("shinobi", "EpH4ZKSeViL5qAHA9QANYVHxdmuzbUH2T79f32DmSCaM")
+            .with_display_name("Shinobi"),
+        PoolInfo::new("helius", "2rMuGTyXCqCHZBSu6NZR9Aq8MhZX9gLkCHoQsPhSj2YF")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("Helius")
+            .with_lst_mint("he1iusmfkpAdwvxLNGV8Y1iSbj4rUy6yMhEA3fotn9A"),
+        PoolInfo::new("marginfi", "3b7XQeZ8nSMyjcQGTFJS5kBw4pXS2SqtB9ooHCnF2xV9")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("marginfi")
+            .with_lst_mint("LSTxxxnJzKDFSLr4dUkPcmCf5VyryEqzPLz5j4bpxFp"),
+        PoolInfo::new("vault", "GdNXJobf8fbTR5JSE7adxa6niaygjx4EEbnnRaDCHMMW")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("The Vault")
+            .with_lst_mint("vSoLxydx6akxyMD9XEcPvGYNGq6Nn66oqVb3UkGkei7"),
+        PoolInfo::new("drift", "6727ZvQ2YEz8jky1Z9fqDFG5mYuAvC9G34o2MxwzmrUK")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("Drift")
+            .with_lst_mint("Dso1bDeDjCQxTrWHqUUi63oBvV7Mdm6WaobLbQ7gnPQ"),
+        PoolInfo::new("aerosol", "AKJt3m2xJ6ANda9adBGqb5BMrheKJSwxyCfYkLuZNmjn")
+            .with_display_name("Aerosol"),
+        PoolInfo::new("ftx", "H4yiPhdSsmSMJTznXzmZvdqWuhxDRzzkoQMEWXZ6agFZ")
+            .with_display_name("FTX")
+            .with_status(PoolStatus::Deprecated)
+            .with_tag("exchange"),
+        PoolInfo::new("juicy", "FKDyJz5tPUy1ArAUba7ziQLbMKzaivRnHiW4FHzCSE9t")
+            .with_display_name("Juicy"),
+        PoolInfo::new("picosol", "4At8nQXanWgRvjbrVXmxMBBdfz39txWVm4SiXEoP1kGh")
+            .with_program(PoolProgram::SplStakePool)
+            .with_display_name("picoSOL")
+            .with_lst_mint("picobAEvs6w7QEknPce34wAE4gknZA9v5tTonnmHYdX"),
+        PoolInfo::new("STKE", "5vzKiHVuZNx1XQWQZQEcuqKaq4nfDp6LhuSvowQK2ayd")
+            .with_display_name("STKE"),
+        PoolInfo::new("jag_pool", "Hodkwm8xf43JzRuKNYPGnYJ7V9cXZ7LJGNy96TWQiSGN")
+            .with_display_name("Jag Pool"),
+        PoolInfo::new("shark_pool", "12bX3M9rnu1HWG87BwGfxeE5ouhWAJpdSqwBWiP8hnuQ")
+            .with_display_name("Shark Pool"),
+        PoolInfo::new("dynosol", "BqPJdYKKpReEfXHv8kgdmRcBfLToBSHpt1qThtb52GSs")
+            .with_display_name("DynoSOL"),
+        PoolInfo::new("definity", "5ugu8RogBq5ZdfGt4hKxKotRBkndiV1ndsqWCf7PBmST")
+            .with_display_name("Definity"),
+        PoolInfo::new("layer33", "FQS7JfBjCUiSj6JRHZWqnuM8FNxnDrbaoErCXXe6fAj8")
+            .with_display_name("Layer33"),
+        PoolInfo::new("starpool", "JBV9qdbKkiz1WmszJuL7qGuAYE2suPP6YU5R7gHqCGRe")
+            .with_display_name("Starpool"),
     ]
 });
 
@@ -117,6 +397,16 @@ pub fn pool_exists(name: &str) -> bool {
     POOLS_BY_NAME.contains_key(name)
 }
 
+/// Get all pools run by an operator
+#[must_use]
+pub fn get_pools_by_operator(operator: &str) -> Vec<PoolInfo> {
+    POOLS_REGISTRY
+        .iter()
+        .filter(|pool| pool.operator == operator)
+        .cloned()
+        .collect()
+}
+
 /// Get all pool names
 pub fn get_all_pool_names() -> Vec<String> {
     POOLS_REGISTRY
@@ -173,6 +463,54 @@ mod tests {
         assert!(pools.iter().any(|p| p.name == "marinade"));
     }
 
+    #[test]
+    fn test_authority_role() {
+        assert_eq!(get_pool_by_name("jito").unwrap().authority_role, AuthorityRole::Staker);
+        assert_eq!(AuthorityRole::Both.roles(), &[AuthorityRole::Staker, AuthorityRole::Withdrawer]);
+        assert_eq!(AuthorityRole::Withdrawer.memcmp_offset(), Some(44));
+        assert_eq!(AuthorityRole::Both.memcmp_offset(), None);
+        assert_eq!(AuthorityRole::Staker.merge(AuthorityRole::Withdrawer), AuthorityRole::Both);
+        assert_eq!(AuthorityRole::Staker.merge(AuthorityRole::Staker), AuthorityRole::Staker);
+
+        let pool = PoolInfo::new("rotated", "auth").with_authority_role(AuthorityRole::Withdrawer);
+        assert_eq!(pool.authority_role, AuthorityRole::Withdrawer);
+    }
+
+    #[test]
+    fn test_lst_mint() {
+        assert_eq!(
+            get_pool_by_name("jito").unwrap().lst_mint.as_deref(),
+            Some("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn")
+        );
+        assert!(get_pool_by_name("foundation").unwrap().lst_mint.is_none());
+    }
+
+    #[test]
+    fn test_pool_metadata() {
+        let marinade = get_pool_by_name("marinade").unwrap();
+        assert_eq!(marinade.program, PoolProgram::Marinade);
+        assert_eq!(get_pool_by_name("lido").unwrap().program, PoolProgram::Lido);
+        assert_eq!(get_pool_by_name("foundation").unwrap().program, PoolProgram::Foundation);
+        assert_eq!(get_pool_by_name("jito").unwrap().program, PoolProgram::SplStakePool);
+
+        for deprecated in ["ftx", "socean", "eversol"] {
+            assert!(!get_pool_by_name(deprecated).unwrap().is_active());
+        }
+        assert!(get_pool_by_name("bybit").unwrap().has_tag("exchange"));
+
+        let binance: Vec<String> = get_pools_by_operator("binance").into_iter().map(|p| p.name).collect();
+        assert_eq!(binance, vec!["binance", "binance_2"]);
+
+        let groups = group_pools_by_operator(get_all_pools());
+        assert_eq!(groups["marinade"].len(), 3);
+        assert_eq!(groups["jito"].len(), 1);
+
+        let pool = PoolInfo::new("custom", "auth");
+        assert_eq!(pool.operator, "custom");
+        assert_eq!(pool.display_name, "custom");
+        assert_eq!(pool.program, PoolProgram::Native);
+    }
+
     #[test]
     fn test_pool_exists() {
         assert!(pool_exists("jito"));
@@ -0,0 +1,706 @@
+//! Pool registry with external overrides.
+//!
+//! A `PoolRegistry` starts from the embedded pool list and can be extended from
+//! TOML/JSON files or an HTTP URL, so new pools don't require a crate release.
+//!
+//! Overlay format (TOML shown, JSON uses the same keys):
+//!
+//! ```toml
+//! # Drop the embedded defaults entirely (optional)
+//! replace_defaults = false
+//! # Remove pools by name
+//! disable = ["ftx"]
+//!
+//! # Add a pool, or override an existing one with the same name
+//! [[pools]]
+//! name = "newlst"
+//! authority = "6iQKfEyhr3bZMotVkW6beNZz5CPAkiwvgV2CTje9pVSS"
+//! authority_role = "both"   # staker (default) | withdrawer | both
+//! lst_mint = "J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn"
+//! program = "spl_stake_pool"  # spl_stake_pool | marinade | lido | native (default) | foundation
+//! operator = "newlst"         # defaults to name
+//! display_name = "New LST"    # defaults to name
+//! status = "active"           # active (default) | deprecated
+//! tags = ["exchange"]
+//!
+//! # Alternative names resolving to a pool
+//! [aliases]
+//! jitosol = "jito"
+//! ```
+
+use crate::error::{PoolsDataError, Result};
+use crate::pools::{get_all_pools, group_pools_by_operator, AuthorityRole, PoolInfo, PoolProgram, PoolStatus};
+use crate::types::{PoolStatistics, ProductionPoolData};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+
+/// Timeout for downloading a registry overlay
+const REGISTRY_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest registry overlay accepted from a URL
+const MAX_REGISTRY_SIZE: usize = 1024 * 1024;
+
+/// Pool entry of a registry overlay
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryPoolEntry {
+    /// Pool name (overrides the embedded pool with the same name)
+    pub name: String,
+    /// Base58 authority public key
+    pub authority: String,
+    /// Authority role(s) to match
+    #[serde(default)]
+    pub authority_role: AuthorityRole,
+    /// Base58 LST mint
+    #[serde(default)]
+    pub lst_mint: Option<String>,
+    /// Program managing the pool
+    #[serde(default)]
+    pub program: PoolProgram,
+    /// Operator (group) id, defaults to `name`
+    #[serde(default)]
+    pub operator: Option<String>,
+    /// Display name, defaults to `name`
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Active or deprecated
+    #[serde(default)]
+    pub status: PoolStatus,
+    /// Free-form tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Remove this pool instead of adding it
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+/// Registry overlay loaded from a file or URL
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryOverlay {
+    /// Start from an empty registry instead of the current pools
+    #[serde(default)]
+    pub replace_defaults: bool,
+    /// Pool names to remove
+    #[serde(default)]
+    pub disable: Vec<String>,
+    /// Pools to add or override
+    #[serde(default)]
+    pub pools: Vec<RegistryPoolEntry>,
+    /// Alias to pool name
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl RegistryOverlay {
+    /// Parse a TOML overlay
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::ConfigurationError` if the TOML is invalid.
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| PoolsDataError::ConfigurationError {
+            message: format!("Invalid registry TOML: {e}"),
+        })
+    }
+
+    /// Parse a JSON overlay
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::ConfigurationError` if the JSON is invalid.
+    pub fn from_json_str(content: &str) -> Result<Self> {
+        serde_json::from_str(content).map_err(|e| PoolsDataError::ConfigurationError {
+            message: format!("Invalid registry JSON: {e}"),
+        })
+    }
+
+    /// Parse an overlay, detecting JSON by a leading `{`
+    fn from_str_auto(content: &str) -> Result<Self> {
+        if content.trim_start().starts_with('{') {
+            Self::from_json_str(content)
+        } else {
+            Self::from_toml_str(content)
+        }
+    }
+}
+
+/// Statistics summed over all pools of one operator
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OperatorStatistics {
+    /// Operator (group) id
+    pub operator: String,
+    /// Names of the aggregated pools
+    pub pools: Vec<String>,
+    /// Summed statistics (validators counted once)
+    pub statistics: PoolStatistics,
+}
+
+/// Set of known pools with name, alias and authority lookups
+#[derive(Debug, Clone)]
+pub struct PoolRegistry {
+    pools: Vec<PoolInfo>,
+    aliases: BTreeMap<String, String>,
+    by_name: HashMap<String, usize>,
+    by_authority: HashMap<String, usize>,
+}
+
+impl Default for PoolRegistry {
+    fn default() -> Self {
+        Self::embedded()
+    }
+}
+
+impl PoolRegistry {
+    /// Registry with the pools embedded in this crate
+    #[must_use]
+    pub fn embedded() -> Self {
+        Self::from_pools(get_all_pools().to_vec())
+    }
+
+    /// Registry with no pools
+    #[must_use]
+    pub fn empty() -> Self {
+        Self::from_pools(Vec::new())
+    }
+
+    fn from_pools(pools: Vec<PoolInfo>) -> Self {
+        let mut registry = Self {
+            pools,
+            aliases: BTreeMap::new(),
+            by_name: HashMap::new(),
+            by_authority: HashMap::new(),
+        };
+        registry.reindex();
+        registry
+    }
+
+    fn reindex(&mut self) {
+        self.by_name = self
+            .pools
+            .iter()
+            .enumerate()
+            .map(|(index, pool)| (pool.name.clone(), index))
+            .collect();
+        self.by_authority = self
+            .pools
+            .iter()
+            .enumerate()
+            .map(|(index, pool)| (pool.authority.clone(), index))
+            .collect();
+    }
+
+    /// Add a pool, replacing any pool with the same name
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::ConfigurationError` if the name is empty or a key is not valid base58.
+    pub fn insert(&mut self, pool: PoolInfo) -> Result<()> {
+        validate_pool(&pool)?;
+        if let Some(&index) = self.by_name.get(&pool.name) {
+            self.pools[index] = pool;
+        } else {
+            self.pools.push(pool);
+        }
+        self.reindex();
+        Ok(())
+    }
+
+    /// Remove a pool by name or alias, returning it if present
+    pub fn remove(&mut self, name: &str) -> Option<PoolInfo> {
+        let index = *self.by_name.get(self.resolve(name))?;
+        let pool = self.pools.remove(index);
+        self.aliases.retain(|_, target| *target != pool.name);
+        self.reindex();
+        Some(pool)
+    }
+
+    /// Add an alias for an existing pool
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::ConfigurationError` if the target does not exist
+    /// or the alias is already a pool name.
+    pub fn add_alias(&mut self, alias: impl Into<String>, name: &str) -> Result<()> {
+        let alias = alias.into();
+        if self.by_name.contains_key(&alias) {
+            return Err(PoolsDataError::ConfigurationError {
+                message: format!("Alias '{alias}' shadows an existing pool"),
+            });
+        }
+        let target = self.resolve(name).to_string();
+        if !self.by_name.contains_key(&target) {
+            return Err(PoolsDataError::ConfigurationError {
+                message: format!("Alias '{alias}' points to unknown pool '{name}'"),
+            });
+        }
+        self.aliases.insert(alias, target);
+        Ok(())
+    }
+
+    /// Apply an overlay: replace, disable, add/override, then aliases
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::ConfigurationError` on invalid entries or aliases;
+    /// the registry is left unchanged in that case.
+    pub fn apply(&mut self, overlay: RegistryOverlay) -> Result<()> {
+        let mut next = if overlay.replace_defaults { Self::empty() } else { self.clone() };
+
+        for name in &overlay.disable {
+            next.remove(name);
+        }
+        for entry in overlay.pools {
+            if entry.disabled {
+                next.remove(&entry.name);
+                continue;
+            }
+            let mut pool = PoolInfo::new(entry.name, entry.authority)
+                .with_authority_role(entry.authority_role)
+                .with_program(entry.program)
+                .with_status(entry.status);
+            pool.lst_mint = entry.lst_mint;
+            pool.tags = entry.tags;
+            if let Some(operator) = entry.operator {
+                pool.operator = operator;
+            }
+            if let Some(display_name) = entry.display_name {
+                pool.display_name = display_name;
+            }
+            next.insert(pool)?;
+        }
+        for (alias, name) in &overlay.aliases {
+            next.add_alias(alias.clone(), name)?;
+        }
+
+        next.validate()?;
+        *self = next;
+        Ok(())
+    }
+
+    /// Apply an overlay file (`.json`, otherwise TOML)
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::ConfigurationError` if the file cannot be read or is invalid.
+    pub fn merge_file(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| PoolsDataError::ConfigurationError {
+            message: format!("Failed to read registry file {}: {e}", path.display()),
+        })?;
+        let overlay = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => RegistryOverlay::from_json_str(&content)?,
+            Some("toml") => RegistryOverlay::from_toml_str(&content)?,
+            _ => RegistryOverlay::from_str_auto(&content)?,
+        };
+        self.apply(overlay)?;
+        Ok(self)
+    }
+
+    /// Apply an overlay fetched over HTTP (JSON or TOML)
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::NetworkError` if the download fails and
+    /// `PoolsDataError::ConfigurationError` if the content is invalid or larger than 1 MiB.
+    pub async fn merge_url(mut self, url: &str) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(REGISTRY_FETCH_TIMEOUT).build()?;
+        let mut response = client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(PoolsDataError::NetworkError {
+                message: format!("Failed to fetch registry from {url}: HTTP {}", response.status()),
+            });
+        }
+
+        let too_large = || PoolsDataError::ConfigurationError {
+            message: format!("Registry from {url} is larger than {MAX_REGISTRY_SIZE} bytes"),
+        };
+        if response.content_length().is_some_and(|length| length > MAX_REGISTRY_SIZE as u64) {
+            return Err(too_large());
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > MAX_REGISTRY_SIZE {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        let content = String::from_utf8(body).map_err(|e| PoolsDataError::ConfigurationError {
+            message: format!("Registry from {url} is not UTF-8: {e}"),
+        })?;
+        self.apply(RegistryOverlay::from_str_auto(&content)?)?;
+        Ok(self)
+    }
+
+    /// Check names, keys, authorities and aliases
+    ///
+    /// Each authority belongs to one pool, so lookups by authority are unambiguous.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::ConfigurationError` describing the first problem found.
+    pub fn validate(&self) -> Result<()> {
+        if self.by_name.len() != self.pools.len() {
+            return Err(PoolsDataError::ConfigurationError {
+                message: "Registry contains duplicate pool names".to_string(),
+            });
+        }
+        let mut claimed: HashMap<&str, &str> = HashMap::new();
+        for pool in &self.pools {
+            validate_pool(pool)?;
+            if let Some(other) = claimed.insert(pool.authority.as_str(), pool.name.as_str()) {
+                return Err(PoolsDataError::ConfigurationError {
+                    message: format!("Pools '{other}' and '{}' both use authority {}", pool.name, pool.authority),
+                });
+            }
+        }
+        for (alias, target) in &self.aliases {
+            if !self.by_name.contains_key(target) {
+                return Err(PoolsDataError::ConfigurationError {
+                    message: format!("Alias '{alias}' points to unknown pool '{target}'"),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve an alias to its pool name (names resolve to themselves)
+    #[must_use]
+    pub fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map_or(name, String::as_str)
+    }
+
+    /// All pools, in registry order
+    #[must_use]
+    pub fn pools(&self) -> &[PoolInfo] {
+        &self.pools
+    }
+
+    /// All aliases
+    #[must_use]
+    pub const fn aliases(&self) -> &BTreeMap<String, String> {
+        &self.aliases
+    }
+
+    /// Get pool info by name or alias
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&PoolInfo> {
+        self.by_name.get(self.resolve(name)).map(|&index| &self.pools[index])
+    }
+
+    /// Get pool info by authority
+    #[must_use]
+    pub fn get_by_authority(&self, authority: &str) -> Option<&PoolInfo> {
+        self.by_authority.get(authority).map(|&index| &self.pools[index])
+    }
+
+    /// Get multiple pools by names or aliases, skipping unknown names
+    #[must_use]
+    pub fn get_many(&self, names: &[&str]) -> Vec<PoolInfo> {
+        names.iter().filter_map(|name| self.get(name)).cloned().collect()
+    }
+
+    /// Check if a pool name or alias exists
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// All pool names
+    #[must_use]
+    pub fn names(&self) -> Vec<String> {
+        self.pools.iter().map(|pool| pool.name.clone()).collect()
+    }
+
+    /// Pools run by an operator
+    #[must_use]
+    pub fn pools_by_operator(&self, operator: &str) -> Vec<&PoolInfo> {
+        self.pools.iter().filter(|pool| pool.operator == operator).collect()
+    }
+
+    /// Pools grouped by operator id
+    #[must_use]
+    pub fn group_by_operator(&self) -> BTreeMap<String, Vec<&PoolInfo>> {
+        group_pools_by_operator(&self.pools)
+    }
+
+    /// Sum fetched pool statistics per operator
+    ///
+    /// Pools missing from the registry are reported under their own name.
+    /// Validators delegated to by several of an operator's pools are counted once.
+    #[must_use]
+    pub fn aggregate_by_operator(&self, pools: &HashMap<String, ProductionPoolData>) -> BTreeMap<String, OperatorStatistics> {
+        let mut groups: BTreeMap<String, Vec<&ProductionPoolData>> = BTreeMap::new();
+        for pool in pools.values() {
+            let operator = self.get(&pool.pool_name).map_or_else(|| pool.pool_name.clone(), |info| info.operator.clone());
+            groups.entry(operator).or_default().push(pool);
+        }
+
+        groups
+            .into_iter()
+            .map(|(operator, mut members)| {
+                members.sort_by(|a, b| a.pool_name.cmp(&b.pool_name));
+                let mut statistics = PoolStatistics::default();
+                let mut validators = HashSet::new();
+                for pool in &members {
+                    let stats = &pool.statistics;
+                    statistics.total_accounts += stats.total_accounts;
+                    statistics.activating_accounts += stats.activating_accounts;
+                    statistics.active_accounts += stats.active_accounts;
+                    statistics.deactivating_accounts += stats.deactivating_accounts;
+                    statistics.deactivated_accounts += stats.deactivated_accounts;
+                    statistics.total_lamports += stats.total_lamports;
+                    statistics.activating_stake_lamports += stats.activating_stake_lamports;
+                    statistics.active_stake_lamports += stats.active_stake_lamports;
+                    statistics.deactivating_stake_lamports += stats.deactivating_stake_lamports;
+                    statistics.deactivated_stake_lamports += stats.deactivated_stake_lamports;
+                    validators.extend(pool.validator_distribution.keys());
+                }
+                statistics.validator_count = validators.len();
+
+                let aggregate = OperatorStatistics {
+                    operator: operator.clone(),
+                    pools: members.iter().map(|pool| pool.pool_name.clone()).collect(),
+                    statistics,
+                };
+                (operator, aggregate)
+            })
+            .collect()
+    }
+
+    /// Number of pools
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    /// Check if the registry has no pools
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+}
+
+/// Check that `value` is a base58-encoded 32-byte public key
+///
+/// # Errors
+///
+/// Returns `PoolsDataError::ConfigurationError` naming `field` if it is not.
+pub fn validate_pubkey(field: &str, value: &str) -> Result<()> {
+    match bs58::decode(value).into_vec() {
+        Ok(bytes) if bytes.len() == 32 => Ok(()),
+        Ok(bytes) => Err(PoolsDataError::ConfigurationError {
+            message: format!("Invalid {field} '{value}': {} bytes, expected 32", bytes.len()),
+        }),
+        Err(e) => Err(PoolsDataError::ConfigurationError {
+            message: format!("Invalid {field} '{value}': {e}"),
+        }),
+    }
+}
+
+fn validate_pool(pool: &PoolInfo) -> Result<()> {
+    if pool.name.trim().is_empty() {
+        return Err(PoolsDataError::ConfigurationError {
+            message: "Pool name cannot be empty".to_string(),
+        });
+    }
+    validate_pubkey(&format!("authority of pool '{}'", pool.name), &pool.authority)?;
+    if let Some(mint) = &pool.lst_mint {
+        validate_pubkey(&format!("LST mint of pool '{}'", pool.name), mint)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JITO_AUTHORITY: &str = "6iQKfEyhr3bZMotVkW6beNZz5CPAkiwvgV2CTje9pVSS";
+    const NEW_AUTHORITY: &str = "Vote111111111111111111111111111111111111111";
+
+    #[test]
+    fn test_embedded_registry_is_valid() {
+        let registry = PoolRegistry::embedded();
+        assert_eq!(registry.len(), get_all_pools().len());
+        registry.validate().unwrap();
+        assert_eq!(registry.get_by_authority(JITO_AUTHORITY).unwrap().name, "jito");
+    }
+
+    #[test]
+    fn test_toml_overlay() {
+        let overlay = RegistryOverlay::from_toml_str(&format!(
+            r#"
+            disable = ["ftx"]
+
+            [[pools]]
+            name = "newlst"
+            authority = "{NEW_AUTHORITY}"
+            authority_role = "both"
+            program = "spl_stake_pool"
+            operator = "jito"
+            status = "deprecated"
+            tags = ["test"]
+
+            [[pools]]
+            name = "jito"
+            authority = "{JITO_AUTHORITY}"
+            authority_role = "withdrawer"
+
+            [[pools]]
+            name = "socean"
+            authority = "{JITO_AUTHORITY}"
+            disabled = true
+
+            [aliases]
+            jitosol = "jito"
+            "#
+        ))
+        .unwrap();
+
+        let mut registry = PoolRegistry::embedded();
+        let before = registry.len();
+        registry.apply(overlay).unwrap();
+
+        assert_eq!(registry.len(), before - 1);
+        assert!(!registry.contains("ftx"));
+        assert!(!registry.contains("socean"));
+        let newlst = registry.get("newlst").unwrap();
+        assert_eq!(newlst.authority_role, AuthorityRole::Both);
+        assert_eq!(newlst.program, PoolProgram::SplStakePool);
+        assert_eq!(newlst.display_name, "newlst");
+        assert!(!newlst.is_active() && newlst.has_tag("test"));
+        assert_eq!(registry.pools_by_operator("jito").len(), 2);
+        assert_eq!(registry.get("jitosol").unwrap().authority_role, AuthorityRole::Withdrawer);
+        // Aliases resolve in bulk lookups, unknown names are skipped
+        assert_eq!(registry.get_many(&["jitosol", "unknown"]).len(), 1);
+    }
+
+    #[test]
+    fn test_json_overlay_replace_defaults() {
+        let overlay = RegistryOverlay::from_json_str(&format!(
+            r#"{{"replace_defaults": true, "pools": [{{"name": "only", "authority": "{NEW_AUTHORITY}"}}]}}"#
+        ))
+        .unwrap();
+
+        let mut registry = PoolRegistry::embedded();
+        registry.apply(overlay).unwrap();
+        assert_eq!(registry.names(), vec!["only".to_string()]);
+    }
+
+    #[test]
+    fn test_invalid_overlay_is_rejected() {
+        let mut registry = PoolRegistry::embedded();
+        let before = registry.len();
+
+        let bad_key = RegistryOverlay {
+            pools: vec![RegistryPoolEntry {
+                name: "bad".to_string(),
+                authority: "not-base58-0OIl".to_string(),
+                authority_role: AuthorityRole::Staker,
+                lst_mint: None,
+                program: PoolProgram::Native,
+                operator: None,
+                display_name: None,
+                status: PoolStatus::Active,
+                tags: Vec::new(),
+                disabled: false,
+            }],
+            ..RegistryOverlay::default()
+        };
+        assert!(registry.apply(bad_key).is_err());
+
+        let mut bad_alias = RegistryOverlay::default();
+        bad_alias.aliases.insert("x".to_string(), "missing".to_string());
+        assert!(registry.apply(bad_alias).is_err());
+
+        // jito already uses this authority, whatever the role
+        let duplicate = RegistryOverlay::from_toml_str(&format!(
+            r#"
+            [[pools]]
+            name = "jito_copy"
+            authority = "{JITO_AUTHORITY}"
+            authority_role = "withdrawer"
+            "#
+        ))
+        .unwrap();
+        assert!(registry.apply(duplicate).is_err());
+
+        let mut shadowing = RegistryOverlay::default();
+        shadowing.aliases.insert("jito".to_string(), "marinade".to_string());
+        assert!(registry.apply(shadowing).is_err());
+
+        // Failed overlays leave the registry untouched
+        assert_eq!(registry.len(), before);
+        assert!(validate_pubkey("key", "11111111111111111111111111111111").is_ok());
+        assert!(validate_pubkey("key", "1111").is_err());
+    }
+
+    #[test]
+    fn test_aggregate_by_operator() {
+        let pool = |name: &str, lamports: u64, validators: &[&str]| {
+            let mut data = crate::types::PoolData::new(name.to_string(), String::new());
+            data.statistics.total_accounts = validators.len();
+            data.statistics.total_lamports = lamports;
+            for vote in validators {
+                data.validator_distribution.insert((*vote).to_string(), crate::types::ValidatorStake::new());
+            }
+            (name.to_string(), ProductionPoolData::from(&data))
+        };
+        let pools: HashMap<String, ProductionPoolData> =
+            [pool("binance", 10, &["a", "b"]), pool("binance_2", 5, &["b", "c"]), pool("jito", 7, &["a"]), pool("unlisted", 1, &["d"])]
+                .into_iter()
+                .collect();
+
+        let operators = PoolRegistry::embedded().aggregate_by_operator(&pools);
+        let binance = &operators["binance"];
+        assert_eq!(binance.pools, vec!["binance", "binance_2"]);
+        assert_eq!(binance.statistics.total_lamports, 15);
+        assert_eq!(binance.statistics.total_accounts, 4);
+        assert_eq!(binance.statistics.validator_count, 3);
+        assert_eq!(operators["jito"].statistics.validator_count, 1);
+        assert_eq!(operators["unlisted"].pools, vec!["unlisted"]);
+    }
+
+    #[test]
+    fn test_merge_file() {
+        let path = std::env::temp_dir().join(format!("pools-registry-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"disable": ["ftx"], "aliases": {"msol": "marinade"}}"#).unwrap();
+
+        let registry = PoolRegistry::embedded().merge_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(!registry.contains("ftx"));
+        assert_eq!(registry.get("msol").unwrap().name, "marinade");
+        assert!(PoolRegistry::embedded().merge_file("/nonexistent/registry.toml").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_merge_url_size_limit() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Serve one response per body; the large one has no Content-Length
+        let serve = |body: String, length: bool| async move {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/pools.json", listener.local_addr().unwrap());
+            tokio::spawn(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+                let header = if length { format!("Content-Length: {}\r\n", body.len()) } else { String::new() };
+                let response = format!("HTTP/1.1 200 OK\r\n{header}Connection: close\r\n\r\n{body}");
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+            url
+        };
+
+        let url = serve(r#"{"disable": ["ftx"]}"#.to_string(), true).await;
+        assert!(!PoolRegistry::embedded().merge_url(&url).await.unwrap().contains("ftx"));
+
+        let padding = " ".repeat(MAX_REGISTRY_SIZE);
+        for length in [true, false] {
+            let url = serve(format!(r#"{{"disable": ["ftx"]}}{padding}"#), length).await;
+            let error = PoolRegistry::embedded().merge_url(&url).await.unwrap_err();
+            assert!(matches!(error, PoolsDataError::ConfigurationError { .. }), "{error:?}");
+        }
+    }
+}
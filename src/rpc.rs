@@ -3,10 +3,22 @@
 //! This module handles the low-level RPC communication with Solana nodes,
 //! including request formatting, response parsing, and error handling.
 
+use crate::config::StakeAccountEncoding;
+use crate::decode::{decode_account_data, decode_stake_account, ByteReader, STAKE_ACCOUNT_SIZE};
 use crate::error::{PoolsDataError, Result};
-use crate::types::{StakeAccountInfo, StakeAuthorized, StakeDelegation, StakeLockup};
+use crate::exchange_rate::TokenSupply;
+use crate::pools::AuthorityRole;
+use crate::stake_math::{feature_activation_slot, StakeHistory, STAKE_HISTORY_SYSVAR};
+use crate::transport::{http_error_status, HttpTransport, RpcTransport};
+use crate::types::{
+    EpochInfo, EpochSchedule, StakeAccountInfo, StakeAuthorized, StakeDelegation, StakeLockup,
+};
+use futures::StreamExt;
+use serde::de::{DeserializeOwned, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// RPC request structure
@@ -18,6 +30,20 @@ struct RpcRequest {
     params: Value,
 }
 
+/// `filters` matching stake accounts whose authority at `offset` is `authority`
+///
+/// Shared by `getProgramAccounts` and `programSubscribe` so both select the same accounts.
+fn stake_authority_filters(authority: &str, offset: usize) -> Value {
+    json!([
+        {
+            "memcmp": {
+                "offset": offset,
+                "bytes": authority
+            }
+        }
+    ])
+}
+
 impl RpcRequest {
     /// Create a new RPC request
     fn new(id: u64, method: &str, params: Value) -> Self {
@@ -30,16 +56,108 @@ impl RpcRequest {
     }
 
     /// Create getProgramAccounts request for stake accounts
-    fn get_program_accounts_stake(id: u64, authority: &str) -> Self {
+    ///
+    /// `offset` selects the authority field to match (12 = staker, 44 = withdrawer).
+    fn get_program_accounts_stake(
+        id: u64,
+        authority: &str,
+        offset: usize,
+        encoding: StakeAccountEncoding,
+    ) -> Self {
+        let params = json!([
+            "Stake11111111111111111111111111111111111111",
+            {
+                "encoding": encoding.as_rpc_str(),
+                "filters": stake_authority_filters(authority, offset)
+            }
+        ]);
+
+        Self::new(id, "getProgramAccounts", params)
+    }
+
+    /// Create getProgramAccounts request for every stake account (binary `encoding`)
+    ///
+    /// `data_slice` is `(offset, length)` of the data to return for each account.
+    fn get_program_accounts_stake_sweep(
+        id: u64,
+        encoding: StakeAccountEncoding,
+        data_slice: Option<(usize, usize)>,
+    ) -> Self {
+        let mut config = json!({
+            "encoding": encoding.as_rpc_str(),
+            "filters": [
+                {
+                    "dataSize": STAKE_ACCOUNT_SIZE
+                }
+            ]
+        });
+        if let Some((offset, length)) = data_slice {
+            config["dataSlice"] = json!({
+                "offset": offset,
+                "length": length
+            });
+        }
+
+        Self::new(id, "getProgramAccounts", json!(["Stake11111111111111111111111111111111111111", config]))
+    }
+
+    /// Create getMultipleAccounts request with a binary `encoding`
+    fn get_multiple_accounts(id: u64, pubkeys: &[String], encoding: StakeAccountEncoding) -> Self {
+        let params = json!([
+            pubkeys,
+            {
+                "encoding": encoding.as_rpc_str()
+            }
+        ]);
+
+        Self::new(id, "getMultipleAccounts", params)
+    }
+
+    /// Create programSubscribe request for base64 stake accounts (same filter as `get_program_accounts_stake`)
+    #[cfg(feature = "websocket")]
+    fn program_subscribe_stake(id: u64, authority: &str, offset: usize) -> Self {
         let params = json!([
             "Stake11111111111111111111111111111111111111",
             {
-                "encoding": "jsonParsed",
+                "encoding": StakeAccountEncoding::Base64.as_rpc_str(),
+                "commitment": "confirmed",
+                "filters": stake_authority_filters(authority, offset)
+            }
+        ]);
+
+        Self::new(id, "programSubscribe", params)
+    }
+
+    /// Create getEpochInfo request
+    fn get_epoch_info(id: u64) -> Self {
+        Self::new(id, "getEpochInfo", json!([]))
+    }
+
+    /// Create getEpochSchedule request
+    fn get_epoch_schedule(id: u64) -> Self {
+        Self::new(id, "getEpochSchedule", json!([]))
+    }
+
+    /// Create getProgramAccounts request returning a base64 slice of each matching account
+    fn get_program_accounts_sliced(
+        id: u64,
+        program_id: &str,
+        memcmp: (usize, &str),
+        data_slice: (usize, usize),
+    ) -> Self {
+        let params = json!([
+            program_id,
+            {
+                "encoding": "base64",
+                "dataSlice": {
+                    "offset": data_slice.0,
+                    "length": data_slice.1
+                },
                 "filters": [
                     {
                         "memcmp": {
-                            "offset": 12,
-                            "bytes": authority
+                            "offset": memcmp.0,
+                            "bytes": memcmp.1
                         }
                     }
                 ]
@@ -48,6 +166,23 @@ impl RpcRequest {
 
         Self::new(id, "getProgramAccounts", params)
     }
+
+    /// Create getTokenSupply request
+    fn get_token_supply(id: u64, mint: &str) -> Self {
+        Self::new(id, "getTokenSupply", json!([mint]))
+    }
+
+    /// Create getAccountInfo request returning base64 account data
+    fn get_account_info_base64(id: u64, pubkey: &str) -> Self {
+        let params = json!([
+            pubkey,
+            {
+                "encoding": "base64"
+            }
+        ]);
+
+        Self::new(id, "getAccountInfo", params)
+    }
 }
 
 /// RPC response structure
@@ -67,6 +202,36 @@ struct RpcError {
     data: Option<Value>, // Additional error data for debugging (kept for future use)
 }
 
+/// Response wrapper carrying a slot context
+#[derive(Debug, Deserialize)]
+struct RpcContextResponse<T> {
+    value: T,
+}
+
+/// Account returned with a binary encoding
+#[derive(Debug, Deserialize)]
+struct RawEncodedAccount {
+    lamports: u64,
+    /// `[data, encoding]` pair
+    data: (String, String),
+    executable: bool,
+    owner: String,
+}
+
+/// Token amount returned by getTokenSupply
+#[derive(Debug, Deserialize)]
+struct RawTokenAmount {
+    amount: String, // String because values can exceed JSON number precision
+    decimals: u8,
+}
+
+/// Program account returned by getProgramAccounts with a binary encoding
+#[derive(Debug, Deserialize)]
+struct RawEncodedProgramAccount {
+    pubkey: String,
+    account: RawEncodedAccount,
+}
+
 /// Raw stake account data from RPC
 #[derive(Debug, Deserialize)]
 struct RawStakeAccount {
@@ -162,81 +327,432 @@ struct RawDelegation {
     warmup_cooldown_rate: f64,
 }
 
+/// Number of body chunks buffered between the transport and the streaming parser
+const STREAM_CHUNK_BUFFER: usize = 16;
+
+/// Blocking reader over response body chunks sent by an async task
+struct ChunkReader {
+    chunks: tokio::sync::mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl std::io::Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.chunks.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.chunk.len() - self.position);
+        buf[..len].copy_from_slice(&self.chunk[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// `getProgramAccounts` result array, converted to stake accounts element by element
+///
+/// With `authorities`, only accounts whose staker or withdrawer is in the set are kept.
+#[derive(Debug, Clone)]
+struct StakeAccountSeq {
+    role: AuthorityRole,
+    encoding: StakeAccountEncoding,
+    authorities: Option<Arc<HashSet<String>>>,
+}
+
+impl StakeAccountSeq {
+    fn keeps(&self, stake_account: &StakeAccountInfo) -> bool {
+        self.authorities.as_ref().map_or(true, |authorities| is_authorized(authorities, &stake_account.authorized))
+    }
+}
+
+/// Whether the staker or withdrawer of a stake account is in `authorities`
+fn is_authorized(authorities: &HashSet<String>, authorized: &StakeAuthorized) -> bool {
+    authorities.contains(&authorized.staker) || authorities.contains(&authorized.withdrawer)
+}
+
+impl<'de> DeserializeSeed<'de> for StakeAccountSeq {
+    type Value = Vec<StakeAccountInfo>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for StakeAccountSeq {
+    type Value = Vec<StakeAccountInfo>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an array of program accounts")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error> {
+        let mut stake_accounts = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        if self.encoding == StakeAccountEncoding::JsonParsed {
+            while let Some(raw_account) = seq.next_element::<RawStakeAccount>()? {
+                let pubkey = raw_account.pubkey.clone();
+                let parsed = RpcClient::keep_valid(&pubkey, RpcClient::parse_stake_account(raw_account), self.role);
+                stake_accounts.extend(parsed.filter(|stake_account| self.keeps(stake_account)));
+            }
+        } else {
+            while let Some(raw_account) = seq.next_element::<RawEncodedProgramAccount>()? {
+                let pubkey = raw_account.pubkey.clone();
+                let parsed = RpcClient::keep_valid(&pubkey, RpcClient::parse_encoded_stake_account(raw_account), self.role);
+                stake_accounts.extend(parsed.filter(|stake_account| self.keeps(stake_account)));
+            }
+        }
+        Ok(stake_accounts)
+    }
+}
+
+/// `getProgramAccounts` result array of `dataSlice` authority bytes, converted element by element
+///
+/// Only accounts whose staker or withdrawer is in `authorities` are kept.
+struct StakeAuthoritySeq {
+    authorities: Arc<HashSet<String>>,
+}
+
+impl<'de> DeserializeSeed<'de> for StakeAuthoritySeq {
+    type Value = Vec<(String, StakeAuthorized)>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for StakeAuthoritySeq {
+    type Value = Vec<(String, StakeAuthorized)>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an array of program account slices")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error> {
+        let mut authorities = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(raw) = seq.next_element::<RawEncodedProgramAccount>()? {
+            let authorized = RpcClient::parse_stake_authorities(&raw).map_err(serde::de::Error::custom)?;
+            if is_authorized(&self.authorities, &authorized) {
+                authorities.push((raw.pubkey, authorized));
+            }
+        }
+        Ok(authorities)
+    }
+}
+
+/// JSON-RPC envelope whose `result` is parsed with the seed `S`
+struct ResponseSeed<S>(S);
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for ResponseSeed<S> {
+    type Value = RpcResponse<S::Value>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de>> Visitor<'de> for ResponseSeed<S> {
+    type Value = RpcResponse<S::Value>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a JSON-RPC response")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Self::Value, A::Error> {
+        let (mut jsonrpc, mut id, mut result, mut error) = (None, None, None, None);
+        let mut seed = Some(self.0);
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "jsonrpc" => jsonrpc = Some(map.next_value()?),
+                "id" => id = Some(map.next_value()?),
+                "result" => {
+                    let seed = seed.take().ok_or_else(|| serde::de::Error::duplicate_field("result"))?;
+                    result = Some(map.next_value_seed(seed)?);
+                }
+                "error" => error = map.next_value()?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(RpcResponse {
+            jsonrpc: jsonrpc.ok_or_else(|| serde::de::Error::missing_field("jsonrpc"))?,
+            id: id.ok_or_else(|| serde::de::Error::missing_field("id"))?,
+            result,
+            error,
+        })
+    }
+}
+
+/// Reply to a `getProgramAccounts` batch
+enum StakeAccountsBatch {
+    /// One response per request, in the order the endpoint sent them
+    Responses(Vec<RpcResponse<Vec<StakeAccountInfo>>>),
+    /// A single response (its id is usually null): the endpoint did not process the batch
+    Single(SingleReply),
+}
+
+/// Error of a single response sent in reply to a batch
+#[derive(Debug, Deserialize)]
+struct SingleReply {
+    error: Option<RpcError>,
+}
+
+/// Parses a [`StakeAccountsBatch`], converting each response's accounts with [`StakeAccountSeq`]
+struct StakeAccountsBatchSeed(StakeAccountSeq);
+
+impl<'de> DeserializeSeed<'de> for StakeAccountsBatchSeed {
+    type Value = StakeAccountsBatch;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for StakeAccountsBatchSeed {
+    type Value = StakeAccountsBatch;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a JSON-RPC batch response")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error> {
+        let mut responses = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(response) = seq.next_element_seed(ResponseSeed(self.0.clone()))? {
+            responses.push(response);
+        }
+        Ok(StakeAccountsBatch::Responses(responses))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> std::result::Result<Self::Value, A::Error> {
+        SingleReply::deserialize(serde::de::value::MapAccessDeserializer::new(map)).map(StakeAccountsBatch::Single)
+    }
+}
+
+/// Serialized `programSubscribe` request for stake accounts where `authority` is at `offset`
+#[cfg(feature = "websocket")]
+pub(crate) fn program_subscribe_stake_message(id: u64, authority: &str, offset: usize) -> Result<String> {
+    Ok(serde_json::to_string(&RpcRequest::program_subscribe_stake(id, authority, offset))?)
+}
+
+/// Parse the `value` of a `programNotification` into the account pubkey and stake account
+///
+/// The account is `None` when it was closed (no lamports or no longer owned by the stake program).
+#[cfg(feature = "websocket")]
+pub(crate) fn parse_stake_account_notification(value: Value) -> Result<(String, Option<StakeAccountInfo>)> {
+    let raw: RawEncodedProgramAccount = serde_json::from_value(value)?;
+    if raw.account.lamports == 0 || raw.account.owner != "Stake11111111111111111111111111111111111111" {
+        return Ok((raw.pubkey, None));
+    }
+    let pubkey = raw.pubkey.clone();
+    RpcClient::parse_encoded_stake_account(raw).map(|account| (pubkey, Some(account)))
+}
+
+/// Parse a whole `jsonParsed` `getProgramAccounts` response body at once
+///
+/// How responses were parsed before streaming: the body and every raw account are
+/// held until conversion. Only kept as the baseline of the `parse_memory` benchmark.
+pub fn parse_stake_accounts_buffered(body: &str) -> Result<Vec<StakeAccountInfo>> {
+    let response: RpcResponse<Vec<RawStakeAccount>> = serde_json::from_str(body)?;
+    let raw_accounts = RpcClient::into_result(response)?;
+    Ok(raw_accounts
+        .into_iter()
+        .filter_map(|raw_account| {
+            let pubkey = raw_account.pubkey.clone();
+            RpcClient::keep_valid(&pubkey, RpcClient::parse_stake_account(raw_account), AuthorityRole::Staker)
+        })
+        .collect())
+}
+
+/// Maximum number of pubkeys accepted by `getMultipleAccounts`
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
 /// Internal RPC client for making requests
 pub struct RpcClient {
-    client: reqwest::Client,
-    url: String,
+    transport: Arc<dyn RpcTransport>,
+    timeout: Duration,
     request_id: std::sync::atomic::AtomicU64,
+    account_encoding: StakeAccountEncoding,
+    /// Cleared once the endpoint rejects a JSON-RPC batch
+    batch_supported: std::sync::atomic::AtomicBool,
 }
 
 impl Clone for RpcClient {
     fn clone(&self) -> Self {
         Self {
-            client: self.client.clone(),
-            url: self.url.clone(),
+            transport: Arc::clone(&self.transport),
+            timeout: self.timeout,
             request_id: std::sync::atomic::AtomicU64::new(0),
+            account_encoding: self.account_encoding,
+            batch_supported: std::sync::atomic::AtomicBool::new(self.supports_batch()),
         }
     }
 }
 
 impl RpcClient {
-    /// Create a new RPC client
+    /// Create a new RPC client posting to `url` over HTTP
     pub fn new(url: String, timeout: Duration) -> Self {
-        let client = reqwest::Client::builder()
-            .timeout(timeout)
-            .user_agent("pools-data-lib/0.1.0")
-            .build()
-            .expect("Failed to create HTTP client");
+        Self::with_transport(Arc::new(HttpTransport::new(url, timeout)), timeout)
+    }
 
+    /// Create a new RPC client over a custom transport
+    ///
+    /// `timeout` bounds every request, whatever the transport.
+    pub fn with_transport(transport: Arc<dyn RpcTransport>, timeout: Duration) -> Self {
         Self {
-            client,
-            url,
+            transport,
+            timeout,
             request_id: std::sync::atomic::AtomicU64::new(1),
+            account_encoding: StakeAccountEncoding::JsonParsed,
+            batch_supported: std::sync::atomic::AtomicBool::new(true),
         }
     }
 
+    /// Set the encoding used when fetching stake accounts
+    #[must_use]
+    pub const fn with_account_encoding(mut self, encoding: StakeAccountEncoding) -> Self {
+        self.account_encoding = encoding;
+        self
+    }
+
     /// Get next request ID
     fn next_request_id(&self) -> u64 {
         self.request_id
             .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
     }
 
-    /// Fetch stake accounts for a specific pool authority
-    pub async fn fetch_stake_accounts_for_authority(
-        &self,
-        authority: &str,
-    ) -> Result<Vec<StakeAccountInfo>> {
-        let request_id = self.next_request_id();
-        let request = RpcRequest::get_program_accounts_stake(request_id, authority);
-
-        log::debug!("Sending RPC request for authority: {authority}");
-
-        let response = self
-            .client
-            .post(&self.url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
-
-        // Check for HTTP errors
-        if !response.status().is_success() {
-            return Err(PoolsDataError::NetworkError {
-                message: format!("HTTP error: {}", response.status()),
-            });
-        }
+    /// Whether JSON-RPC batches are sent (false once the endpoint rejected one)
+    pub fn supports_batch(&self) -> bool {
+        self.batch_supported.load(std::sync::atomic::Ordering::Relaxed)
+    }
 
-        let response_text = response.text().await?;
+    /// Stop sending batches to this endpoint
+    fn reject_batches(&self, reason: &dyn std::fmt::Display) {
+        log::warn!("RPC endpoint rejected batch request ({reason}), falling back to single requests");
+        self.batch_supported.store(false, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Whether an error response to a batch means the endpoint does not accept batches
+    ///
+    /// Transient errors (rate limits, unhealthy node) are not rejections.
+    fn is_batch_rejection(error: &RpcError) -> bool {
+        matches!(error.code, -32600 | -32601) || error.message.to_ascii_lowercase().contains("batch")
+    }
+
+    /// Send a request (or a batch of requests) through the transport and return the raw response body
+    async fn post(&self, request: &(impl Serialize + Sync)) -> Result<String> {
+        let body = serde_json::to_string(request)?;
+        tokio::time::timeout(self.timeout, self.transport.send(body))
+            .await
+            .map_err(|_| PoolsDataError::RequestTimeout { timeout: self.timeout })?
+    }
+
+    /// Send a request and return the validated `result` payload
+    async fn send_request<T: DeserializeOwned>(&self, request: &RpcRequest) -> Result<T> {
+        let response_text = self.post(request).await?;
 
         // Try to parse as RPC response
-        let rpc_response: RpcResponse<Vec<RawStakeAccount>> = serde_json::from_str(&response_text)
-            .map_err(|e| PoolsDataError::ParseError {
+        let rpc_response: RpcResponse<T> = serde_json::from_str(&response_text).map_err(|e| {
+            PoolsDataError::ParseError {
                 message: format!("Failed to parse RPC response: {e}"),
-            })?;
+            }
+        })?;
 
         // Validate RPC response format
-        Self::validate_rpc_response(&rpc_response, request_id)?;
+        Self::validate_rpc_response(&rpc_response, request.id)?;
+
+        Self::into_result(rpc_response)
+    }
+
+    /// Send a request (or a batch) and parse the response with `seed` while it streams in
+    ///
+    /// The body is parsed from the transport's chunks on a blocking thread, so
+    /// neither the whole body nor a parsed tree of it is held: seeds convert large
+    /// results element by element.
+    async fn send_streamed<S, T>(&self, request: &(impl Serialize + Sync), seed: S) -> Result<T>
+    where
+        S: for<'de> DeserializeSeed<'de, Value = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let body = serde_json::to_string(request)?;
+
+        let streamed = async {
+            let mut chunks = self.transport.send_chunked(body).await?;
+            let (sender, receiver) = tokio::sync::mpsc::channel(STREAM_CHUNK_BUFFER);
+            let parser = tokio::task::spawn_blocking(move || {
+                let reader = ChunkReader {
+                    chunks: receiver,
+                    chunk: Vec::new(),
+                    position: 0,
+                };
+                let mut deserializer = serde_json::Deserializer::from_reader(std::io::BufReader::new(reader));
+                let response = seed.deserialize(&mut deserializer)?;
+                deserializer.end()?;
+                Ok::<_, serde_json::Error>(response)
+            });
+
+            let mut transport_error = None;
+            while let Some(chunk) = chunks.next().await {
+                match chunk {
+                    Ok(chunk) => {
+                        // The parser stopped early (invalid payload): its error is reported below
+                        if sender.send(chunk).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        transport_error = Some(e);
+                        break;
+                    }
+                }
+            }
+            drop(sender);
+
+            let parsed = parser.await.map_err(|e| PoolsDataError::InternalError {
+                message: format!("Response parser failed: {e}"),
+            })?;
+            // A truncated body is reported as the transport failure that caused it
+            if let Some(error) = transport_error {
+                return Err(error);
+            }
+            parsed.map_err(|e| PoolsDataError::ParseError {
+                message: format!("Failed to parse RPC response: {e}"),
+            })
+        };
+
+        tokio::time::timeout(self.timeout, streamed)
+            .await
+            .map_err(|_| PoolsDataError::RequestTimeout { timeout: self.timeout })?
+    }
 
+    /// Send a request whose `result` is parsed with `seed` while the response streams in
+    async fn send_streamed_request<S, T>(&self, request: &RpcRequest, seed: S) -> Result<T>
+    where
+        S: for<'de> DeserializeSeed<'de, Value = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let rpc_response = self.send_streamed(request, ResponseSeed(seed)).await?;
+        Self::validate_rpc_response(&rpc_response, request.id)?;
+        Self::into_result(rpc_response)
+    }
+
+    /// Send a `getProgramAccounts` request, converting stake accounts while the response streams in
+    async fn send_stake_accounts_request(
+        &self,
+        request: &RpcRequest,
+        role: AuthorityRole,
+        encoding: StakeAccountEncoding,
+    ) -> Result<Vec<StakeAccountInfo>> {
+        self.send_streamed_request(request, StakeAccountSeq { role, encoding, authorities: None }).await
+    }
+
+    /// Extract the `result` of a validated response, or its RPC error
+    fn into_result<T>(rpc_response: RpcResponse<T>) -> Result<T> {
         // Check for RPC errors
         if let Some(error) = rpc_response.error {
             // Validate the error structure before using it
@@ -250,32 +766,361 @@ impl RpcClient {
             });
         }
 
-        let raw_accounts = rpc_response
+        rpc_response
             .result
             .ok_or_else(|| PoolsDataError::ParseError {
                 message: "Missing result in RPC response".to_string(),
-            })?;
+            })
+    }
+
+    /// Fetch current epoch information
+    pub async fn get_epoch_info(&self) -> Result<EpochInfo> {
+        let request = RpcRequest::get_epoch_info(self.next_request_id());
+        let epoch_info: EpochInfo = self.send_request(&request).await?;
+
+        log::debug!(
+            "Current epoch: {} (slot {}/{})",
+            epoch_info.epoch,
+            epoch_info.slot_index,
+            epoch_info.slots_in_epoch
+        );
+
+        Ok(epoch_info)
+    }
+
+    /// Fetch the cluster epoch schedule
+    pub async fn get_epoch_schedule(&self) -> Result<EpochSchedule> {
+        let request = RpcRequest::get_epoch_schedule(self.next_request_id());
+        self.send_request(&request).await
+    }
+
+    /// Fetch raw account data for a single account
+    pub async fn get_account_data(&self, pubkey: &str) -> Result<Vec<u8>> {
+        self.get_account_with_owner(pubkey).await.map(|(_, data)| data)
+    }
+
+    /// Fetch raw account data for a single account, with its owner program
+    pub async fn get_account_with_owner(&self, pubkey: &str) -> Result<(String, Vec<u8>)> {
+        let request = RpcRequest::get_account_info_base64(self.next_request_id(), pubkey);
+        let response: RpcContextResponse<Option<RawEncodedAccount>> =
+            self.send_request(&request).await?;
+
+        let account = response.value.ok_or_else(|| PoolsDataError::InvalidStakeData {
+            message: format!("Account not found: {pubkey}"),
+        })?;
+
+        let data = decode_account_data(&account.data.0, &account.data.1)?;
+        Ok((account.owner, data))
+    }
+
+    /// Fetch the slot at which `feature` was activated (`None` while pending or absent)
+    pub async fn get_feature_activation_slot(&self, feature: &str) -> Result<Option<u64>> {
+        let request = RpcRequest::get_account_info_base64(self.next_request_id(), feature);
+        let response: RpcContextResponse<Option<RawEncodedAccount>> =
+            self.send_request(&request).await?;
+
+        match response.value {
+            Some(account) => feature_activation_slot(&decode_account_data(&account.data.0, &account.data.1)?),
+            None => Ok(None),
+        }
+    }
+
+    /// Fetch the supply of an SPL token mint
+    pub async fn get_token_supply(&self, mint: &str) -> Result<TokenSupply> {
+        let request = RpcRequest::get_token_supply(self.next_request_id(), mint);
+        let response: RpcContextResponse<RawTokenAmount> = self.send_request(&request).await?;
+
+        let amount = response.value.amount.parse().map_err(|e| PoolsDataError::ParseError {
+            message: format!("Invalid token supply for {mint}: {e}"),
+        })?;
+
+        Ok(TokenSupply {
+            amount,
+            decimals: response.value.decimals,
+        })
+    }
+
+    /// Fetch a slice of every program account whose data matches `memcmp`
+    ///
+    /// `memcmp` is `(offset, base58 bytes)`, `data_slice` is `(offset, length)`.
+    /// Returns `(pubkey, sliced data)` pairs.
+    pub async fn get_program_account_slices(
+        &self,
+        program_id: &str,
+        memcmp: (usize, &str),
+        data_slice: (usize, usize),
+    ) -> Result<Vec<(String, Vec<u8>)>> {
+        let request = RpcRequest::get_program_accounts_sliced(
+            self.next_request_id(),
+            program_id,
+            memcmp,
+            data_slice,
+        );
+        let accounts: Vec<RawEncodedProgramAccount> = self.send_request(&request).await?;
+
+        log::debug!("Received {} accounts owned by {program_id}", accounts.len());
+
+        accounts
+            .into_iter()
+            .map(|raw| {
+                decode_account_data(&raw.account.data.0, &raw.account.data.1)
+                    .map(|data| (raw.pubkey, data))
+            })
+            .collect()
+    }
+
+    /// Fetch and parse the `StakeHistory` sysvar
+    pub async fn get_stake_history(&self) -> Result<StakeHistory> {
+        let data = self.get_account_data(STAKE_HISTORY_SYSVAR).await?;
+        let history = StakeHistory::from_account_data(&data)?;
+
+        log::debug!(
+            "Received stake history with {} epochs (latest: {:?})",
+            history.len(),
+            history.latest_epoch()
+        );
+
+        Ok(history)
+    }
+
+    /// Fetch stake accounts where `authority` holds a single `role`
+    pub async fn fetch_stake_accounts_for_authority(
+        &self,
+        authority: &str,
+        role: AuthorityRole,
+    ) -> Result<Vec<StakeAccountInfo>> {
+        let offset = role.memcmp_offset().ok_or_else(|| PoolsDataError::ConfigurationError {
+            message: format!("Authority role {role:?} must be queried one role at a time"),
+        })?;
+        let request_id = self.next_request_id();
+        let request = RpcRequest::get_program_accounts_stake(
+            request_id,
+            authority,
+            offset,
+            self.account_encoding,
+        );
+
+        log::debug!("Sending RPC request for authority: {authority} ({role:?})");
+
+        let stake_accounts = self.send_stake_accounts_request(&request, role, self.account_encoding).await?;
 
         log::debug!(
             "Received {} stake accounts for authority: {}",
-            raw_accounts.len(),
+            stake_accounts.len(),
             authority
         );
 
-        // Convert raw accounts to our types
-        let mut stake_accounts = Vec::new();
-        for raw_account in raw_accounts {
-            let pubkey = raw_account.pubkey.clone(); // Clone before moving
-            match Self::parse_stake_account(raw_account) {
-                Ok(stake_account) => stake_accounts.push(stake_account),
-                Err(e) => {
-                    log::warn!("Failed to parse stake account {pubkey}: {e}");
-                    // Continue processing other accounts instead of failing completely
+        Ok(stake_accounts)
+    }
+
+    /// Binary encoding for sweeps and multi-account fetches (`jsonParsed` falls back to base64)
+    fn binary_encoding(&self) -> StakeAccountEncoding {
+        match self.account_encoding {
+            StakeAccountEncoding::JsonParsed => StakeAccountEncoding::Base64,
+            encoding => encoding,
+        }
+    }
+
+    /// Fetch the stake accounts of `authorities` in one unfiltered `getProgramAccounts` scan
+    ///
+    /// Accounts whose staker and withdrawer are both outside `authorities` are dropped
+    /// as the response is parsed. Accounts are returned with `matched_authority` set to
+    /// `Staker`; callers partitioning them by authority set the role they matched.
+    pub async fn fetch_all_stake_accounts(&self, authorities: &HashSet<String>) -> Result<Vec<StakeAccountInfo>> {
+        let encoding = self.binary_encoding();
+        let request = RpcRequest::get_program_accounts_stake_sweep(self.next_request_id(), encoding, None);
+        let seed = StakeAccountSeq {
+            role: AuthorityRole::Staker,
+            encoding,
+            authorities: Some(Arc::new(authorities.clone())),
+        };
+        let stake_accounts = self.send_streamed_request(&request, seed).await?;
+
+        log::debug!("Received {} stake accounts from stake program sweep", stake_accounts.len());
+
+        Ok(stake_accounts)
+    }
+
+    /// Fetch the staker and withdrawer of the stake accounts of `authorities`, without the rest of their data
+    ///
+    /// Returns `(pubkey, authorities)` pairs from a `dataSlice` scan of the authority bytes;
+    /// accounts of other authorities are dropped as the response is parsed.
+    pub async fn fetch_all_stake_authorities(&self, authorities: &HashSet<String>) -> Result<Vec<(String, StakeAuthorized)>> {
+        let data_slice = (AuthorityRole::STAKER_OFFSET, 64);
+        let request =
+            RpcRequest::get_program_accounts_stake_sweep(self.next_request_id(), self.binary_encoding(), Some(data_slice));
+        let seed = StakeAuthoritySeq {
+            authorities: Arc::new(authorities.clone()),
+        };
+        let authorities = self.send_streamed_request(&request, seed).await?;
+
+        log::debug!("Received authorities of {} stake accounts from stake program sweep", authorities.len());
+
+        Ok(authorities)
+    }
+
+    /// Staker and withdrawer from the `dataSlice` authority bytes of a stake account
+    fn parse_stake_authorities(raw: &RawEncodedProgramAccount) -> Result<StakeAuthorized> {
+        let data = decode_account_data(&raw.account.data.0, &raw.account.data.1)?;
+        let mut reader = ByteReader::new(&data);
+        Ok(StakeAuthorized {
+            staker: reader.read_pubkey()?,
+            withdrawer: reader.read_pubkey()?,
+        })
+    }
+
+    /// Fetch stake accounts by pubkey with `getMultipleAccounts`
+    ///
+    /// At most [`MAX_MULTIPLE_ACCOUNTS`] pubkeys per call. Closed accounts are skipped.
+    pub async fn fetch_stake_accounts_by_pubkey(&self, pubkeys: &[String]) -> Result<Vec<StakeAccountInfo>> {
+        let request = RpcRequest::get_multiple_accounts(self.next_request_id(), pubkeys, self.binary_encoding());
+        let response: RpcContextResponse<Vec<Option<RawEncodedAccount>>> = self.send_request(&request).await?;
+
+        let raw_accounts = pubkeys
+            .iter()
+            .zip(response.value)
+            .filter_map(|(pubkey, account)| {
+                account.map(|account| RawEncodedProgramAccount {
+                    pubkey: pubkey.clone(),
+                    account,
+                })
+            })
+            .collect();
+
+        Ok(Self::parse_encoded_stake_accounts(raw_accounts, AuthorityRole::Staker))
+    }
+
+    /// Fetch stake accounts for several `(authority, role)` queries in one JSON-RPC batch
+    ///
+    /// Returns one result per query, in query order, matched to the responses by
+    /// request id. Returns `None` when the endpoint rejects batches (an invalid
+    /// request or "batch" error instead of an array, or HTTP 400/405/413), and
+    /// without sending anything from then on; callers then fall back to
+    /// [`Self::fetch_stake_accounts_for_authority`]. Other error responses, such as
+    /// rate limits, are returned as errors and leave batching enabled.
+    pub async fn fetch_stake_accounts_batch(
+        &self,
+        queries: &[(String, AuthorityRole)],
+    ) -> Result<Option<Vec<Result<Vec<StakeAccountInfo>>>>> {
+        if !self.supports_batch() {
+            return Ok(None);
+        }
+
+        let requests = queries
+            .iter()
+            .map(|(authority, role)| {
+                let offset = role.memcmp_offset().ok_or_else(|| PoolsDataError::ConfigurationError {
+                    message: format!("Authority role {role:?} must be queried one role at a time"),
+                })?;
+                Ok(RpcRequest::get_program_accounts_stake(
+                    self.next_request_id(),
+                    authority,
+                    offset,
+                    self.account_encoding,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        log::debug!("Sending batch of {} getProgramAccounts requests", requests.len());
+
+        // Accounts are tagged with their query's role once matched by id
+        let seed = StakeAccountsBatchSeed(StakeAccountSeq {
+            role: AuthorityRole::Staker,
+            encoding: self.account_encoding,
+            authorities: None,
+        });
+        let reply = match self.send_streamed(&requests, seed).await {
+            Err(e) if matches!(http_error_status(&e), Some(400 | 405 | 413)) => {
+                self.reject_batches(&e);
+                return Ok(None);
+            }
+            reply => reply?,
+        };
+
+        let responses = match reply {
+            StakeAccountsBatch::Responses(responses) => responses,
+            StakeAccountsBatch::Single(reply) => match reply.error {
+                Some(error) if Self::is_batch_rejection(&error) => {
+                    self.reject_batches(&error.message);
+                    return Ok(None);
                 }
+                Some(error) => {
+                    return Err(PoolsDataError::RpcError {
+                        code: error.code,
+                        message: error.message,
+                    })
+                }
+                None => {
+                    return Err(PoolsDataError::ParseError {
+                        message: "Expected an array in reply to a batch request".to_string(),
+                    })
+                }
+            },
+        };
+        let ids: Vec<u64> = requests.iter().map(|request| request.id).collect();
+        let responses = Self::validate_batch_response(responses, &ids)?;
+
+        Ok(Some(
+            responses
+                .into_iter()
+                .zip(queries)
+                .map(|(response, (authority, role))| {
+                    let mut accounts = Self::into_result(response)?;
+                    for account in &mut accounts {
+                        account.matched_authority = *role;
+                    }
+                    log::debug!("Received {} stake accounts for authority: {authority} ({role:?})", accounts.len());
+                    Ok(accounts)
+                })
+                .collect(),
+        ))
+    }
+
+    /// Decode binary stake accounts, skipping (and logging) invalid ones
+    fn parse_encoded_stake_accounts(
+        raw_accounts: Vec<RawEncodedProgramAccount>,
+        role: AuthorityRole,
+    ) -> Vec<StakeAccountInfo> {
+        raw_accounts
+            .into_iter()
+            .filter_map(|raw_account| {
+                let pubkey = raw_account.pubkey.clone();
+                Self::keep_valid(&pubkey, Self::parse_encoded_stake_account(raw_account), role)
+            })
+            .collect()
+    }
+
+    /// Tag a converted account with the `role` it matched, logging and dropping invalid ones
+    fn keep_valid(pubkey: &str, parsed: Result<StakeAccountInfo>, role: AuthorityRole) -> Option<StakeAccountInfo> {
+        match parsed {
+            Ok(mut stake_account) => {
+                stake_account.matched_authority = role;
+                Some(stake_account)
+            }
+            Err(e) => {
+                log::warn!("Failed to parse stake account {pubkey}: {e}");
+                // Continue processing other accounts instead of failing completely
+                None
             }
         }
+    }
 
-        Ok(stake_accounts)
+    /// Decode a single binary stake account
+    fn parse_encoded_stake_account(raw: RawEncodedProgramAccount) -> Result<StakeAccountInfo> {
+        if raw.account.owner != "Stake11111111111111111111111111111111111111" {
+            return Err(PoolsDataError::InvalidStakeData {
+                message: format!("Account owner is not stake program: {}", raw.account.owner),
+            });
+        }
+
+        if raw.account.executable {
+            return Err(PoolsDataError::InvalidStakeData {
+                message: "Stake account should not be executable".to_string(),
+            });
+        }
+
+        let data = decode_account_data(&raw.account.data.0, &raw.account.data.1)?;
+        decode_stake_account(raw.pubkey, raw.account.lamports, &data)
     }
 
     /// Parse raw stake account data into our types
@@ -320,6 +1165,7 @@ impl RpcClient {
             delegation,
             authorized,
             lockup,
+            matched_authority: AuthorityRole::Staker,
         })
     }
 
@@ -413,22 +1259,7 @@ impl RpcClient {
     /// Test RPC connection
     pub async fn test_connection(&self) -> Result<()> {
         let request = RpcRequest::new(1, "getHealth", json!([]));
-
-        let response = self
-            .client
-            .post(&self.url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(PoolsDataError::NetworkError {
-                message: format!("Health check failed: {}", response.status()),
-            });
-        }
-
-        let response_text = response.text().await?;
+        let response_text = self.post(&request).await?;
         let rpc_response: RpcResponse<String> = serde_json::from_str(&response_text)?;
 
         if let Some(error) = rpc_response.error {
@@ -474,6 +1305,47 @@ impl RpcClient {
         Ok(())
     }
 
+    /// Validate a batch response and order it like the requests
+    ///
+    /// Batch responses may arrive in any order; each one is matched to its request
+    /// by id and validated with [`Self::validate_rpc_response`]. Missing, duplicate
+    /// or unknown ids are errors.
+    fn validate_batch_response<T>(responses: Vec<RpcResponse<T>>, expected_ids: &[u64]) -> Result<Vec<RpcResponse<T>>> {
+        if responses.len() != expected_ids.len() {
+            return Err(PoolsDataError::RpcError {
+                code: -32603,
+                message: format!(
+                    "Batch response size mismatch: {} (expected {})",
+                    responses.len(),
+                    expected_ids.len()
+                ),
+            });
+        }
+
+        let mut by_id: std::collections::HashMap<u64, RpcResponse<T>> = std::collections::HashMap::new();
+        for response in responses {
+            if !expected_ids.contains(&response.id) || by_id.contains_key(&response.id) {
+                return Err(PoolsDataError::RpcError {
+                    code: -32603,
+                    message: format!("Unexpected response ID in batch: {}", response.id),
+                });
+            }
+            by_id.insert(response.id, response);
+        }
+
+        expected_ids
+            .iter()
+            .map(|&id| {
+                let response = by_id.remove(&id).ok_or_else(|| PoolsDataError::RpcError {
+                    code: -32603,
+                    message: format!("Missing response ID in batch: {id}"),
+                })?;
+                Self::validate_rpc_response(&response, id)?;
+                Ok(response)
+            })
+            .collect()
+    }
+
     /// Validate RPC error structure and content
     fn validate_rpc_error(error: &RpcError) -> Result<()> {
         // Validate error code is within expected ranges
@@ -506,14 +1378,327 @@ impl RpcClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures::FutureExt;
 
     #[test]
     fn test_rpc_request_creation() {
-        let request = RpcRequest::get_program_accounts_stake(1, "test_authority");
+        let request = RpcRequest::get_program_accounts_stake(1, "test_authority", 12, StakeAccountEncoding::JsonParsed);
 
         assert_eq!(request.jsonrpc, "2.0");
         assert_eq!(request.id, 1);
         assert_eq!(request.method, "getProgramAccounts");
+
+        assert_eq!(request.params[1]["encoding"], "jsonParsed");
+
+        let withdrawer_request = RpcRequest::get_program_accounts_stake(2, "test_authority", 44, StakeAccountEncoding::Base64);
+        assert_eq!(withdrawer_request.params[1]["filters"][0]["memcmp"]["offset"], 44);
+        assert_eq!(withdrawer_request.params[1]["encoding"], "base64");
+        #[cfg(feature = "websocket")]
+        {
+            let subscribe = RpcRequest::program_subscribe_stake(5, "test_authority", 44);
+            assert_eq!(subscribe.params[1]["filters"], withdrawer_request.params[1]["filters"]);
+        }
+
+        let sweep = RpcRequest::get_program_accounts_stake_sweep(4, StakeAccountEncoding::Base64, Some((12, 64)));
+        assert_eq!(sweep.params[1]["filters"][0]["dataSize"], 200);
+        assert_eq!(sweep.params[1]["dataSlice"]["length"], 64);
+
+        let sliced = RpcRequest::get_program_accounts_sliced(3, "program", (0, "2"), (130, 32));
+        assert_eq!(sliced.params[0], "program");
+        assert_eq!(sliced.params[1]["dataSlice"]["offset"], 130);
+        assert_eq!(sliced.params[1]["dataSlice"]["length"], 32);
+        assert_eq!(sliced.params[1]["filters"][0]["memcmp"]["bytes"], "2");
+    }
+
+    #[test]
+    fn test_epoch_info_parsing() {
+        let response = r#"{
+            "jsonrpc": "2.0",
+            "id": 7,
+            "result": {
+                "absoluteSlot": 166598,
+                "blockHeight": 166500,
+                "epoch": 27,
+                "slotIndex": 2790,
+                "slotsInEpoch": 8192,
+                "transactionCount": 22661093
+            }
+        }"#;
+
+        let rpc_response: RpcResponse<EpochInfo> = serde_json::from_str(response).unwrap();
+        RpcClient::validate_rpc_response(&rpc_response, 7).unwrap();
+
+        let epoch_info = rpc_response.result.unwrap();
+        assert_eq!(epoch_info.epoch, 27);
+        assert_eq!(epoch_info.slot_index, 2790);
+        assert_eq!(epoch_info.slots_in_epoch, 8192);
+        assert_eq!(epoch_info.absolute_slot, 166_598);
+        assert_eq!(epoch_info.block_height, 166_500);
+        assert_eq!(epoch_info.transaction_count, Some(22_661_093));
+    }
+
+    #[test]
+    fn test_batch_response_matching() {
+        let response = r#"[
+            { "jsonrpc": "2.0", "id": 6, "result": [] },
+            { "jsonrpc": "2.0", "id": 5, "error": { "code": -32005, "message": "Node is behind" } }
+        ]"#;
+        let parse = || serde_json::from_str::<Vec<RpcResponse<Value>>>(response).unwrap();
+
+        // Responses are reordered to match the requests
+        let ordered = RpcClient::validate_batch_response(parse(), &[5, 6]).unwrap();
+        assert_eq!(ordered[0].id, 5);
+        assert!(matches!(RpcClient::into_result(ordered.into_iter().next().unwrap()), Err(PoolsDataError::RpcError { code: -32005, .. })));
+
+        assert!(RpcClient::validate_batch_response(parse(), &[5, 7]).is_err());
+        assert!(RpcClient::validate_batch_response(parse(), &[5, 6, 7]).is_err());
+    }
+
+    /// Serves a fixed body in small chunks, optionally failing midway
+    #[derive(Debug)]
+    struct ChunkedTransport {
+        body: String,
+        fail_after: Option<usize>,
+    }
+
+    impl RpcTransport for ChunkedTransport {
+        fn send(&self, _body: String) -> futures::future::BoxFuture<'_, Result<String>> {
+            futures::future::ready(Ok(self.body.clone())).boxed()
+        }
+
+        fn send_chunked(&self, _body: String) -> futures::future::BoxFuture<'_, Result<crate::transport::BodyChunks>> {
+            let mut chunks: Vec<Result<Vec<u8>>> = self.body.as_bytes().chunks(7).map(|chunk| Ok(chunk.to_vec())).collect();
+            if let Some(count) = self.fail_after {
+                chunks.truncate(count);
+                chunks.push(Err(PoolsDataError::NetworkError { message: "connection reset".to_string() }));
+            }
+            futures::future::ready(Ok(futures::stream::iter(chunks).boxed())).boxed()
+        }
+    }
+
+    async fn fetch_streamed(body: String, fail_after: Option<usize>) -> Result<Vec<StakeAccountInfo>> {
+        let transport = Arc::new(ChunkedTransport { body, fail_after });
+        RpcClient::with_transport(transport, Duration::from_secs(5))
+            .fetch_stake_accounts_for_authority("staker1", AuthorityRole::Staker)
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_streamed_stake_accounts() {
+        let account = |pubkey: &str, space: u64| {
+            json!({
+                "pubkey": pubkey,
+                "account": {
+                    "lamports": 2_282_880, "executable": false, "rentEpoch": 0, "space": space,
+                    "owner": "Stake11111111111111111111111111111111111111",
+                    "data": {
+                        "program": "stake", "space": space,
+                        "parsed": {
+                            "type": "initialized",
+                            "info": {
+                                "meta": {
+                                    "authorized": { "staker": "staker1", "withdrawer": "withdrawer1" },
+                                    "lockup": { "custodian": "custodian1", "epoch": 0, "unixTimestamp": 0 },
+                                    "rentExemptReserve": "2282880"
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+        };
+        let body = json!({
+            "result": [account("acct1", 200), account("invalid", 3), account("acct2", 200)],
+            "extra": { "ignored": [1, 2] },
+            "id": 1,
+            "jsonrpc": "2.0"
+        })
+        .to_string();
+
+        // Invalid accounts are skipped, unknown fields ignored
+        let accounts = fetch_streamed(body.clone(), None).await.unwrap();
+        let pubkeys: Vec<&str> = accounts.iter().map(|account| account.pubkey.as_str()).collect();
+        assert_eq!(pubkeys, ["acct1", "acct2"]);
+        assert_eq!(accounts[0].authorized.withdrawer, "withdrawer1");
+
+        let truncated = body[..body.len() / 2].to_string();
+        assert!(matches!(fetch_streamed(truncated, None).await, Err(PoolsDataError::ParseError { .. })));
+        assert!(matches!(fetch_streamed(body, Some(20)).await, Err(PoolsDataError::NetworkError { .. })));
+
+        let error = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32010,"message":"excluded from account secondary indexes"}}"#;
+        assert!(matches!(fetch_streamed(error.to_string(), None).await, Err(PoolsDataError::RpcError { code: -32010, .. })));
+    }
+
+    #[test]
+    fn test_sweep_keeps_only_pool_authorities() {
+        use crate::decode::tests::pubkey_bytes;
+
+        let key = |seed| bs58::encode(pubkey_bytes(seed)).into_string();
+        let authorities = Arc::new(HashSet::from([key(1), key(2)]));
+        let parsed = |pubkey: &str, staker: &str, withdrawer: &str| {
+            json!({
+                "pubkey": pubkey,
+                "account": {
+                    "lamports": 2_282_880, "executable": false, "rentEpoch": 0, "space": 200,
+                    "owner": "Stake11111111111111111111111111111111111111",
+                    "data": {
+                        "program": "stake", "space": 200,
+                        "parsed": {
+                            "type": "initialized",
+                            "info": {
+                                "meta": {
+                                    "authorized": { "staker": staker, "withdrawer": withdrawer },
+                                    "lockup": { "custodian": "custodian1", "epoch": 0, "unixTimestamp": 0 },
+                                    "rentExemptReserve": "2282880"
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+        };
+        let accounts = json!([
+            parsed("by_staker", &key(1), &key(9)),
+            parsed("by_withdrawer", &key(9), &key(2)),
+            parsed("foreign", &key(8), &key(9)),
+        ]);
+        let seed = StakeAccountSeq {
+            role: AuthorityRole::Staker,
+            encoding: StakeAccountEncoding::JsonParsed,
+            authorities: Some(Arc::clone(&authorities)),
+        };
+        let kept = seed.deserialize(accounts).unwrap();
+        let pubkeys: Vec<&str> = kept.iter().map(|account| account.pubkey.as_str()).collect();
+        assert_eq!(pubkeys, ["by_staker", "by_withdrawer"]);
+
+        let slice = |pubkey: &str, staker: u8, withdrawer: u8| {
+            let data = [pubkey_bytes(staker), pubkey_bytes(withdrawer)].concat();
+            let data = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, data);
+            json!({ "pubkey": pubkey, "account": { "data": [data, "base64"], "executable": false, "lamports": 1, "owner": "Stake11111111111111111111111111111111111111" } })
+        };
+        let slices = json!([slice("by_staker", 1, 9), slice("foreign", 8, 9)]);
+        let kept = StakeAuthoritySeq { authorities }.deserialize(slices).unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].0, "by_staker");
+    }
+
+    #[test]
+    fn test_account_info_decoding() {
+        let response = r#"{
+            "jsonrpc": "2.0",
+            "id": 3,
+            "result": {
+                "context": { "slot": 1 },
+                "value": {
+                    "data": ["AQAAAAAAAAA=", "base64"],
+                    "executable": false,
+                    "lamports": 1,
+                    "owner": "Sysvar1111111111111111111111111111111111111",
+                    "rentEpoch": 0,
+                    "space": 8
+                }
+            }
+        }"#;
+
+        let rpc_response: RpcResponse<RpcContextResponse<Option<RawEncodedAccount>>> =
+            serde_json::from_str(response).unwrap();
+        let account = rpc_response.result.unwrap().value.unwrap();
+        let data = decode_account_data(&account.data.0, &account.data.1).unwrap();
+        assert_eq!(data, 1u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_encoded_stake_account_parsing() {
+        let mut data = vec![0u8; crate::decode::STAKE_ACCOUNT_SIZE];
+        data[0] = 1; // Initialized
+        let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data);
+        let response = format!(
+            r#"{{
+                "jsonrpc": "2.0",
+                "id": 4,
+                "result": [
+                    {{
+                        "pubkey": "acct1",
+                        "account": {{
+                            "data": ["{encoded}", "base64"],
+                            "executable": false,
+                            "lamports": 2282880,
+                            "owner": "Stake11111111111111111111111111111111111111",
+                            "rentEpoch": 0,
+                            "space": 200
+                        }}
+                    }},
+                    {{
+                        "pubkey": "acct2",
+                        "account": {{
+                            "data": ["AAAA", "base64"],
+                            "executable": false,
+                            "lamports": 1,
+                            "owner": "Stake11111111111111111111111111111111111111",
+                            "rentEpoch": 0,
+                            "space": 3
+                        }}
+                    }}
+                ]
+            }}"#
+        );
+
+        let rpc_response: RpcResponse<Vec<RawEncodedProgramAccount>> =
+            serde_json::from_str(&response).unwrap();
+        let accounts = RpcClient::parse_encoded_stake_accounts(
+            rpc_response.result.unwrap(),
+            AuthorityRole::Withdrawer,
+        );
+
+        // Malformed account is skipped
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].pubkey, "acct1");
+        assert_eq!(accounts[0].lamports, 2_282_880);
+        assert!(accounts[0].delegation.is_none());
+        assert_eq!(accounts[0].matched_authority, AuthorityRole::Withdrawer);
+    }
+
+    #[test]
+    fn test_token_supply_parsing() {
+        let response = r#"{
+            "jsonrpc": "2.0",
+            "id": 4,
+            "result": {
+                "context": { "slot": 1114 },
+                "value": {
+                    "amount": "18446744073709551615",
+                    "decimals": 9,
+                    "uiAmount": 18446744073.709553,
+                    "uiAmountString": "18446744073.709551615"
+                }
+            }
+        }"#;
+
+        let parsed: RpcResponse<RpcContextResponse<RawTokenAmount>> = serde_json::from_str(response).unwrap();
+        let value = parsed.result.unwrap().value;
+        assert_eq!(value.amount.parse::<u64>().unwrap(), u64::MAX);
+        assert_eq!(value.decimals, 9);
+    }
+
+    #[test]
+    fn test_epoch_schedule_parsing() {
+        let response = r#"{
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "firstNormalEpoch": 8,
+                "firstNormalSlot": 8160,
+                "leaderScheduleSlotOffset": 8192,
+                "slotsPerEpoch": 8192,
+                "warmup": true
+            }
+        }"#;
+
+        let rpc_response: RpcResponse<EpochSchedule> = serde_json::from_str(response).unwrap();
+        let schedule = rpc_response.result.unwrap();
+        assert_eq!(schedule.slots_per_epoch, 8192);
+        assert_eq!(schedule.first_normal_epoch, 8);
+        assert!(schedule.warmup);
     }
 
     #[test]
@@ -540,6 +1725,6 @@ mod tests {
         assert_eq!(delegation.deactivation_epoch, u64::MAX); // Active delegation
     }
 
-    // Note: Integration tests that require actual RPC calls should be in a separate file
-    // and marked with #[ignore] or run only in CI with real endpoints
+    // Note: Client tests without a network use `MockTransport` (see transport.rs); tests
+    // against real endpoints should be in a separate file and marked with #[ignore]
 }
@@ -0,0 +1,481 @@
+//! Embedded HTTP API (`server` feature).
+//!
+//! `PoolsServer` keeps one `PoolsDataClient` and a cache of every registry pool,
+//! refreshed in the background, and serves JSON:
+//!
+//! | Route                          | Response                                                     |
+//! |--------------------------------|--------------------------------------------------------------|
+//! | `GET /pools`                   | Registry entries with cached `statistics` and `fetched_at`   |
+//! | `GET /pools/{name}`            | `ProductionPoolData`                                         |
+//! | `GET /pools/{name}/validators` | `ValidatorRow`s, largest stake first                         |
+//! | `GET /pools/{name}/stats`      | Statistics classified at `?epoch=N` (current epoch if unset) |
+//! | `GET /validators/{vote}/pools` | `ValidatorRow`s of the pools delegating to a vote account    |
+//!
+//! Errors are `{"error": "..."}` with status 404 for unknown pools, 502 when pools could
+//! not be fetched and nothing is cached, and 503 when `/stats` has no epoch to use.
+//! After a failed refresh, requests keep the stale cache (or the 502) for one TTL
+//! rather than refetching on every request. Pool names may be registry aliases.
+
+use crate::client::PoolsDataClient;
+use crate::error::{PoolsDataError, Result};
+use crate::export::ValidatorRow;
+use crate::pools::PoolInfo;
+use crate::statistics::PoolStatisticsSummary;
+use crate::statistics_calc;
+use crate::types::{PoolStatistics, ProductionPoolData};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// Default cache TTL and background refresh interval
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Fetched pools shared by all requests
+struct Cache {
+    pools: BTreeMap<String, ProductionPoolData>,
+    epoch: Option<u64>,
+    refreshed_at: Instant,
+}
+
+struct ServerState {
+    client: Arc<PoolsDataClient>,
+    cache_ttl: Duration,
+    cache: RwLock<Option<Arc<Cache>>>,
+    refreshing: tokio::sync::Mutex<()>,
+    /// Time and error of the last failed refresh, cleared by a successful one
+    last_failure: std::sync::Mutex<Option<(Instant, PoolsDataError)>>,
+}
+
+impl ServerState {
+    fn new(client: Arc<PoolsDataClient>, cache_ttl: Duration) -> Self {
+        Self {
+            client,
+            cache_ttl,
+            cache: RwLock::new(None),
+            refreshing: tokio::sync::Mutex::new(()),
+            last_failure: std::sync::Mutex::new(None),
+        }
+    }
+
+    fn last_failure(&self) -> std::sync::MutexGuard<'_, Option<(Instant, PoolsDataError)>> {
+        self.last_failure.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// While a refresh failed less than a TTL ago: the stale cache, or the failure without one
+    fn backing_off(&self) -> Option<Result<Arc<Cache>>> {
+        let failure = self.last_failure();
+        let (failed_at, error) = failure.as_ref().filter(|(failed_at, _)| failed_at.elapsed() < self.cache_ttl)?;
+        log::debug!("Pool refresh failed {:?} ago, not retrying before the TTL", failed_at.elapsed());
+        Some(self.current().ok_or_else(|| error.clone()))
+    }
+
+    fn current(&self) -> Option<Arc<Cache>> {
+        self.cache.read().unwrap_or_else(std::sync::PoisonError::into_inner).clone()
+    }
+
+    fn fresh(&self) -> Option<Arc<Cache>> {
+        self.current().filter(|cache| cache.refreshed_at.elapsed() < self.cache_ttl)
+    }
+
+    /// Cached pools, refreshing them first if older than the TTL
+    ///
+    /// After a failed refresh, requests are served from the stale cache (or get the
+    /// failure) until a TTL has passed instead of refetching every pool each time.
+    async fn pools(&self) -> Result<Arc<Cache>> {
+        if let Some(cache) = self.fresh() {
+            return Ok(cache);
+        }
+        match self.backing_off() {
+            Some(cached) => cached,
+            None => self.refresh(false).await,
+        }
+    }
+
+    /// Fetch every registry pool, one refresh at a time
+    ///
+    /// Concurrent callers wait for the running refresh instead of starting their own
+    /// (unless `force`). Pools that fail keep their previous data; if the whole fetch
+    /// fails, the previous cache is served and the failure is recorded.
+    async fn refresh(&self, force: bool) -> Result<Arc<Cache>> {
+        let _refreshing = self.refreshing.lock().await;
+        if !force {
+            if let Some(cache) = self.fresh() {
+                return Ok(cache);
+            }
+            if let Some(cached) = self.backing_off() {
+                return cached;
+            }
+        }
+
+        let previous = self.current();
+        let names = self.client.registry().names();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let result = match self.client.fetch_pools_debug(&names).await {
+            Ok(result) => result,
+            Err(e) => {
+                log::warn!("Pool refresh failed: {e}");
+                *self.last_failure() = Some((Instant::now(), e.clone()));
+                return previous.ok_or(e);
+            }
+        };
+        *self.last_failure() = None;
+        for (name, error) in &result.failed {
+            log::warn!("Failed to refresh pool {name}: {}", error.error);
+        }
+
+        let mut pools = previous.as_ref().map(|cache| cache.pools.clone()).unwrap_or_default();
+        pools.extend(result.successful.iter().map(|(name, pool)| (name.clone(), pool.into())));
+        let epoch = match self.client.fetch_epoch_info().await {
+            Ok(info) => Some(info.epoch),
+            Err(e) => {
+                log::warn!("Failed to refresh epoch info: {e}");
+                previous.as_ref().and_then(|cache| cache.epoch)
+            }
+        };
+
+        let cache = Arc::new(Cache {
+            pools,
+            epoch,
+            refreshed_at: Instant::now(),
+        });
+        *self.cache.write().unwrap_or_else(std::sync::PoisonError::into_inner) = Some(Arc::clone(&cache));
+        Ok(cache)
+    }
+}
+
+/// HTTP API over one shared client and pool cache
+#[derive(Clone)]
+pub struct PoolsServer {
+    state: Arc<ServerState>,
+    background_refresh: bool,
+}
+
+impl PoolsServer {
+    /// Create a server with a 5 minute cache TTL and background refresh
+    #[must_use]
+    pub fn new(client: Arc<PoolsDataClient>) -> Self {
+        Self {
+            state: Arc::new(ServerState::new(client, DEFAULT_CACHE_TTL)),
+            background_refresh: true,
+        }
+    }
+
+    /// Set how long fetched pools are served before they are refetched
+    ///
+    /// Also the background refresh interval. Clears the cache.
+    #[must_use]
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.state = Arc::new(ServerState::new(Arc::clone(&self.state.client), ttl));
+        self
+    }
+
+    /// Refresh the cache in the background every TTL while serving (default on)
+    ///
+    /// Without it, the first request after the TTL refetches the pools.
+    #[must_use]
+    pub const fn background_refresh(mut self, enabled: bool) -> Self {
+        self.background_refresh = enabled;
+        self
+    }
+
+    /// Refetch all pools now
+    ///
+    /// # Errors
+    /// Returns the fetch error if nothing could be fetched and there is no cached data.
+    pub async fn refresh(&self) -> Result<()> {
+        self.state.refresh(true).await.map(|_| ())
+    }
+
+    /// Refresh the cache every TTL in a background task
+    ///
+    /// With a zero TTL every request refetches, so the task ends at once.
+    #[must_use]
+    pub fn spawn_refresh(&self) -> JoinHandle<()> {
+        let state = Arc::clone(&self.state);
+        tokio::spawn(async move {
+            if state.cache_ttl.is_zero() {
+                log::debug!("Cache TTL is zero, no background refresh");
+                return;
+            }
+            let mut interval = tokio::time::interval(state.cache_ttl);
+            loop {
+                interval.tick().await;
+                if let Err(e) = state.refresh(true).await {
+                    log::warn!("Background pool refresh failed: {e}");
+                }
+            }
+        })
+    }
+
+    /// Router with the API routes, to serve or nest into an application's own axum app
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/pools", get(list_pools))
+            .route("/pools/{name}", get(get_pool))
+            .route("/pools/{name}/validators", get(pool_validators))
+            .route("/pools/{name}/stats", get(pool_stats))
+            .route("/validators/{vote}/pools", get(validator_pools))
+            .with_state(Arc::clone(&self.state))
+    }
+
+    /// Serve the API on an address, with background refresh if enabled
+    ///
+    /// # Errors
+    /// Returns `NetworkError` if the address cannot be bound or the server fails.
+    pub async fn serve(self, address: SocketAddr) -> Result<()> {
+        let listener = tokio::net::TcpListener::bind(address).await.map_err(network_error)?;
+        let refresh = self.background_refresh.then(|| self.spawn_refresh());
+        let served = axum::serve(listener, self.router()).await.map_err(network_error);
+        if let Some(refresh) = refresh {
+            refresh.abort();
+        }
+        served
+    }
+}
+
+/// JSON error response
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn not_found(message: String) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message,
+        }
+    }
+}
+
+impl From<PoolsDataError> for ApiError {
+    fn from(error: PoolsDataError) -> Self {
+        let status = match error {
+            PoolsDataError::PoolNotFound { .. } => StatusCode::NOT_FOUND,
+            PoolsDataError::ConfigurationError { .. } => StatusCode::BAD_REQUEST,
+            _ => StatusCode::BAD_GATEWAY,
+        };
+        Self {
+            status,
+            message: error.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(serde_json::json!({ "error": self.message }))).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<Json<T>, ApiError>;
+
+/// Cached pool by name or registry alias
+fn pool<'a>(state: &ServerState, cache: &'a Cache, name: &str) -> std::result::Result<&'a ProductionPoolData, ApiError> {
+    cache
+        .pools
+        .get(state.client.registry().resolve(name))
+        .ok_or_else(|| ApiError::not_found(format!("Pool '{name}' not found")))
+}
+
+#[derive(Serialize)]
+struct PoolSummary {
+    #[serde(flatten)]
+    info: PoolInfo,
+    statistics: Option<PoolStatistics>,
+    fetched_at: Option<DateTime<Utc>>,
+}
+
+async fn list_pools(State(state): State<Arc<ServerState>>) -> ApiResult<Vec<PoolSummary>> {
+    let cache = state.pools().await?;
+    let summaries = state
+        .client
+        .registry()
+        .pools()
+        .iter()
+        .map(|info| {
+            let pool = cache.pools.get(&info.name);
+            PoolSummary {
+                info: info.clone(),
+                statistics: pool.map(|pool| pool.statistics.clone()),
+                fetched_at: pool.map(|pool| pool.fetched_at),
+            }
+        })
+        .collect();
+    Ok(Json(summaries))
+}
+
+async fn get_pool(State(state): State<Arc<ServerState>>, Path(name): Path<String>) -> ApiResult<ProductionPoolData> {
+    let cache = state.pools().await?;
+    Ok(Json(pool(&state, &cache, &name)?.clone()))
+}
+
+async fn pool_validators(State(state): State<Arc<ServerState>>, Path(name): Path<String>) -> ApiResult<Vec<ValidatorRow>> {
+    let cache = state.pools().await?;
+    let mut rows = ValidatorRow::rows(pool(&state, &cache, &name)?);
+    rows.sort_by(|a, b| b.total_delegated.cmp(&a.total_delegated).then_with(|| a.validator.cmp(&b.validator)));
+    Ok(Json(rows))
+}
+
+#[derive(Deserialize)]
+struct StatsQuery {
+    epoch: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct PoolStatsResponse {
+    pool_name: String,
+    epoch: u64,
+    fetched_at: DateTime<Utc>,
+    validator_count: usize,
+    statistics: PoolStatisticsSummary,
+}
+
+async fn pool_stats(
+    State(state): State<Arc<ServerState>>,
+    Path(name): Path<String>,
+    Query(query): Query<StatsQuery>,
+) -> ApiResult<PoolStatsResponse> {
+    let cache = state.pools().await?;
+    let pool = pool(&state, &cache, &name)?;
+    let epoch = query.epoch.or(cache.epoch).ok_or_else(|| ApiError {
+        status: StatusCode::SERVICE_UNAVAILABLE,
+        message: "Current epoch unknown, pass ?epoch=".to_string(),
+    })?;
+    let statistics = statistics_calc::calculate_pool_statistics_full(pool, epoch)?.summary();
+    Ok(Json(PoolStatsResponse {
+        pool_name: pool.pool_name.clone(),
+        epoch,
+        fetched_at: pool.fetched_at,
+        validator_count: pool.validator_distribution.len(),
+        statistics,
+    }))
+}
+
+async fn validator_pools(State(state): State<Arc<ServerState>>, Path(vote): Path<String>) -> ApiResult<Vec<ValidatorRow>> {
+    let cache = state.pools().await?;
+    let rows = cache
+        .pools
+        .values()
+        .flat_map(ValidatorRow::rows)
+        .filter(|row| row.validator == vote)
+        .collect();
+    Ok(Json(rows))
+}
+
+fn network_error(error: std::io::Error) -> PoolsDataError {
+    PoolsDataError::NetworkError {
+        message: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, mock_client_with, offline_client, offline_client_with};
+    use crate::types::ValidatorStake;
+
+    fn pool(name: &str, validators: &[(&str, u64)]) -> ProductionPoolData {
+        let mut data = test_support::pool(name, Vec::new());
+        for (validator, stake) in validators {
+            let mut entry = ValidatorStake::new();
+            entry.total_delegated = *stake;
+            entry.account_count = 1;
+            data.validator_distribution.insert((*validator).to_string(), entry);
+        }
+        data
+    }
+
+    async fn serve_cached(pools: Vec<ProductionPoolData>) -> String {
+        let mut registry = crate::PoolRegistry::default();
+        registry.add_alias("jitosol", "jito").unwrap();
+        let client = offline_client_with(|builder| builder.registry(registry));
+        let server = PoolsServer::new(Arc::new(client)).cache_ttl(Duration::from_secs(3600));
+        *server.state.cache.write().unwrap() = Some(Arc::new(Cache {
+            pools: pools.into_iter().map(|pool| (pool.pool_name.clone(), pool)).collect(),
+            epoch: Some(700),
+            refreshed_at: Instant::now(),
+        }));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, server.router()).await });
+        format!("http://{address}")
+    }
+
+    #[tokio::test]
+    async fn test_routes() {
+        let base = serve_cached(vec![pool("jito", &[("v1", 10), ("v2", 30)]), pool("marinade", &[("v2", 5)])]).await;
+        let get = |path: &str| reqwest::get(format!("{base}{path}"));
+
+        let pools: serde_json::Value = get("/pools").await.unwrap().json().await.unwrap();
+        let jito = pools.as_array().unwrap().iter().find(|p| p["name"] == "jito").unwrap();
+        assert_eq!(jito["statistics"]["total_accounts"], 0);
+        assert!(pools.as_array().unwrap().iter().any(|p| p["name"] == "lido" && p["statistics"].is_null()));
+
+        let validators: serde_json::Value = get("/pools/jito/validators").await.unwrap().json().await.unwrap();
+        assert_eq!(validators[0]["validator"], "v2");
+        let by_alias: serde_json::Value = get("/pools/jitosol").await.unwrap().json().await.unwrap();
+        assert_eq!(by_alias["pool_name"], "jito");
+
+        let stats: serde_json::Value = get("/pools/jito/stats?epoch=650").await.unwrap().json().await.unwrap();
+        assert_eq!(stats["epoch"], 650);
+        let stats: serde_json::Value = get("/pools/jito/stats").await.unwrap().json().await.unwrap();
+        assert_eq!(stats["epoch"], 700);
+
+        let delegating: serde_json::Value = get("/validators/v2/pools").await.unwrap().json().await.unwrap();
+        assert_eq!(delegating.as_array().unwrap().len(), 2);
+
+        let missing = get("/pools/unknown").await.unwrap();
+        assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+        let body: serde_json::Value = missing.json().await.unwrap();
+        assert!(body["error"].as_str().unwrap().contains("unknown"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_serves_cache() {
+        let server = PoolsServer::new(Arc::new(offline_client())).cache_ttl(Duration::ZERO);
+        assert!(server.refresh().await.is_err());
+
+        *server.state.cache.write().unwrap() = Some(Arc::new(Cache {
+            pools: BTreeMap::from([("jito".to_string(), pool("jito", &[]))]),
+            epoch: None,
+            refreshed_at: Instant::now(),
+        }));
+        // Stale and the RPC is down: the previous data is still served
+        let cache = server.state.pools().await.unwrap();
+        assert!(cache.pools.contains_key("jito"));
+    }
+
+    #[tokio::test]
+    async fn test_zero_ttl_skips_background_refresh() {
+        let server = PoolsServer::new(Arc::new(offline_client())).cache_ttl(Duration::ZERO);
+        // Every request refetches: the task ends instead of ticking a zero-period interval
+        assert!(server.spawn_refresh().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_backs_off() {
+        let transport = Arc::new(crate::MockTransport::new());
+        let client = mock_client_with(Arc::clone(&transport), |builder| builder.retry_attempts(0));
+        let server = PoolsServer::new(Arc::new(client)).cache_ttl(Duration::from_secs(3600));
+
+        // No pool has stake accounts: the refresh fails once, then requests get the failure without refetching
+        assert!(server.state.pools().await.is_err());
+        let fetched = transport.calls("getProgramAccounts");
+        assert!(fetched > 0);
+        for _ in 0..3 {
+            assert!(server.state.pools().await.is_err());
+        }
+        assert_eq!(transport.calls("getProgramAccounts"), fetched);
+    }
+}
@@ -0,0 +1,464 @@
+//! SPL stake pool program state.
+//!
+//! Decodes the on-chain `StakePool` and `ValidatorList` accounts of pools run by
+//! the SPL stake pool program (and Sanctum's deployments of the same program).
+
+use crate::decode::ByteReader;
+use crate::error::{PoolsDataError, Result};
+use serde::{Deserialize, Serialize};
+
+/// SPL stake pool program deployments sharing the same account layout
+pub const SPL_STAKE_POOL_PROGRAM_IDS: &[&str] = &[
+    // SPL stake pool program
+    "SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy",
+    // Sanctum single-validator pools
+    "SP12tWFxD9oJsVWNavTTBZvMbA6gkAmxtVgxdqvyvhY",
+    // Sanctum multi-validator pools
+    "SPMBzsVUuoHA4Jm6KunbsotaahvVikZs1JyTW6iJvbn",
+];
+
+/// `AccountType` discriminant of a `StakePool` account
+pub(crate) const ACCOUNT_TYPE_STAKE_POOL: u8 = 1;
+
+/// `AccountType` discriminant of a `ValidatorList` account
+const ACCOUNT_TYPE_VALIDATOR_LIST: u8 = 2;
+
+/// Offset of `reserve_stake` in a `StakePool` account
+pub(crate) const RESERVE_STAKE_OFFSET: usize = 130;
+
+/// Serialized size of one `ValidatorStakeInfo`
+const VALIDATOR_STAKE_INFO_SIZE: usize = 73;
+
+/// Fee expressed as a fraction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakePoolFee {
+    /// Fee numerator
+    pub numerator: u64,
+    /// Fee denominator
+    pub denominator: u64,
+}
+
+impl StakePoolFee {
+    /// Fee as a ratio (0.0 when the denominator is zero)
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn ratio(&self) -> f64 {
+        if self.denominator == 0 {
+            0.0
+        } else {
+            self.numerator as f64 / self.denominator as f64
+        }
+    }
+}
+
+/// Status of a validator in the pool's validator list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SplValidatorStatus {
+    /// Validator is actively delegated to
+    Active,
+    /// Only the transient stake is deactivating
+    DeactivatingTransient,
+    /// Validator stake is fully removed and can be dropped from the list
+    ReadyForRemoval,
+    /// Only the validator stake account is deactivating
+    DeactivatingValidator,
+    /// Validator and transient stake accounts are deactivating
+    DeactivatingAll,
+}
+
+impl SplValidatorStatus {
+    fn from_u8(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::Active),
+            1 => Ok(Self::DeactivatingTransient),
+            2 => Ok(Self::ReadyForRemoval),
+            3 => Ok(Self::DeactivatingValidator),
+            4 => Ok(Self::DeactivatingAll),
+            _ => Err(PoolsDataError::ParseError {
+                message: format!("Unknown validator stake status: {value}"),
+            }),
+        }
+    }
+}
+
+/// Per-validator entry of an SPL stake pool validator list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplValidatorStakeInfo {
+    /// Validator vote account
+    pub vote_account: String,
+    /// Lamports in the validator stake account
+    pub active_stake_lamports: u64,
+    /// Lamports in the transient stake account
+    pub transient_stake_lamports: u64,
+    /// Last epoch the balances were updated
+    pub last_update_epoch: u64,
+    /// Seed suffix of the transient stake account
+    pub transient_seed_suffix: u64,
+    /// Seed suffix of the validator stake account
+    pub validator_seed_suffix: u32,
+    /// Validator status in the pool
+    pub status: SplValidatorStatus,
+}
+
+/// Decoded SPL stake pool state (`StakePool` + `ValidatorList`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplStakePoolState {
+    /// `StakePool` account address
+    pub address: String,
+    /// Program owning the pool
+    pub program_id: String,
+    /// Manager authority
+    pub manager: String,
+    /// Staker authority
+    pub staker: String,
+    /// Stake deposit authority
+    pub stake_deposit_authority: String,
+    /// Validator list account
+    pub validator_list: String,
+    /// Reserve stake account
+    pub reserve_stake: String,
+    /// Pool token (LST) mint
+    pub pool_mint: String,
+    /// Token account receiving manager fees
+    pub manager_fee_account: String,
+    /// Token program of the pool mint
+    pub token_program_id: String,
+    /// Total lamports managed by the pool, as of `last_update_epoch`
+    pub total_lamports: u64,
+    /// Pool token supply, as of `last_update_epoch`
+    pub pool_token_supply: u64,
+    /// Last epoch the pool balances were updated
+    pub last_update_epoch: u64,
+    /// Fee taken from epoch rewards
+    pub epoch_fee: StakePoolFee,
+    /// Fee on stake account deposits
+    pub stake_deposit_fee: StakePoolFee,
+    /// Fee on stake account withdrawals
+    pub stake_withdrawal_fee: StakePoolFee,
+    /// Percentage of the stake deposit fee paid to referrers
+    pub stake_referral_fee: u8,
+    /// Fee on SOL deposits
+    pub sol_deposit_fee: StakePoolFee,
+    /// Fee on SOL withdrawals
+    pub sol_withdrawal_fee: StakePoolFee,
+    /// Percentage of the SOL deposit fee paid to referrers
+    pub sol_referral_fee: u8,
+    /// Preferred validator vote account for deposits
+    pub preferred_deposit_validator: Option<String>,
+    /// Preferred validator vote account for withdrawals
+    pub preferred_withdraw_validator: Option<String>,
+    /// Pool token supply at the previous epoch update
+    pub last_epoch_pool_token_supply: u64,
+    /// Total lamports at the previous epoch update
+    pub last_epoch_total_lamports: u64,
+    /// Capacity of the validator list
+    pub max_validators: u32,
+    /// Validators in the pool
+    pub validators: Vec<SplValidatorStakeInfo>,
+}
+
+impl SplStakePoolState {
+    /// Decode `StakePool` and `ValidatorList` account data
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::ParseError` if either account is truncated or of the wrong type.
+    pub fn from_account_data(
+        address: &str,
+        program_id: &str,
+        stake_pool_data: &[u8],
+        validator_list_data: &[u8],
+    ) -> Result<Self> {
+        Self::decode_stake_pool(address, program_id, stake_pool_data)?
+            .with_validator_list(validator_list_data)
+    }
+
+    /// Attach the decoded `ValidatorList` account to a decoded `StakePool`
+    pub(crate) fn with_validator_list(mut self, data: &[u8]) -> Result<Self> {
+        let (max_validators, validators) = Self::decode_validator_list(data)?;
+        self.max_validators = max_validators;
+        self.validators = validators;
+        Ok(self)
+    }
+
+    /// Decode a `StakePool` account; validator fields are left empty
+    pub(crate) fn decode_stake_pool(address: &str, program_id: &str, data: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader::new(data);
+        let account_type = reader.read_u8()?;
+        if account_type != ACCOUNT_TYPE_STAKE_POOL {
+            return Err(PoolsDataError::ParseError {
+                message: format!("{address} is not a stake pool account (type {account_type})"),
+            });
+        }
+
+        let manager = reader.read_pubkey()?;
+        let staker = reader.read_pubkey()?;
+        let stake_deposit_authority = reader.read_pubkey()?;
+        let _stake_withdraw_bump_seed = reader.read_u8()?;
+        let validator_list = reader.read_pubkey()?;
+        let reserve_stake = reader.read_pubkey()?;
+        let pool_mint = reader.read_pubkey()?;
+        let manager_fee_account = reader.read_pubkey()?;
+        let token_program_id = reader.read_pubkey()?;
+        let total_lamports = reader.read_u64()?;
+        let pool_token_supply = reader.read_u64()?;
+        let last_update_epoch = reader.read_u64()?;
+        // Pool lockup: unix timestamp, epoch, custodian
+        reader.read_bytes(8 + 8 + 32)?;
+        let epoch_fee = read_fee(&mut reader)?;
+        read_future_fee(&mut reader)?;
+        let preferred_deposit_validator = read_optional_pubkey(&mut reader)?;
+        let preferred_withdraw_validator = read_optional_pubkey(&mut reader)?;
+        let stake_deposit_fee = read_fee(&mut reader)?;
+        let stake_withdrawal_fee = read_fee(&mut reader)?;
+        read_future_fee(&mut reader)?;
+        let stake_referral_fee = reader.read_u8()?;
+        let _sol_deposit_authority = read_optional_pubkey(&mut reader)?;
+        let sol_deposit_fee = read_fee(&mut reader)?;
+        let sol_referral_fee = reader.read_u8()?;
+        let _sol_withdraw_authority = read_optional_pubkey(&mut reader)?;
+        let sol_withdrawal_fee = read_fee(&mut reader)?;
+        read_future_fee(&mut reader)?;
+        let last_epoch_pool_token_supply = reader.read_u64()?;
+        let last_epoch_total_lamports = reader.read_u64()?;
+
+        Ok(Self {
+            address: address.to_string(),
+            program_id: program_id.to_string(),
+            manager,
+            staker,
+            stake_deposit_authority,
+            validator_list,
+            reserve_stake,
+            pool_mint,
+            manager_fee_account,
+            token_program_id,
+            total_lamports,
+            pool_token_supply,
+            last_update_epoch,
+            epoch_fee,
+            stake_deposit_fee,
+            stake_withdrawal_fee,
+            stake_referral_fee,
+            sol_deposit_fee,
+            sol_withdrawal_fee,
+            sol_referral_fee,
+            preferred_deposit_validator,
+            preferred_withdraw_validator,
+            last_epoch_pool_token_supply,
+            last_epoch_total_lamports,
+            max_validators: 0,
+            validators: Vec::new(),
+        })
+    }
+
+    /// Decode a `ValidatorList` account into `(max_validators, validators)`
+    fn decode_validator_list(data: &[u8]) -> Result<(u32, Vec<SplValidatorStakeInfo>)> {
+        let mut reader = ByteReader::new(data);
+        let account_type = reader.read_u8()?;
+        if account_type != ACCOUNT_TYPE_VALIDATOR_LIST {
+            return Err(PoolsDataError::ParseError {
+                message: format!("Not a validator list account (type {account_type})"),
+            });
+        }
+
+        let max_validators = reader.read_u32()?;
+        let len = reader.read_u32()? as usize;
+        if (data.len() - 9) / VALIDATOR_STAKE_INFO_SIZE < len {
+            return Err(PoolsDataError::ParseError {
+                message: format!("Validator list truncated: {len} validators declared"),
+            });
+        }
+
+        let mut validators = Vec::with_capacity(len);
+        for _ in 0..len {
+            let active_stake_lamports = reader.read_u64()?;
+            let transient_stake_lamports = reader.read_u64()?;
+            let last_update_epoch = reader.read_u64()?;
+            let transient_seed_suffix = reader.read_u64()?;
+            let _unused = reader.read_u32()?;
+            let validator_seed_suffix = reader.read_u32()?;
+            let status = SplValidatorStatus::from_u8(reader.read_u8()?)?;
+            let vote_account = reader.read_pubkey()?;
+
+            validators.push(SplValidatorStakeInfo {
+                vote_account,
+                active_stake_lamports,
+                transient_stake_lamports,
+                last_update_epoch,
+                transient_seed_suffix,
+                validator_seed_suffix,
+                status,
+            });
+        }
+
+        Ok((max_validators, validators))
+    }
+
+    /// Lamports per pool token, as of `last_update_epoch`
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn lamports_per_pool_token(&self) -> Option<f64> {
+        (self.pool_token_supply > 0)
+            .then(|| self.total_lamports as f64 / self.pool_token_supply as f64)
+    }
+
+    /// Sum of active stake across the validator list
+    #[must_use]
+    pub fn total_active_stake_lamports(&self) -> u64 {
+        self.validators.iter().map(|v| v.active_stake_lamports).sum()
+    }
+
+    /// Sum of transient stake across the validator list
+    #[must_use]
+    pub fn total_transient_stake_lamports(&self) -> u64 {
+        self.validators.iter().map(|v| v.transient_stake_lamports).sum()
+    }
+
+    /// Find a validator entry by vote account
+    #[must_use]
+    pub fn validator(&self, vote_account: &str) -> Option<&SplValidatorStakeInfo> {
+        self.validators.iter().find(|v| v.vote_account == vote_account)
+    }
+}
+
+fn read_fee(reader: &mut ByteReader<'_>) -> Result<StakePoolFee> {
+    Ok(StakePoolFee {
+        denominator: reader.read_u64()?,
+        numerator: reader.read_u64()?,
+    })
+}
+
+/// `FutureEpoch<Fee>`: `None`, `One(Fee)` or `Two(Fee)`
+fn read_future_fee(reader: &mut ByteReader<'_>) -> Result<Option<StakePoolFee>> {
+    match reader.read_u8()? {
+        0 => Ok(None),
+        1 | 2 => read_fee(reader).map(Some),
+        tag => Err(PoolsDataError::ParseError {
+            message: format!("Invalid future fee tag: {tag}"),
+        }),
+    }
+}
+
+fn read_optional_pubkey(reader: &mut ByteReader<'_>) -> Result<Option<String>> {
+    match reader.read_u8()? {
+        0 => Ok(None),
+        1 => reader.read_pubkey().map(Some),
+        tag => Err(PoolsDataError::ParseError {
+            message: format!("Invalid option tag: {tag}"),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::tests::pubkey_bytes;
+
+    fn fee(data: &mut Vec<u8>, numerator: u64, denominator: u64) {
+        data.extend_from_slice(&denominator.to_le_bytes());
+        data.extend_from_slice(&numerator.to_le_bytes());
+    }
+
+    fn encode_stake_pool() -> Vec<u8> {
+        let mut data = vec![ACCOUNT_TYPE_STAKE_POOL];
+        for seed in 1..=3 {
+            data.extend_from_slice(&pubkey_bytes(seed));
+        }
+        data.push(255);
+        for seed in 4..=8 {
+            data.extend_from_slice(&pubkey_bytes(seed));
+        }
+        assert_eq!(data.len() - 32 * 4, RESERVE_STAKE_OFFSET);
+        data.extend_from_slice(&1_100_000_000_000u64.to_le_bytes());
+        data.extend_from_slice(&1_000_000_000_000u64.to_le_bytes());
+        data.extend_from_slice(&600u64.to_le_bytes());
+        data.extend_from_slice(&[0u8; 48]);
+        fee(&mut data, 4, 100);
+        data.push(1);
+        fee(&mut data, 5, 100);
+        data.push(1);
+        data.extend_from_slice(&pubkey_bytes(9));
+        data.push(0);
+        fee(&mut data, 0, 0);
+        fee(&mut data, 1, 1000);
+        data.push(0);
+        data.push(50);
+        data.push(0);
+        fee(&mut data, 0, 0);
+        data.push(0);
+        data.push(0);
+        fee(&mut data, 3, 1000);
+        data.push(0);
+        data.extend_from_slice(&990_000_000_000u64.to_le_bytes());
+        data.extend_from_slice(&1_080_000_000_000u64.to_le_bytes());
+        data
+    }
+
+    fn encode_validator_list() -> Vec<u8> {
+        let mut data = vec![ACCOUNT_TYPE_VALIDATOR_LIST];
+        data.extend_from_slice(&10u32.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        for (seed, status) in [(20u8, 0u8), (21, 3)] {
+            data.extend_from_slice(&(u64::from(seed) * 1000).to_le_bytes());
+            data.extend_from_slice(&7u64.to_le_bytes());
+            data.extend_from_slice(&600u64.to_le_bytes());
+            data.extend_from_slice(&1u64.to_le_bytes());
+            data.extend_from_slice(&0u32.to_le_bytes());
+            data.extend_from_slice(&0u32.to_le_bytes());
+            data.push(status);
+            data.extend_from_slice(&pubkey_bytes(seed));
+        }
+        // Unused capacity
+        data.resize(data.len() + VALIDATOR_STAKE_INFO_SIZE * 8, 0);
+        data
+    }
+
+    #[test]
+    fn test_decode_stake_pool_state() {
+        let state = SplStakePoolState::from_account_data(
+            "pool",
+            SPL_STAKE_POOL_PROGRAM_IDS[0],
+            &encode_stake_pool(),
+            &encode_validator_list(),
+        )
+        .unwrap();
+
+        assert_eq!(state.manager, bs58::encode(pubkey_bytes(1)).into_string());
+        assert_eq!(state.reserve_stake, bs58::encode(pubkey_bytes(5)).into_string());
+        assert_eq!(state.pool_mint, bs58::encode(pubkey_bytes(6)).into_string());
+        assert_eq!(state.total_lamports, 1_100_000_000_000);
+        assert_eq!(state.pool_token_supply, 1_000_000_000_000);
+        assert_eq!(state.last_update_epoch, 600);
+        assert_eq!(state.epoch_fee, StakePoolFee { numerator: 4, denominator: 100 });
+        assert_eq!(state.preferred_deposit_validator, Some(bs58::encode(pubkey_bytes(9)).into_string()));
+        assert_eq!(state.preferred_withdraw_validator, None);
+        assert_eq!(state.stake_withdrawal_fee.numerator, 1);
+        assert_eq!(state.stake_referral_fee, 50);
+        assert_eq!(state.sol_withdrawal_fee, StakePoolFee { numerator: 3, denominator: 1000 });
+        assert_eq!(state.last_epoch_total_lamports, 1_080_000_000_000);
+        assert!((state.lamports_per_pool_token().unwrap() - 1.1).abs() < 1e-9);
+
+        assert_eq!(state.max_validators, 10);
+        assert_eq!(state.validators.len(), 2);
+        assert_eq!(state.total_active_stake_lamports(), 41_000);
+        assert_eq!(state.total_transient_stake_lamports(), 14);
+        let second = state.validator(&bs58::encode(pubkey_bytes(21)).into_string()).unwrap();
+        assert_eq!(second.status, SplValidatorStatus::DeactivatingValidator);
+    }
+
+    #[test]
+    fn test_decode_invalid_accounts() {
+        let pool = encode_stake_pool();
+        let list = encode_validator_list();
+
+        // Swapped account types
+        assert!(SplStakePoolState::from_account_data("pool", "program", &list, &pool).is_err());
+        // Truncated pool
+        assert!(SplStakePoolState::from_account_data("pool", "program", &pool[..200], &list).is_err());
+        // Validator list declaring more entries than it holds
+        let short_list = &list[..9 + VALIDATOR_STAKE_INFO_SIZE];
+        assert!(SplStakePoolState::from_account_data("pool", "program", &pool, short_list).is_err());
+    }
+}
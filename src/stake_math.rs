@@ -0,0 +1,493 @@
+//! Stake activation math matching the Solana runtime.
+//!
+//! This module reproduces the stake program's warmup/cooldown calculation so that
+//! effective, activating and deactivating lamports can be computed per account for
+//! any epoch covered by the `StakeHistory` sysvar.
+
+use crate::decode::ByteReader;
+use crate::error::{PoolsDataError, Result};
+use crate::types::{ProductionStakeDelegation, StakeDelegation};
+use serde::{Deserialize, Serialize};
+
+/// Address of the `StakeHistory` sysvar account
+pub const STAKE_HISTORY_SYSVAR: &str = "SysvarStakeHistory1111111111111111111111111";
+
+/// Warmup/cooldown rate used before the reduced rate takes effect
+pub const DEFAULT_WARMUP_COOLDOWN_RATE: f64 = 0.25;
+
+/// Warmup/cooldown rate used once the reduced rate feature is active
+pub const NEW_WARMUP_COOLDOWN_RATE: f64 = 0.09;
+
+/// Feature account of `reduce_stake_warmup_cooldown`, which switches to the reduced rate
+pub const REDUCE_STAKE_WARMUP_COOLDOWN_FEATURE: &str = "GwtDQBghCTBgmX2cpEGNPxTEBUTQRaDMGTr5qychdGMj";
+
+/// Size in bytes of one serialized `(epoch, StakeHistoryEntry)` pair
+const STAKE_HISTORY_ENTRY_SIZE: usize = 32;
+
+/// Cluster-wide stake totals for a single epoch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakeHistoryEntry {
+    /// Effective stake at the start of the epoch
+    pub effective: u64,
+    /// Stake that was activating during the epoch
+    pub activating: u64,
+    /// Stake that was deactivating during the epoch
+    pub deactivating: u64,
+}
+
+/// Parsed contents of the `StakeHistory` sysvar
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakeHistory {
+    /// Entries sorted by epoch, newest first (same order as on-chain)
+    entries: Vec<(u64, StakeHistoryEntry)>,
+}
+
+impl StakeHistory {
+    /// Create stake history from `(epoch, entry)` pairs
+    #[must_use]
+    pub fn new(mut entries: Vec<(u64, StakeHistoryEntry)>) -> Self {
+        entries.sort_by_key(|(epoch, _)| std::cmp::Reverse(*epoch));
+        Self { entries }
+    }
+
+    /// Decode the raw sysvar account data
+    ///
+    /// Layout is a bincode `Vec<(u64, StakeHistoryEntry)>`: a little-endian `u64`
+    /// length followed by 32-byte entries (epoch, effective, activating, deactivating).
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::ParseError` if the data is truncated.
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader::new(data);
+        let len = reader.read_u64()?;
+        let len = usize::try_from(len).map_err(|_| PoolsDataError::ParseError {
+            message: format!("Invalid stake history length: {len}"),
+        })?;
+
+        if (data.len() - 8) / STAKE_HISTORY_ENTRY_SIZE < len {
+            return Err(PoolsDataError::ParseError {
+                message: format!(
+                    "Stake history data truncated: {} entries declared, {} bytes available",
+                    len,
+                    data.len() - 8
+                ),
+            });
+        }
+
+        let mut entries = Vec::with_capacity(len);
+        for _ in 0..len {
+            let epoch = reader.read_u64()?;
+            let entry = StakeHistoryEntry {
+                effective: reader.read_u64()?,
+                activating: reader.read_u64()?,
+                deactivating: reader.read_u64()?,
+            };
+            entries.push((epoch, entry));
+        }
+
+        Ok(Self::new(entries))
+    }
+
+    /// Get the entry for an epoch
+    #[must_use]
+    pub fn get(&self, epoch: u64) -> Option<&StakeHistoryEntry> {
+        self.entries
+            .binary_search_by(|(e, _)| epoch.cmp(e))
+            .ok()
+            .map(|index| &self.entries[index].1)
+    }
+
+    /// Most recent epoch covered by the history
+    #[must_use]
+    pub fn latest_epoch(&self) -> Option<u64> {
+        self.entries.first().map(|(epoch, _)| *epoch)
+    }
+
+    /// Number of epochs in the history
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the history has no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Effective, activating and deactivating stake of one delegation at an epoch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakeActivationStatus {
+    /// Stake currently earning rewards (includes deactivating stake)
+    pub effective: u64,
+    /// Stake still warming up
+    pub activating: u64,
+    /// Effective stake that is cooling down
+    pub deactivating: u64,
+}
+
+impl StakeActivationStatus {
+    /// Effective stake that is not cooling down
+    #[must_use]
+    pub const fn active(&self) -> u64 {
+        self.effective.saturating_sub(self.deactivating)
+    }
+
+    /// Portion of `delegated_stake` that is neither effective nor activating
+    #[must_use]
+    pub const fn inactive(&self, delegated_stake: u64) -> u64 {
+        delegated_stake
+            .saturating_sub(self.effective)
+            .saturating_sub(self.activating)
+    }
+}
+
+/// Slot at which a feature was activated, from its feature account data
+///
+/// Layout is a bincode `Feature { activated_at: Option<u64> }`: a one-byte tag
+/// followed, when set, by the little-endian slot.
+///
+/// # Errors
+///
+/// Returns `PoolsDataError::ParseError` if the data is truncated or the tag is invalid.
+pub fn feature_activation_slot(data: &[u8]) -> Result<Option<u64>> {
+    let mut reader = ByteReader::new(data);
+    match reader.read_u8()? {
+        0 => Ok(None),
+        1 => reader.read_u64().map(Some),
+        tag => Err(PoolsDataError::ParseError {
+            message: format!("Invalid feature activation tag: {tag}"),
+        }),
+    }
+}
+
+/// Warmup/cooldown rate in effect at `epoch`
+///
+/// With `new_rate_activation_epoch` unknown the delegation's own `legacy_rate` is used,
+/// which is what the runtime did before the reduced rate feature existed.
+#[must_use]
+pub fn warmup_cooldown_rate(epoch: u64, new_rate_activation_epoch: Option<u64>, legacy_rate: f64) -> f64 {
+    match new_rate_activation_epoch {
+        Some(activation) if epoch >= activation => NEW_WARMUP_COOLDOWN_RATE,
+        Some(_) => DEFAULT_WARMUP_COOLDOWN_RATE,
+        None => legacy_rate,
+    }
+}
+
+impl StakeDelegation {
+    /// Compute the activation status of this delegation at `target_epoch`
+    #[must_use]
+    pub fn activation_status(
+        &self,
+        target_epoch: u64,
+        history: &StakeHistory,
+        new_rate_activation_epoch: Option<u64>,
+    ) -> StakeActivationStatus {
+        stake_activating_and_deactivating(
+            DelegationEpochs {
+                stake: self.stake,
+                activation_epoch: self.activation_epoch,
+                deactivation_epoch: self.deactivation_epoch,
+                legacy_rate: self.warmup_cooldown_rate,
+            },
+            target_epoch,
+            history,
+            new_rate_activation_epoch,
+        )
+    }
+}
+
+impl ProductionStakeDelegation {
+    /// Compute the activation status of this delegation at `target_epoch`
+    ///
+    /// Production data drops `warmup_cooldown_rate`, so the network default is assumed.
+    #[must_use]
+    pub fn activation_status(
+        &self,
+        target_epoch: u64,
+        history: &StakeHistory,
+        new_rate_activation_epoch: Option<u64>,
+    ) -> StakeActivationStatus {
+        stake_activating_and_deactivating(
+            DelegationEpochs {
+                stake: self.stake_lamports,
+                activation_epoch: self.activation_epoch,
+                deactivation_epoch: self.deactivation_epoch,
+                legacy_rate: DEFAULT_WARMUP_COOLDOWN_RATE,
+            },
+            target_epoch,
+            history,
+            new_rate_activation_epoch,
+        )
+    }
+}
+
+/// Delegation fields needed for activation math
+#[derive(Debug, Clone, Copy)]
+struct DelegationEpochs {
+    stake: u64,
+    activation_epoch: u64,
+    deactivation_epoch: u64,
+    legacy_rate: f64,
+}
+
+/// Runtime `Delegation::stake_activating_and_deactivating`
+fn stake_activating_and_deactivating(
+    delegation: DelegationEpochs,
+    target_epoch: u64,
+    history: &StakeHistory,
+    new_rate_activation_epoch: Option<u64>,
+) -> StakeActivationStatus {
+    let (effective_stake, activating_stake) =
+        stake_and_activating(delegation, target_epoch, history, new_rate_activation_epoch);
+
+    if target_epoch < delegation.deactivation_epoch {
+        return StakeActivationStatus {
+            effective: effective_stake,
+            activating: activating_stake,
+            deactivating: 0,
+        };
+    }
+
+    if target_epoch == delegation.deactivation_epoch {
+        return StakeActivationStatus {
+            effective: effective_stake,
+            activating: 0,
+            deactivating: effective_stake,
+        };
+    }
+
+    let Some(mut prev_cluster_stake) = history.get(delegation.deactivation_epoch).copied() else {
+        // No history for the deactivation epoch: the stake is fully cooled down
+        return StakeActivationStatus::default();
+    };
+
+    let mut prev_epoch = delegation.deactivation_epoch;
+    let mut current_effective_stake = effective_stake;
+    loop {
+        let current_epoch = prev_epoch + 1;
+        if prev_cluster_stake.deactivating == 0 {
+            break;
+        }
+
+        #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        // Floating point weighting mirrors the runtime exactly
+        let newly_not_effective_stake = {
+            let weight = current_effective_stake as f64 / prev_cluster_stake.deactivating as f64;
+            let rate = warmup_cooldown_rate(current_epoch, new_rate_activation_epoch, delegation.legacy_rate);
+            let newly_not_effective_cluster_stake = prev_cluster_stake.effective as f64 * rate;
+            ((weight * newly_not_effective_cluster_stake) as u64).max(1)
+        };
+
+        current_effective_stake = current_effective_stake.saturating_sub(newly_not_effective_stake);
+        if current_effective_stake == 0 || current_epoch >= target_epoch {
+            break;
+        }
+
+        match history.get(current_epoch) {
+            Some(entry) => {
+                prev_epoch = current_epoch;
+                prev_cluster_stake = *entry;
+            }
+            None => break,
+        }
+    }
+
+    StakeActivationStatus {
+        effective: current_effective_stake,
+        activating: 0,
+        deactivating: current_effective_stake,
+    }
+}
+
+/// Runtime `Delegation::stake_and_activating`, returns `(effective, activating)`
+fn stake_and_activating(
+    delegation: DelegationEpochs,
+    target_epoch: u64,
+    history: &StakeHistory,
+    new_rate_activation_epoch: Option<u64>,
+) -> (u64, u64) {
+    let delegated_stake = delegation.stake;
+
+    if delegation.activation_epoch == u64::MAX {
+        // Bootstrap stake is fully effective from genesis
+        return (delegated_stake, 0);
+    }
+    if delegation.activation_epoch == delegation.deactivation_epoch {
+        // Deactivated in the same epoch it was delegated: never became effective
+        return (0, 0);
+    }
+    if target_epoch == delegation.activation_epoch {
+        return (0, delegated_stake);
+    }
+    if target_epoch < delegation.activation_epoch {
+        return (0, 0);
+    }
+
+    let Some(mut prev_cluster_stake) = history.get(delegation.activation_epoch).copied() else {
+        // No history for the activation epoch: the stake is fully warmed up
+        return (delegated_stake, 0);
+    };
+
+    let mut prev_epoch = delegation.activation_epoch;
+    let mut current_effective_stake = 0;
+    loop {
+        let current_epoch = prev_epoch + 1;
+        if prev_cluster_stake.activating == 0 {
+            break;
+        }
+
+        #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        // Floating point weighting mirrors the runtime exactly
+        let newly_effective_stake = {
+            let remaining_activating_stake = delegated_stake - current_effective_stake;
+            let weight = remaining_activating_stake as f64 / prev_cluster_stake.activating as f64;
+            let rate = warmup_cooldown_rate(current_epoch, new_rate_activation_epoch, delegation.legacy_rate);
+            let newly_effective_cluster_stake = prev_cluster_stake.effective as f64 * rate;
+            ((weight * newly_effective_cluster_stake) as u64).max(1)
+        };
+
+        current_effective_stake += newly_effective_stake;
+        if current_effective_stake >= delegated_stake {
+            current_effective_stake = delegated_stake;
+            break;
+        }
+
+        if current_epoch >= target_epoch || current_epoch >= delegation.deactivation_epoch {
+            break;
+        }
+
+        match history.get(current_epoch) {
+            Some(entry) => {
+                prev_epoch = current_epoch;
+                prev_cluster_stake = *entry;
+            }
+            None => break,
+        }
+    }
+
+    (current_effective_stake, delegated_stake - current_effective_stake)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delegation(stake: u64, activation_epoch: u64, deactivation_epoch: u64) -> StakeDelegation {
+        StakeDelegation {
+            voter: "validator1".to_string(),
+            stake,
+            activation_epoch,
+            deactivation_epoch,
+            last_epoch_credits_cumulative: 0,
+            warmup_cooldown_rate: DEFAULT_WARMUP_COOLDOWN_RATE,
+        }
+    }
+
+    fn entry(effective: u64, activating: u64, deactivating: u64) -> StakeHistoryEntry {
+        StakeHistoryEntry {
+            effective,
+            activating,
+            deactivating,
+        }
+    }
+
+    #[test]
+    fn test_stake_history_from_account_data() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&2u64.to_le_bytes());
+        for (epoch, values) in [(11u64, [5u64, 6, 7]), (10, [1, 2, 3])] {
+            data.extend_from_slice(&epoch.to_le_bytes());
+            for value in values {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        // Sysvar accounts are allocated larger than their contents
+        data.extend_from_slice(&[0u8; 64]);
+
+        let history = StakeHistory::from_account_data(&data).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.latest_epoch(), Some(11));
+        assert_eq!(history.get(10), Some(&entry(1, 2, 3)));
+        assert_eq!(history.get(11), Some(&entry(5, 6, 7)));
+        assert!(history.get(12).is_none());
+
+        assert!(StakeHistory::from_account_data(&data[..40]).is_err());
+    }
+
+    #[test]
+    fn test_partial_warmup() {
+        let history = StakeHistory::new(vec![(10, entry(1000, 1000, 0)), (11, entry(1250, 750, 0))]);
+        let stake = delegation(1000, 10, u64::MAX);
+
+        let at_activation = stake.activation_status(10, &history, None);
+        assert_eq!(at_activation, StakeActivationStatus { effective: 0, activating: 1000, deactivating: 0 });
+
+        // 25% of cluster effective stake (250) becomes effective, all of it ours
+        let one_epoch = stake.activation_status(11, &history, None);
+        assert_eq!(one_epoch, StakeActivationStatus { effective: 250, activating: 750, deactivating: 0 });
+
+        // 25% of 1250 = 312 more
+        let two_epochs = stake.activation_status(12, &history, None);
+        assert_eq!(two_epochs, StakeActivationStatus { effective: 562, activating: 438, deactivating: 0 });
+
+        // No history beyond epoch 11: warmup stops advancing after the last known entry
+        assert_eq!(stake.activation_status(13, &history, None), two_epochs);
+    }
+
+    #[test]
+    fn test_partial_cooldown() {
+        let history = StakeHistory::new(vec![(20, entry(2000, 0, 1000)), (21, entry(2000, 0, 500))]);
+        let stake = delegation(1000, u64::MAX, 20);
+
+        let at_deactivation = stake.activation_status(20, &history, None);
+        assert_eq!(at_deactivation, StakeActivationStatus { effective: 1000, activating: 0, deactivating: 1000 });
+
+        let one_epoch = stake.activation_status(21, &history, None);
+        assert_eq!(one_epoch, StakeActivationStatus { effective: 500, activating: 0, deactivating: 500 });
+        assert_eq!(one_epoch.active(), 0);
+        assert_eq!(one_epoch.inactive(1000), 500);
+
+        let done = stake.activation_status(22, &history, None);
+        assert_eq!(done, StakeActivationStatus::default());
+    }
+
+    #[test]
+    fn test_new_warmup_rate() {
+        let history = StakeHistory::new(vec![(10, entry(1000, 1000, 0))]);
+        let stake = delegation(1000, 10, u64::MAX);
+
+        // Reduced rate active from epoch 11: 9% of 1000 becomes effective
+        let status = stake.activation_status(11, &history, Some(11));
+        assert_eq!(status, StakeActivationStatus { effective: 90, activating: 910, deactivating: 0 });
+    }
+
+    #[test]
+    fn test_feature_activation_slot() {
+        let mut data = vec![1];
+        data.extend_from_slice(&123_456u64.to_le_bytes());
+        assert_eq!(feature_activation_slot(&data).unwrap(), Some(123_456));
+        assert_eq!(feature_activation_slot(&[0; 9]).unwrap(), None);
+        assert!(feature_activation_slot(&data[..4]).is_err());
+        assert!(feature_activation_slot(&[2; 9]).is_err());
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        let history = StakeHistory::default();
+
+        // Delegated and deactivated in the same epoch
+        let waste = delegation(1000, 5, 5);
+        assert_eq!(waste.activation_status(6, &history, None), StakeActivationStatus::default());
+
+        // Future activation
+        let future = delegation(1000, 50, u64::MAX);
+        assert_eq!(future.activation_status(40, &history, None), StakeActivationStatus::default());
+
+        // Old stake outside the history window is fully effective
+        let old = delegation(1000, 1, u64::MAX);
+        assert_eq!(old.activation_status(500, &history, None).effective, 1000);
+    }
+}
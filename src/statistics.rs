@@ -1,4 +1,4 @@
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, serde::Serialize)]
 pub struct PoolStatisticsSummary {
     pub total_accounts: usize,
     pub activating_accounts: usize,
@@ -107,3 +107,32 @@ pub fn classify_stake_state(
         }
     })
 }
+
+// Helper: classify canonical state using runtime warmup/cooldown math
+// Partially warmed up stake is Activating, partially cooled down stake is Deactivating
+#[must_use]
+pub fn classify_stake_state_with_history(
+    delegation: Option<&crate::types::ProductionStakeDelegation>,
+    current_epoch: u64,
+    stake_history: &crate::stake_math::StakeHistory,
+    new_rate_activation_epoch: Option<u64>,
+) -> StakeState {
+    delegation.map_or(StakeState::Inactive, |d| {
+        if d.activation_epoch == d.deactivation_epoch {
+            return StakeState::Waste;
+        }
+        if d.activation_epoch != u64::MAX && d.activation_epoch > d.deactivation_epoch {
+            return StakeState::Unknown;
+        }
+        let status = d.activation_status(current_epoch, stake_history, new_rate_activation_epoch);
+        if status.activating > 0 {
+            StakeState::Activating
+        } else if status.deactivating > 0 {
+            StakeState::Deactivating
+        } else if status.effective > 0 {
+            StakeState::Active
+        } else {
+            StakeState::Inactive
+        }
+    })
+}
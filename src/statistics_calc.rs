@@ -1,8 +1,9 @@
 // Calculation logic for PoolStatisticsFull, ValidatorStatisticsFull, AccountStatisticsFull
 // Uses canonical state classification and current_epoch
 
-use crate::statistics::{AccountStatisticsFull, ValidatorStatisticsFull, PoolStatisticsFull, classify_stake_state};
-use crate::types::ProductionPoolData;
+use crate::statistics::{AccountStatisticsFull, ValidatorStatisticsFull, PoolStatisticsFull, StakeState, classify_stake_state, classify_stake_state_with_history};
+use crate::stake_math::StakeHistory;
+use crate::types::{ProductionPoolData, ProductionStakeDelegation};
 use crate::error::PoolsDataError;
 
 /// Calculate canonical pool statistics, grouping by validator and account state
@@ -11,6 +12,28 @@ use crate::error::PoolsDataError;
 /// # Errors
 /// Returns `PoolsDataError::ConfigurationError` if pool name or authority is empty.
 pub fn calculate_pool_statistics_full(pool: &ProductionPoolData, current_epoch: u64) -> Result<PoolStatisticsFull, PoolsDataError> {
+    calculate_with_classifier(pool, |delegation| classify_stake_state(delegation, current_epoch))
+}
+
+/// Calculate canonical pool statistics, classifying accounts with runtime warmup/cooldown math
+///
+/// # Errors
+/// Returns `PoolsDataError::ConfigurationError` if pool name or authority is empty.
+pub fn calculate_pool_statistics_full_with_history(
+    pool: &ProductionPoolData,
+    current_epoch: u64,
+    stake_history: &StakeHistory,
+    new_rate_activation_epoch: Option<u64>,
+) -> Result<PoolStatisticsFull, PoolsDataError> {
+    calculate_with_classifier(pool, |delegation| {
+        classify_stake_state_with_history(delegation, current_epoch, stake_history, new_rate_activation_epoch)
+    })
+}
+
+fn calculate_with_classifier<F>(pool: &ProductionPoolData, classify: F) -> Result<PoolStatisticsFull, PoolsDataError>
+where
+    F: Fn(Option<&ProductionStakeDelegation>) -> StakeState,
+{
     if pool.pool_name.trim().is_empty() {
         return Err(PoolsDataError::ConfigurationError { message: "Pool name is empty".to_string() });
     }
@@ -21,7 +44,7 @@ pub fn calculate_pool_statistics_full(pool: &ProductionPoolData, current_epoch:
     let mut validator_map: std::collections::HashMap<String, (Vec<AccountStatisticsFull>, Option<u64>)> = std::collections::HashMap::new();
     for account in &pool.stake_accounts {
         let delegation = account.delegation.as_ref();
-        let state = classify_stake_state(delegation, current_epoch);
+        let state = classify(delegation);
         let validator_pubkey = delegation.map_or_else(String::new, |d| d.validator.clone());
         let credits = delegation.map(|d| d.last_epoch_credits_cumulative);
         let account_stats = AccountStatisticsFull {
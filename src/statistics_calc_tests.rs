@@ -14,6 +14,11 @@ mod tests {
             validator_distribution: HashMap::new(),
             statistics: PoolStatistics::default(),
             fetched_at: chrono::Utc::now(),
+            epoch: None,
+            served_by: Vec::new(),
+            spl_stake_pool: None,
+            marinade_state: None,
+            exchange_rate: None,
         };
         let result = crate::statistics_calc::calculate_pool_statistics_full(&pool, 1);
         assert!(matches!(result, Err(crate::error::PoolsDataError::ConfigurationError { .. })), "Expected ConfigurationError for empty pool name");
@@ -31,6 +36,11 @@ mod tests {
             validator_distribution: HashMap::new(),
             statistics: PoolStatistics::default(),
             fetched_at: chrono::Utc::now(),
+            epoch: None,
+            served_by: Vec::new(),
+            spl_stake_pool: None,
+            marinade_state: None,
+            exchange_rate: None,
         };
         let result = crate::statistics_calc::calculate_pool_statistics_full(&pool, 1);
         assert!(matches!(result, Err(crate::error::PoolsDataError::ConfigurationError { .. })), "Expected ConfigurationError for empty authority");
@@ -50,6 +60,11 @@ mod tests {
             validator_distribution: HashMap::new(),
             statistics: PoolStatistics::default(),
             fetched_at: Utc::now(),
+            epoch: None,
+            served_by: Vec::new(),
+            spl_stake_pool: None,
+            marinade_state: None,
+            exchange_rate: None,
         };
             let stats = calculate_pool_statistics_full(&pool, 123).unwrap();
             assert_eq!(stats.summary().total_accounts, 0);
@@ -82,6 +97,7 @@ mod tests {
                 epoch: 0,
                 unix_timestamp: 0,
             },
+            matched_authority: crate::pools::AuthorityRole::Staker,
         };
         let pool = ProductionPoolData {
             pool_name: "testpool".to_string(),
@@ -90,6 +106,11 @@ mod tests {
             validator_distribution: HashMap::new(),
             statistics: PoolStatistics::default(),
             fetched_at: Utc::now(),
+            epoch: None,
+            served_by: Vec::new(),
+            spl_stake_pool: None,
+            marinade_state: None,
+            exchange_rate: None,
         };
             let stats = calculate_pool_statistics_full(&pool, 1).unwrap();
             let summary = stats.summary();
@@ -0,0 +1,766 @@
+//! SQLite persistence of fetched pool snapshots (`sqlite` feature).
+//!
+//! Each saved snapshot is a set of `ProductionPoolData` keyed by fetch time and epoch,
+//! stored in a normalized schema:
+//!
+//! - `snapshots`: one row per save (`fetched_at`, `epoch`)
+//! - `pools`: one row per pool in a snapshot, with the epoch of its own fetch
+//! - `stake_accounts`: one row per stake account, in fetch order
+//! - `validator_distribution` / `validator_accounts`: per-validator totals and their accounts
+//! - `statistics`: one row of `PoolStatistics` per pool
+//! - `exchange_rates`: the pool's SOL/LST exchange rate, if measured
+//!
+//! On-chain pool state (`spl_stake_pool`, `marinade_state`) and `served_by` are kept
+//! as JSON columns of `pools`: they are nested or lists, read back whole and never queried.
+//!
+//! Epoch columns are `NULL` where the library uses `u64::MAX` (e.g. not deactivating).
+//! The schema version is kept in `PRAGMA user_version` and migrated on open.
+//! `SnapshotStore` is synchronous; use `tokio::task::spawn_blocking` from async code.
+
+use crate::error::{PoolsDataError, Result};
+use crate::exchange_rate::{ExchangeRate, LamportsSource, TokenSupply};
+use crate::pools::AuthorityRole;
+use crate::types::{
+    PoolStatistics, ProductionPoolData, ProductionStakeAccountInfo, ProductionStakeAuthority, ProductionStakeDelegation,
+    ProductionStakeLockup, ValidatorStake,
+};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Schema migrations, applied in order; index + 1 is the schema version
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "
+    CREATE TABLE snapshots (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        fetched_at TEXT NOT NULL,
+        epoch INTEGER
+    );
+    CREATE INDEX idx_snapshots_fetched_at ON snapshots (fetched_at);
+    CREATE INDEX idx_snapshots_epoch ON snapshots (epoch);
+
+    CREATE TABLE pools (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        snapshot_id INTEGER NOT NULL REFERENCES snapshots (id) ON DELETE CASCADE,
+        pool_name TEXT NOT NULL,
+        authority TEXT NOT NULL,
+        fetched_at TEXT NOT NULL,
+        served_by TEXT NOT NULL DEFAULT '[]',
+        spl_stake_pool TEXT,
+        marinade_state TEXT,
+        exchange_rate TEXT,
+        UNIQUE (snapshot_id, pool_name)
+    );
+    CREATE INDEX idx_pools_pool_name ON pools (pool_name);
+
+    CREATE TABLE stake_accounts (
+        pool_id INTEGER NOT NULL REFERENCES pools (id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        pubkey TEXT NOT NULL,
+        lamports INTEGER NOT NULL,
+        stake_type TEXT NOT NULL,
+        validator TEXT,
+        stake_lamports INTEGER,
+        activation_epoch INTEGER,
+        deactivation_epoch INTEGER,
+        last_epoch_credits_cumulative INTEGER,
+        staker TEXT NOT NULL,
+        withdrawer TEXT NOT NULL,
+        custodian TEXT NOT NULL,
+        lockup_epoch INTEGER,
+        lockup_unix_timestamp INTEGER NOT NULL,
+        matched_authority TEXT NOT NULL,
+        PRIMARY KEY (pool_id, position)
+    );
+    CREATE INDEX idx_stake_accounts_pubkey ON stake_accounts (pubkey);
+    CREATE INDEX idx_stake_accounts_validator ON stake_accounts (validator);
+
+    CREATE TABLE validator_distribution (
+        pool_id INTEGER NOT NULL REFERENCES pools (id) ON DELETE CASCADE,
+        validator TEXT NOT NULL,
+        total_delegated INTEGER NOT NULL,
+        account_count INTEGER NOT NULL,
+        PRIMARY KEY (pool_id, validator)
+    );
+
+    CREATE TABLE validator_accounts (
+        pool_id INTEGER NOT NULL REFERENCES pools (id) ON DELETE CASCADE,
+        validator TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        pubkey TEXT NOT NULL,
+        PRIMARY KEY (pool_id, validator, position)
+    );
+
+    CREATE TABLE statistics (
+        pool_id INTEGER PRIMARY KEY REFERENCES pools (id) ON DELETE CASCADE,
+        total_accounts INTEGER NOT NULL,
+        activating_accounts INTEGER NOT NULL,
+        active_accounts INTEGER NOT NULL,
+        deactivating_accounts INTEGER NOT NULL,
+        deactivated_accounts INTEGER NOT NULL,
+        total_lamports INTEGER NOT NULL,
+        activating_stake_lamports INTEGER NOT NULL,
+        active_stake_lamports INTEGER NOT NULL,
+        deactivating_stake_lamports INTEGER NOT NULL,
+        deactivated_stake_lamports INTEGER NOT NULL,
+        validator_count INTEGER NOT NULL
+    );
+    ",
+    // 2: epoch of each pool's own fetch
+    "ALTER TABLE pools ADD COLUMN epoch INTEGER;",
+    // 3: exchange rates in columns instead of JSON
+    "
+    CREATE TABLE exchange_rates (
+        pool_id INTEGER PRIMARY KEY REFERENCES pools (id) ON DELETE CASCADE,
+        lst_mint TEXT NOT NULL,
+        total_lamports INTEGER NOT NULL,
+        lamports_source TEXT NOT NULL,
+        token_supply INTEGER NOT NULL,
+        token_decimals INTEGER NOT NULL,
+        rate REAL NOT NULL,
+        epoch INTEGER,
+        fetched_at TEXT NOT NULL
+    );
+    INSERT INTO exchange_rates
+        SELECT id,
+               json_extract(exchange_rate, '$.lst_mint'),
+               json_extract(exchange_rate, '$.total_lamports'),
+               json_extract(exchange_rate, '$.lamports_source'),
+               json_extract(exchange_rate, '$.token_supply.amount'),
+               json_extract(exchange_rate, '$.token_supply.decimals'),
+               json_extract(exchange_rate, '$.rate'),
+               json_extract(exchange_rate, '$.epoch'),
+               json_extract(exchange_rate, '$.fetched_at')
+        FROM pools WHERE exchange_rate IS NOT NULL;
+    ALTER TABLE pools DROP COLUMN exchange_rate;
+    ",
+];
+
+/// A saved snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    /// Snapshot id
+    pub id: i64,
+    /// Earliest fetch time of the pools in the snapshot
+    pub fetched_at: DateTime<Utc>,
+    /// Epoch the snapshot was taken in, if known
+    pub epoch: Option<u64>,
+    /// Number of pools in the snapshot
+    pub pool_count: usize,
+}
+
+/// SQLite store of pool snapshots
+pub struct SnapshotStore {
+    conn: Connection,
+}
+
+impl SnapshotStore {
+    /// Open (or create) a store at `path`, applying pending migrations
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::StorageError` if the database cannot be opened or migrated.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Open a temporary in-memory store
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::StorageError` if the database cannot be created.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        let mut store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// Apply migrations newer than the database's schema version
+    fn migrate(&mut self) -> Result<()> {
+        let version = self.schema_version()?;
+        if version > MIGRATIONS.len() {
+            return Err(PoolsDataError::StorageError {
+                message: format!("Database schema version {version} is newer than supported ({})", MIGRATIONS.len()),
+            });
+        }
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = self.conn.transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", index + 1)?;
+            tx.commit()?;
+            log::info!("Applied snapshot store migration {}", index + 1);
+        }
+        Ok(())
+    }
+
+    /// Current schema version
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::StorageError` if the version cannot be read.
+    pub fn schema_version(&self) -> Result<usize> {
+        Ok(self.conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+    }
+
+    /// Save pools as one snapshot, returning its id
+    ///
+    /// The snapshot's `fetched_at` is the earliest pool `fetched_at` (now if `pools` is empty).
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::StorageError` if the write fails; nothing is saved in that case.
+    pub fn save_snapshot<'a>(&mut self, pools: impl IntoIterator<Item = &'a ProductionPoolData>, epoch: Option<u64>) -> Result<i64> {
+        let pools: Vec<&ProductionPoolData> = pools.into_iter().collect();
+        let fetched_at = pools.iter().map(|pool| pool.fetched_at).min().unwrap_or_else(Utc::now);
+
+        let tx = self.conn.transaction()?;
+        tx.execute("INSERT INTO snapshots (fetched_at, epoch) VALUES (?1, ?2)", params![fetched_at, epoch])?;
+        let snapshot_id = tx.last_insert_rowid();
+
+        for pool in pools {
+            insert_pool(&tx, snapshot_id, pool)?;
+        }
+
+        tx.commit()?;
+        Ok(snapshot_id)
+    }
+
+    /// All snapshots, oldest first
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::StorageError` if the query fails.
+    pub fn snapshots(&self) -> Result<Vec<SnapshotInfo>> {
+        let mut stmt = self.conn.prepare(&format!("{SNAPSHOT_SELECT} GROUP BY s.id ORDER BY s.fetched_at, s.id"))?;
+        let rows = stmt.query_map([], snapshot_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Most recent snapshot
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::StorageError` if the query fails.
+    pub fn latest_snapshot(&self) -> Result<Option<SnapshotInfo>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("{SNAPSHOT_SELECT} GROUP BY s.id ORDER BY s.fetched_at DESC, s.id DESC LIMIT 1"),
+                [],
+                snapshot_from_row,
+            )
+            .optional()?)
+    }
+
+    /// Snapshots taken in an epoch, oldest first
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::StorageError` if the query fails.
+    pub fn snapshots_in_epoch(&self, epoch: u64) -> Result<Vec<SnapshotInfo>> {
+        let mut stmt =
+            self.conn.prepare(&format!("{SNAPSHOT_SELECT} WHERE s.epoch = ?1 GROUP BY s.id ORDER BY s.fetched_at, s.id"))?;
+        let rows = stmt.query_map([epoch], snapshot_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Load one pool from a snapshot
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::StorageError` if the query fails or stored data is malformed.
+    pub fn load_pool(&self, snapshot_id: i64, pool_name: &str) -> Result<Option<ProductionPoolData>> {
+        let pool_id: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT id FROM pools WHERE snapshot_id = ?1 AND pool_name = ?2",
+                params![snapshot_id, pool_name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        pool_id.map(|id| self.load_pool_by_id(id)).transpose()
+    }
+
+    /// Load a pool from the most recent snapshot containing it
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::StorageError` if the query fails or stored data is malformed.
+    pub fn load_latest_pool(&self, pool_name: &str) -> Result<Option<ProductionPoolData>> {
+        let pool_id: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT p.id FROM pools p JOIN snapshots s ON s.id = p.snapshot_id
+                 WHERE p.pool_name = ?1 ORDER BY s.fetched_at DESC, s.id DESC LIMIT 1",
+                [pool_name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        pool_id.map(|id| self.load_pool_by_id(id)).transpose()
+    }
+
+    /// Load every pool of a snapshot, keyed by pool name
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::StorageError` if the query fails or stored data is malformed.
+    pub fn load_snapshot(&self, snapshot_id: i64) -> Result<HashMap<String, ProductionPoolData>> {
+        let mut stmt = self.conn.prepare("SELECT id FROM pools WHERE snapshot_id = ?1")?;
+        let pool_ids = stmt.query_map([snapshot_id], |row| row.get::<_, i64>(0))?.collect::<rusqlite::Result<Vec<_>>>()?;
+
+        pool_ids
+            .into_iter()
+            .map(|id| self.load_pool_by_id(id).map(|pool| (pool.pool_name.clone(), pool)))
+            .collect()
+    }
+
+    /// Statistics of a pool in every snapshot containing it, oldest first
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::StorageError` if the query fails.
+    pub fn pool_statistics_history(&self, pool_name: &str) -> Result<Vec<(SnapshotInfo, PoolStatistics)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.fetched_at, s.epoch, (SELECT COUNT(*) FROM pools c WHERE c.snapshot_id = s.id), st.*
+             FROM snapshots s
+             JOIN pools p ON p.snapshot_id = s.id
+             JOIN statistics st ON st.pool_id = p.id
+             WHERE p.pool_name = ?1
+             ORDER BY s.fetched_at, s.id",
+        )?;
+        let rows = stmt.query_map([pool_name], |row| Ok((snapshot_from_row(row)?, statistics_from_row(row, 5)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Delete a snapshot and all its rows, returning whether it existed
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::StorageError` if the delete fails.
+    pub fn delete_snapshot(&mut self, snapshot_id: i64) -> Result<bool> {
+        Ok(self.conn.execute("DELETE FROM snapshots WHERE id = ?1", [snapshot_id])? > 0)
+    }
+
+    fn load_pool_by_id(&self, pool_id: i64) -> Result<ProductionPoolData> {
+        let pool = self.conn.query_row(
+            "SELECT pool_name, authority, fetched_at, epoch, served_by, spl_stake_pool, marinade_state
+             FROM pools WHERE id = ?1",
+            [pool_id],
+            |row| {
+                Ok(PoolRow {
+                    pool_name: row.get(0)?,
+                    authority: row.get(1)?,
+                    fetched_at: row.get(2)?,
+                    epoch: row.get(3)?,
+                    served_by: row.get(4)?,
+                    spl_stake_pool: row.get(5)?,
+                    marinade_state: row.get(6)?,
+                })
+            },
+        )?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT pubkey, lamports, stake_type, validator, stake_lamports, activation_epoch, deactivation_epoch,
+                    last_epoch_credits_cumulative, staker, withdrawer, custodian, lockup_epoch, lockup_unix_timestamp,
+                    matched_authority
+             FROM stake_accounts WHERE pool_id = ?1 ORDER BY position",
+        )?;
+        let stake_accounts = stmt.query_map([pool_id], stake_account_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut validator_distribution: HashMap<String, ValidatorStake> = HashMap::new();
+        let mut stmt = self
+            .conn
+            .prepare("SELECT validator, total_delegated, account_count FROM validator_distribution WHERE pool_id = ?1")?;
+        let rows = stmt.query_map([pool_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                ValidatorStake {
+                    total_delegated: row.get(1)?,
+                    account_count: row.get(2)?,
+                    accounts: Vec::new(),
+                },
+            ))
+        })?;
+        for row in rows {
+            let (validator, stake) = row?;
+            validator_distribution.insert(validator, stake);
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT validator, pubkey FROM validator_accounts WHERE pool_id = ?1 ORDER BY validator, position")?;
+        let rows = stmt.query_map([pool_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (validator, pubkey) = row?;
+            if let Some(stake) = validator_distribution.get_mut(&validator) {
+                stake.accounts.push(pubkey);
+            }
+        }
+
+        let statistics = self
+            .conn
+            .query_row("SELECT * FROM statistics WHERE pool_id = ?1", [pool_id], |row| statistics_from_row(row, 1))
+            .optional()?
+            .unwrap_or_default();
+
+        let exchange_rate = self
+            .conn
+            .query_row(
+                "SELECT lst_mint, total_lamports, lamports_source, token_supply, token_decimals, rate, epoch, fetched_at
+                 FROM exchange_rates WHERE pool_id = ?1",
+                [pool_id],
+                |row| {
+                    let lamports_source: String = row.get(2)?;
+                    Ok(ExchangeRate {
+                        pool_name: pool.pool_name.clone(),
+                        lst_mint: row.get(0)?,
+                        total_lamports: row.get(1)?,
+                        lamports_source: lamports_source_from_sql(&lamports_source),
+                        token_supply: TokenSupply {
+                            amount: row.get(3)?,
+                            decimals: row.get(4)?,
+                        },
+                        rate: row.get(5)?,
+                        epoch: row.get(6)?,
+                        fetched_at: row.get(7)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(ProductionPoolData {
+            pool_name: pool.pool_name,
+            authority: pool.authority,
+            stake_accounts,
+            validator_distribution,
+            statistics,
+            fetched_at: pool.fetched_at,
+            epoch: pool.epoch,
+            served_by: serde_json::from_str(&pool.served_by)?,
+            spl_stake_pool: pool.spl_stake_pool.as_deref().map(serde_json::from_str).transpose()?,
+            marinade_state: pool.marinade_state.as_deref().map(serde_json::from_str).transpose()?,
+            exchange_rate,
+        })
+    }
+}
+
+/// Row of the `pools` table (JSON columns still encoded)
+struct PoolRow {
+    pool_name: String,
+    authority: String,
+    fetched_at: DateTime<Utc>,
+    epoch: Option<u64>,
+    served_by: String,
+    spl_stake_pool: Option<String>,
+    marinade_state: Option<String>,
+}
+
+/// Snapshot columns with pool count, followed by a `WHERE`/`GROUP BY` clause
+const SNAPSHOT_SELECT: &str =
+    "SELECT s.id, s.fetched_at, s.epoch, COUNT(p.id) FROM snapshots s LEFT JOIN pools p ON p.snapshot_id = s.id";
+
+fn snapshot_from_row(row: &Row<'_>) -> rusqlite::Result<SnapshotInfo> {
+    Ok(SnapshotInfo {
+        id: row.get(0)?,
+        fetched_at: row.get(1)?,
+        epoch: row.get(2)?,
+        pool_count: row.get(3)?,
+    })
+}
+
+/// Read `PoolStatistics` from the `statistics` columns starting after `pool_id` at `offset`
+fn statistics_from_row(row: &Row<'_>, offset: usize) -> rusqlite::Result<PoolStatistics> {
+    Ok(PoolStatistics {
+        total_accounts: row.get(offset)?,
+        activating_accounts: row.get(offset + 1)?,
+        active_accounts: row.get(offset + 2)?,
+        deactivating_accounts: row.get(offset + 3)?,
+        deactivated_accounts: row.get(offset + 4)?,
+        total_lamports: row.get(offset + 5)?,
+        activating_stake_lamports: row.get(offset + 6)?,
+        active_stake_lamports: row.get(offset + 7)?,
+        deactivating_stake_lamports: row.get(offset + 8)?,
+        deactivated_stake_lamports: row.get(offset + 9)?,
+        validator_count: row.get(offset + 10)?,
+    })
+}
+
+fn stake_account_from_row(row: &Row<'_>) -> rusqlite::Result<ProductionStakeAccountInfo> {
+    let validator: Option<String> = row.get(3)?;
+    let delegation = match validator {
+        Some(validator) => Some(ProductionStakeDelegation {
+            validator,
+            stake_lamports: row.get(4)?,
+            activation_epoch: epoch_from_sql(row.get(5)?),
+            deactivation_epoch: epoch_from_sql(row.get(6)?),
+            last_epoch_credits_cumulative: row.get(7)?,
+        }),
+        None => None,
+    };
+    let matched_authority: String = row.get(13)?;
+
+    Ok(ProductionStakeAccountInfo {
+        pubkey: row.get(0)?,
+        lamports: row.get(1)?,
+        stake_type: row.get(2)?,
+        delegation,
+        authority: ProductionStakeAuthority {
+            staker: row.get(8)?,
+            withdrawer: row.get(9)?,
+        },
+        lockup: ProductionStakeLockup {
+            custodian: row.get(10)?,
+            epoch: epoch_from_sql(row.get(11)?),
+            unix_timestamp: row.get(12)?,
+        },
+        matched_authority: authority_role_from_sql(&matched_authority),
+    })
+}
+
+fn insert_pool(tx: &rusqlite::Transaction<'_>, snapshot_id: i64, pool: &ProductionPoolData) -> Result<()> {
+    tx.execute(
+        "INSERT INTO pools (snapshot_id, pool_name, authority, fetched_at, epoch, served_by, spl_stake_pool, marinade_state)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            snapshot_id,
+            pool.pool_name,
+            pool.authority,
+            pool.fetched_at,
+            pool.epoch,
+            serde_json::to_string(&pool.served_by)?,
+            pool.spl_stake_pool.as_ref().map(serde_json::to_string).transpose()?,
+            pool.marinade_state.as_ref().map(serde_json::to_string).transpose()?,
+        ],
+    )?;
+    let pool_id = tx.last_insert_rowid();
+
+    if let Some(rate) = &pool.exchange_rate {
+        tx.execute(
+            "INSERT INTO exchange_rates VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                pool_id,
+                rate.lst_mint,
+                rate.total_lamports,
+                lamports_source_to_sql(rate.lamports_source),
+                rate.token_supply.amount,
+                rate.token_supply.decimals,
+                rate.rate,
+                rate.epoch,
+                rate.fetched_at,
+            ],
+        )?;
+    }
+
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO stake_accounts (pool_id, position, pubkey, lamports, stake_type, validator, stake_lamports,
+             activation_epoch, deactivation_epoch, last_epoch_credits_cumulative, staker, withdrawer, custodian,
+             lockup_epoch, lockup_unix_timestamp, matched_authority)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+    )?;
+    for (position, account) in pool.stake_accounts.iter().enumerate() {
+        let delegation = account.delegation.as_ref();
+        stmt.execute(params![
+            pool_id,
+            position,
+            account.pubkey,
+            account.lamports,
+            account.stake_type,
+            delegation.map(|d| &d.validator),
+            delegation.map(|d| d.stake_lamports),
+            delegation.and_then(|d| epoch_to_sql(d.activation_epoch)),
+            delegation.and_then(|d| epoch_to_sql(d.deactivation_epoch)),
+            delegation.map(|d| d.last_epoch_credits_cumulative),
+            account.authority.staker,
+            account.authority.withdrawer,
+            account.lockup.custodian,
+            epoch_to_sql(account.lockup.epoch),
+            account.lockup.unix_timestamp,
+            authority_role_to_sql(account.matched_authority),
+        ])?;
+    }
+
+    let mut distribution_stmt = tx.prepare_cached(
+        "INSERT INTO validator_distribution (pool_id, validator, total_delegated, account_count) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    let mut accounts_stmt =
+        tx.prepare_cached("INSERT INTO validator_accounts (pool_id, validator, position, pubkey) VALUES (?1, ?2, ?3, ?4)")?;
+    for (validator, stake) in &pool.validator_distribution {
+        distribution_stmt.execute(params![pool_id, validator, stake.total_delegated, stake.account_count])?;
+        for (position, pubkey) in stake.accounts.iter().enumerate() {
+            accounts_stmt.execute(params![pool_id, validator, position, pubkey])?;
+        }
+    }
+
+    let stats = &pool.statistics;
+    tx.execute(
+        "INSERT INTO statistics VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            pool_id,
+            stats.total_accounts,
+            stats.activating_accounts,
+            stats.active_accounts,
+            stats.deactivating_accounts,
+            stats.deactivated_accounts,
+            stats.total_lamports,
+            stats.activating_stake_lamports,
+            stats.active_stake_lamports,
+            stats.deactivating_stake_lamports,
+            stats.deactivated_stake_lamports,
+            stats.validator_count,
+        ],
+    )?;
+    Ok(())
+}
+
+/// `u64::MAX` ("never") is stored as `NULL`
+const fn epoch_to_sql(epoch: u64) -> Option<u64> {
+    if epoch == u64::MAX {
+        None
+    } else {
+        Some(epoch)
+    }
+}
+
+fn epoch_from_sql(epoch: Option<u64>) -> u64 {
+    epoch.unwrap_or(u64::MAX)
+}
+
+const fn authority_role_to_sql(role: AuthorityRole) -> &'static str {
+    match role {
+        AuthorityRole::Staker => "staker",
+        AuthorityRole::Withdrawer => "withdrawer",
+        AuthorityRole::Both => "both",
+    }
+}
+
+fn authority_role_from_sql(role: &str) -> AuthorityRole {
+    match role {
+        "withdrawer" => AuthorityRole::Withdrawer,
+        "both" => AuthorityRole::Both,
+        _ => AuthorityRole::Staker,
+    }
+}
+
+const fn lamports_source_to_sql(source: LamportsSource) -> &'static str {
+    match source {
+        LamportsSource::StakeAccounts => "stake_accounts",
+        LamportsSource::PoolState => "pool_state",
+    }
+}
+
+fn lamports_source_from_sql(source: &str) -> LamportsSource {
+    match source {
+        "pool_state" => LamportsSource::PoolState,
+        _ => LamportsSource::StakeAccounts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{pool, stake_account, undelegated_account};
+
+    fn sample_pool(name: &str) -> ProductionPoolData {
+        let mut matched_by_both = stake_account("a1", "vote1", u64::MAX);
+        matched_by_both.matched_authority = AuthorityRole::Both;
+        let mut data = pool(name, vec![matched_by_both, stake_account("a2", "vote1", 600), undelegated_account("a3")]);
+
+        let mut stake = ValidatorStake::new();
+        stake.total_delegated = 1_000_000_000;
+        stake.account_count = 1;
+        stake.accounts.push("a1".to_string());
+        data.validator_distribution.insert("vote1".to_string(), stake);
+        data.statistics = PoolStatistics {
+            total_accounts: 3,
+            active_accounts: 1,
+            deactivated_accounts: 1,
+            total_lamports: data.stake_accounts.iter().map(|account| account.lamports).sum(),
+            active_stake_lamports: 1_000_000_000,
+            validator_count: 1,
+            ..PoolStatistics::default()
+        };
+        data.served_by = vec!["rpc.example.com".to_string()];
+        data.exchange_rate = ExchangeRate::new(
+            name,
+            "mint",
+            data.statistics.total_lamports,
+            LamportsSource::StakeAccounts,
+            TokenSupply { amount: 2_000_000_000, decimals: 9 },
+            Some(610),
+        );
+        data
+    }
+
+    #[test]
+    fn test_migrations() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+
+        let path = std::env::temp_dir().join(format!("pools-store-{}.sqlite", std::process::id()));
+        drop(SnapshotStore::open(&path).unwrap());
+        // Reopening an up-to-date database applies nothing
+        let reopened = SnapshotStore::open(&path).unwrap();
+        assert_eq!(reopened.schema_version().unwrap(), MIGRATIONS.len());
+        reopened.conn.pragma_update(None, "user_version", MIGRATIONS.len() + 1).unwrap();
+        drop(reopened);
+        assert!(SnapshotStore::open(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_exchange_rate_migration() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&MIGRATIONS[..2].concat()).unwrap();
+        conn.pragma_update(None, "user_version", 2).unwrap();
+        let pool = sample_pool("jito");
+        conn.execute("INSERT INTO snapshots (fetched_at) VALUES (?1)", [pool.fetched_at]).unwrap();
+        conn.execute(
+            "INSERT INTO pools (snapshot_id, pool_name, authority, fetched_at, exchange_rate) VALUES (1, 'jito', 'authority', ?1, ?2)",
+            params![pool.fetched_at, serde_json::to_string(&pool.exchange_rate).unwrap()],
+        )
+        .unwrap();
+
+        let store = SnapshotStore::with_connection(conn).unwrap();
+        let loaded = store.load_pool(1, "jito").unwrap().unwrap();
+        assert!(loaded.exchange_rate.is_some());
+        assert_eq!(loaded.exchange_rate, pool.exchange_rate);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut store = SnapshotStore::open_in_memory().unwrap();
+        let jito = sample_pool("jito");
+        let marinade = sample_pool("marinade");
+
+        let first = store.save_snapshot([&jito, &marinade], Some(610)).unwrap();
+        let second = store.save_snapshot([&jito], None).unwrap();
+
+        let loaded = store.load_pool(first, "jito").unwrap().unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&jito).unwrap());
+        assert_eq!(loaded.stake_accounts[0].delegation.as_ref().unwrap().deactivation_epoch, u64::MAX);
+        assert!(store.load_pool(first, "lido").unwrap().is_none());
+
+        assert_eq!(store.load_snapshot(first).unwrap().len(), 2);
+        assert_eq!(store.snapshots().unwrap().len(), 2);
+        assert_eq!(store.snapshots_in_epoch(610).unwrap()[0].pool_count, 2);
+        assert_eq!(store.latest_snapshot().unwrap().unwrap().id, second);
+        assert!(store.load_latest_pool("jito").unwrap().is_some());
+
+        let history = store.pool_statistics_history("jito").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].1.total_lamports, jito.statistics.total_lamports);
+
+        assert!(store.delete_snapshot(first).unwrap());
+        assert!(store.load_pool(first, "jito").unwrap().is_none());
+        assert_eq!(store.pool_statistics_history("marinade").unwrap().len(), 0);
+    }
+}
@@ -0,0 +1,83 @@
+//! Shared test fixtures: clients over a mock or unreachable RPC, and stake account
+//! and pool factories.
+
+use crate::client::PoolsDataClient;
+use crate::config::PoolsDataClientBuilder;
+use crate::pools::AuthorityRole;
+use crate::transport::RpcTransport;
+use crate::types::{
+    PoolData, ProductionPoolData, ProductionStakeAccountInfo, ProductionStakeAuthority, ProductionStakeDelegation,
+    ProductionStakeLockup,
+};
+use std::sync::Arc;
+
+/// Rent-exempt reserve of a stake account
+pub(crate) const RENT_EXEMPT_RESERVE: u64 = 2_282_880;
+
+/// Client over `transport` without rate limit and with 1 ms retry backoff
+pub(crate) fn mock_client(transport: Arc<impl RpcTransport + 'static>) -> PoolsDataClient {
+    mock_client_with(transport, |builder| builder)
+}
+
+/// `mock_client` with extra builder settings
+pub(crate) fn mock_client_with(
+    transport: Arc<impl RpcTransport + 'static>,
+    configure: impl FnOnce(PoolsDataClientBuilder) -> PoolsDataClientBuilder,
+) -> PoolsDataClient {
+    let builder = PoolsDataClient::builder().transport(transport).retry_base_delay(1).no_rate_limit();
+    configure(builder).build("http://mock").and_then(PoolsDataClient::from_config).unwrap()
+}
+
+/// Client whose RPC endpoint refuses connections, without retries or rate limit
+pub(crate) fn offline_client() -> PoolsDataClient {
+    offline_client_with(|builder| builder)
+}
+
+/// `offline_client` with extra builder settings
+pub(crate) fn offline_client_with(configure: impl FnOnce(PoolsDataClientBuilder) -> PoolsDataClientBuilder) -> PoolsDataClient {
+    let builder = PoolsDataClient::builder().retry_attempts(0).no_rate_limit();
+    configure(builder).build("http://127.0.0.1:1").and_then(PoolsDataClient::from_config).unwrap()
+}
+
+/// Account of 1 SOL (plus rent) delegated to `validator` since epoch 500, matched by its staker
+pub(crate) fn stake_account(pubkey: &str, validator: &str, deactivation_epoch: u64) -> ProductionStakeAccountInfo {
+    ProductionStakeAccountInfo {
+        delegation: Some(ProductionStakeDelegation {
+            validator: validator.to_string(),
+            stake_lamports: 1_000_000_000,
+            activation_epoch: 500,
+            deactivation_epoch,
+            last_epoch_credits_cumulative: 0,
+        }),
+        lamports: 1_000_000_000 + RENT_EXEMPT_RESERVE,
+        stake_type: "delegated".to_string(),
+        ..undelegated_account(pubkey)
+    }
+}
+
+/// Initialized account holding only its rent-exempt reserve, matched by its staker
+pub(crate) fn undelegated_account(pubkey: &str) -> ProductionStakeAccountInfo {
+    ProductionStakeAccountInfo {
+        pubkey: pubkey.to_string(),
+        lamports: RENT_EXEMPT_RESERVE,
+        stake_type: "initialized".to_string(),
+        delegation: None,
+        authority: ProductionStakeAuthority {
+            staker: "staker".to_string(),
+            withdrawer: "withdrawer".to_string(),
+        },
+        lockup: ProductionStakeLockup {
+            custodian: "11111111111111111111111111111111".to_string(),
+            epoch: 0,
+            unix_timestamp: 0,
+        },
+        matched_authority: AuthorityRole::Staker,
+    }
+}
+
+/// Pool `name` holding `accounts`, with empty statistics and validator distribution
+pub(crate) fn pool(name: &str, accounts: Vec<ProductionStakeAccountInfo>) -> ProductionPoolData {
+    let mut data = ProductionPoolData::from(&PoolData::new(name.to_string(), "authority".to_string()));
+    data.stake_accounts = accounts;
+    data
+}
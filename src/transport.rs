@@ -0,0 +1,596 @@
+//! Pluggable JSON-RPC transports.
+//!
+//! The RPC client serializes requests and parses responses itself; a transport only
+//! moves the request body to a node and returns the response body, whole or in
+//! chunks. [`HttpTransport`] posts over HTTP, [`MockTransport`] answers in-process
+//! for deterministic tests.
+
+use crate::error::{PoolsDataError, Result};
+use crate::types::EpochInfo;
+use futures::future::{BoxFuture, FutureExt};
+use futures::stream::{BoxStream, StreamExt};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Sends serialized JSON-RPC requests and returns the raw response bodies
+///
+/// Implementations report transport failures (connection, HTTP status) as errors;
+/// JSON-RPC errors are part of the response body and are handled by the caller.
+pub trait RpcTransport: Send + Sync + std::fmt::Debug {
+    /// Send one request body and return the response body
+    fn send(&self, body: String) -> BoxFuture<'_, Result<String>>;
+
+    /// Send one request body and return the response body as it arrives
+    ///
+    /// Large `getProgramAccounts` responses are parsed from these chunks without
+    /// holding the whole body. The default delivers the body of [`Self::send`] as
+    /// a single chunk.
+    fn send_chunked(&self, body: String) -> BoxFuture<'_, Result<BodyChunks>> {
+        self.send(body)
+            .map(|response| response.map(|text| futures::stream::once(async move { Ok(text.into_bytes()) }).boxed()))
+            .boxed()
+    }
+}
+
+/// Response body chunks of [`RpcTransport::send_chunked`]
+pub type BodyChunks = BoxStream<'static, Result<Vec<u8>>>;
+
+/// Prefix of the `NetworkError` message for a non-success HTTP status
+const HTTP_ERROR_PREFIX: &str = "HTTP error: ";
+
+/// HTTP status of a request [`HttpTransport`] failed with a non-success status
+pub(crate) fn http_error_status(error: &PoolsDataError) -> Option<u16> {
+    match error {
+        PoolsDataError::NetworkError { message } => message.strip_prefix(HTTP_ERROR_PREFIX)?.get(..3)?.parse().ok(),
+        _ => None,
+    }
+}
+
+/// JSON-RPC over HTTP POST (the default transport)
+pub struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl std::fmt::Debug for HttpTransport {
+    // The URL is left out: its path or query may hold an API key
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpTransport").finish_non_exhaustive()
+    }
+}
+
+impl HttpTransport {
+    /// Create a transport posting to `url`
+    ///
+    /// # Panics
+    /// Panics if the HTTP client cannot be created (TLS backend unavailable).
+    #[must_use]
+    pub fn new(url: String, timeout: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .user_agent("pools-data-lib/0.1.0")
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { client, url }
+    }
+
+    /// Post `body`, failing on non-success HTTP status
+    async fn post(&self, body: String) -> Result<reqwest::Response> {
+        let response = self
+            .client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(PoolsDataError::NetworkError {
+                message: format!("{HTTP_ERROR_PREFIX}{}", response.status()),
+            });
+        }
+
+        Ok(response)
+    }
+}
+
+impl RpcTransport for HttpTransport {
+    fn send(&self, body: String) -> BoxFuture<'_, Result<String>> {
+        async move { Ok(self.post(body).await?.text().await?) }.boxed()
+    }
+
+    fn send_chunked(&self, body: String) -> BoxFuture<'_, Result<BodyChunks>> {
+        async move {
+            let response = self.post(body).await?;
+            let chunks = futures::stream::unfold(Some(response), |response| async move {
+                let mut response = response?;
+                match response.chunk().await {
+                    Ok(Some(chunk)) => Some((Ok(chunk.to_vec()), Some(response))),
+                    Ok(None) => None,
+                    // Stop after the first error
+                    Err(e) => Some((Err(e.into()), None)),
+                }
+            });
+            Ok(chunks.boxed())
+        }
+        .boxed()
+    }
+}
+
+/// Canned answer of a [`MockTransport`]
+#[derive(Debug, Clone)]
+pub enum MockReply {
+    /// Successful response with this `result`
+    Result(Value),
+    /// JSON-RPC error response
+    RpcError { code: i64, message: String },
+    /// Transport failure (e.g. `NetworkError` to exercise retries and failover)
+    Fail(PoolsDataError),
+    /// Response body sent verbatim (e.g. truncated or non-JSON payloads)
+    Raw(String),
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    replies: HashMap<String, MockReply>,
+    queued: HashMap<String, VecDeque<MockReply>>,
+    program_accounts: HashMap<String, Value>,
+    delays: HashMap<String, Duration>,
+    reject_batches: bool,
+    requests: Vec<Value>,
+    sends: usize,
+}
+
+impl MockState {
+    /// Every canned program account, with base64 data cut to `data_slice` if given
+    fn sweep(&self, data_slice: &Value) -> Value {
+        let slice = |data: &str| {
+            let engine = base64::engine::general_purpose::STANDARD;
+            let bytes = base64::Engine::decode(&engine, data).unwrap_or_default();
+            let start = data_slice["offset"].as_u64().and_then(|n| usize::try_from(n).ok()).unwrap_or(0).min(bytes.len());
+            let length = data_slice["length"].as_u64().and_then(|n| usize::try_from(n).ok()).unwrap_or(usize::MAX);
+            base64::Engine::encode(&engine, &bytes[start..start.saturating_add(length).min(bytes.len())])
+        };
+
+        let accounts = self.program_accounts.values().filter_map(Value::as_array).flatten().cloned();
+        accounts
+            .map(|mut account| {
+                let data = &mut account["account"]["data"];
+                if !data_slice.is_null() && data[1] == "base64" {
+                    data[0] = json!(slice(data[0].as_str().unwrap_or_default()));
+                }
+                account
+            })
+            .collect()
+    }
+
+    /// `getMultipleAccounts` result for `pubkeys`, `null` for unknown accounts
+    fn multiple_accounts(&self, pubkeys: &Value) -> Value {
+        let accounts: Vec<&Value> = self.program_accounts.values().filter_map(Value::as_array).flatten().collect();
+        let value: Vec<Value> = pubkeys
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|pubkey| {
+                let account = accounts.iter().find(|account| account["pubkey"] == *pubkey);
+                account.map_or(Value::Null, |account| account["account"].clone())
+            })
+            .collect();
+        json!({ "context": { "slot": 0 }, "value": value })
+    }
+}
+
+/// In-process transport serving canned responses by RPC method
+///
+/// Replies are looked up per method: queued one-shot replies first, then the
+/// method's standing reply. `getProgramAccounts` can also be answered per
+/// authority (the `memcmp` filter bytes); authorities without accounts get an
+/// empty list, and a query without `memcmp` filter (a sweep) gets every
+/// account, honouring `dataSlice` for base64 data. `getMultipleAccounts` looks
+/// accounts up by pubkey among them. `getHealth` answers `"ok"` unless
+/// overridden, and any other method without a reply gets a "Method not found" error.
+///
+/// JSON-RPC batches are answered item by item; a `Fail` or `Raw` reply to any
+/// item applies to the whole batch.
+///
+/// ```
+/// use solana_pools_data_lib::{MockReply, MockTransport, PoolsDataClient};
+/// use std::sync::Arc;
+///
+/// let transport = Arc::new(
+///     MockTransport::new().reply("getEpochInfo", MockReply::Raw("{ truncated".to_string())),
+/// );
+/// let client = PoolsDataClient::builder()
+///     .transport(transport.clone())
+///     .build("http://mock")
+///     .and_then(PoolsDataClient::from_config)
+///     .unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    state: Mutex<MockState>,
+}
+
+impl MockTransport {
+    /// Create a transport with no canned responses
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Standing reply for every request of `method`
+    #[must_use]
+    pub fn reply(self, method: &str, reply: MockReply) -> Self {
+        self.state().replies.insert(method.to_string(), reply);
+        self
+    }
+
+    /// Answer `getEpochInfo` with `epoch_info`
+    #[must_use]
+    pub fn epoch_info(self, epoch_info: &EpochInfo) -> Self {
+        self.reply("getEpochInfo", MockReply::Result(json!(epoch_info)))
+    }
+
+    /// Answer `getProgramAccounts` filtered on `authority` with `accounts` (a JSON array)
+    #[must_use]
+    pub fn program_accounts(self, authority: &str, accounts: Value) -> Self {
+        self.state().program_accounts.insert(authority.to_string(), accounts);
+        self
+    }
+
+    /// Wait `delay` before answering each request of `method`
+    #[must_use]
+    pub fn delay(self, method: &str, delay: Duration) -> Self {
+        self.state().delays.insert(method.to_string(), delay);
+        self
+    }
+
+    /// Answer JSON-RPC batches with a single error, like providers that disable batching
+    #[must_use]
+    pub fn reject_batches(self) -> Self {
+        self.state().reject_batches = true;
+        self
+    }
+
+    /// Queue a one-shot reply for the next request of `method`, ahead of its standing reply
+    pub fn push_reply(&self, method: &str, reply: MockReply) {
+        self.state().queued.entry(method.to_string()).or_default().push_back(reply);
+    }
+
+    /// Every request received so far, in order
+    #[must_use]
+    pub fn requests(&self) -> Vec<Value> {
+        self.state().requests.clone()
+    }
+
+    /// Number of bodies sent to the transport (a batch counts once)
+    #[must_use]
+    pub fn sends(&self) -> usize {
+        self.state().sends
+    }
+
+    /// Number of requests received for `method`
+    #[must_use]
+    pub fn calls(&self, method: &str) -> usize {
+        self.state().requests.iter().filter(|request| request["method"] == method).count()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Reply and delay for a request, recording it
+    fn answer(&self, request: Value) -> (MockReply, Option<Duration>) {
+        let mut state = self.state();
+        let method = request["method"].as_str().unwrap_or_default().to_string();
+        let reply = state
+            .queued
+            .get_mut(&method)
+            .and_then(VecDeque::pop_front)
+            .or_else(|| state.replies.get(&method).cloned())
+            .or_else(|| match method.as_str() {
+                "getHealth" => Some(MockReply::Result(json!("ok"))),
+                "getProgramAccounts" => match request["params"][1]["filters"][0]["memcmp"]["bytes"].as_str() {
+                    Some(authority) => {
+                        Some(MockReply::Result(state.program_accounts.get(authority).cloned().unwrap_or_else(|| json!([]))))
+                    }
+                    None => Some(MockReply::Result(state.sweep(&request["params"][1]["dataSlice"]))),
+                },
+                "getMultipleAccounts" => Some(MockReply::Result(state.multiple_accounts(&request["params"][0]))),
+                _ => None,
+            })
+            .unwrap_or_else(|| MockReply::RpcError {
+                code: -32601,
+                message: format!("Method not found: {method}"),
+            });
+        let delay = state.delays.get(&method).copied();
+        state.requests.push(request);
+        (reply, delay)
+    }
+}
+
+impl RpcTransport for MockTransport {
+    fn send(&self, body: String) -> BoxFuture<'_, Result<String>> {
+        async move {
+            let request: Value = serde_json::from_str(&body)?;
+            let reject_batch = {
+                let mut state = self.state();
+                state.sends += 1;
+                state.reject_batches && request.is_array()
+            };
+            if reject_batch {
+                let error = json!({ "code": -32600, "message": "Batch requests are not supported" });
+                return Ok(json!({ "jsonrpc": "2.0", "id": null, "error": error }).to_string());
+            }
+
+            let Value::Array(requests) = request else {
+                let id = request["id"].clone();
+                let (reply, delay) = self.answer(request);
+                if let Some(delay) = delay {
+                    tokio::time::sleep(delay).await;
+                }
+                return Self::respond(id, reply).map(|response| response.map_or_else(|body| body, |response| response.to_string()));
+            };
+
+            let answers: Vec<_> = requests
+                .into_iter()
+                .map(|request| (request["id"].clone(), self.answer(request)))
+                .collect();
+            if let Some(delay) = answers.iter().filter_map(|(_, (_, delay))| *delay).max() {
+                tokio::time::sleep(delay).await;
+            }
+            let responses = answers
+                .into_iter()
+                .map(|(id, (reply, _))| Self::respond(id, reply))
+                .collect::<Result<std::result::Result<Vec<_>, _>>>()?;
+            Ok(responses.map_or_else(|body| body, |responses| Value::Array(responses).to_string()))
+        }
+        .boxed()
+    }
+}
+
+impl MockTransport {
+    /// Response to one request, or the verbatim body of a `Raw` reply
+    fn respond(id: Value, reply: MockReply) -> Result<std::result::Result<Value, String>> {
+        match reply {
+            MockReply::Result(result) => Ok(Ok(json!({ "jsonrpc": "2.0", "id": id, "result": result }))),
+            MockReply::RpcError { code, message } => {
+                Ok(Ok(json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })))
+            }
+            MockReply::Fail(error) => Err(error),
+            MockReply::Raw(body) => Ok(Err(body)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::mock_client_with;
+    use crate::PoolsDataClient;
+    use std::sync::Arc;
+
+    fn client(transport: &Arc<MockTransport>) -> PoolsDataClient {
+        mock_client_with(Arc::clone(transport), |builder| builder.retry_attempts(2))
+    }
+
+    fn delegated_account(pubkey: &str, staker: &str, voter: &str, stake: u64) -> Value {
+        json!({
+            "pubkey": pubkey,
+            "account": {
+                "lamports": stake + 2_282_880,
+                "executable": false,
+                "owner": "Stake11111111111111111111111111111111111111",
+                "rentEpoch": 0,
+                "space": 200,
+                "data": {
+                    "program": "stake",
+                    "space": 200,
+                    "parsed": {
+                        "type": "delegated",
+                        "info": {
+                            "meta": {
+                                "authorized": { "staker": staker, "withdrawer": staker },
+                                "lockup": { "custodian": "11111111111111111111111111111111", "epoch": 0, "unixTimestamp": 0 },
+                                "rentExemptReserve": "2282880"
+                            },
+                            "stake": {
+                                "creditsObserved": 0,
+                                "delegation": {
+                                    "activationEpoch": "100",
+                                    "deactivationEpoch": "18446744073709551615",
+                                    "stake": stake.to_string(),
+                                    "voter": voter,
+                                    "warmupCooldownRate": 0.25
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_client_over_mock_transport() {
+        let jito = crate::PoolRegistry::default().get("jito").unwrap().authority.clone();
+        let transport = Arc::new(
+            MockTransport::new()
+                .epoch_info(&EpochInfo {
+                    epoch: 800,
+                    slot_index: 10,
+                    slots_in_epoch: 432_000,
+                    absolute_slot: 345_600_010,
+                    block_height: 320_000_000,
+                    transaction_count: None,
+                })
+                .program_accounts(
+                    &jito,
+                    json!([
+                        delegated_account("acct1", &jito, "vote1", 5_000_000_000),
+                        delegated_account("acct2", &jito, "vote2", 3_000_000_000),
+                    ]),
+                ),
+        );
+        let client = client(&transport);
+
+        client.test_connection().await.unwrap();
+        assert_eq!(client.fetch_epoch_info().await.unwrap().epoch, 800);
+
+        let pools = client.fetch_pools(&["jito"]).await.unwrap();
+        let jito_pool = &pools["jito"];
+        assert_eq!(jito_pool.stake_accounts.len(), 2);
+        assert_eq!(jito_pool.validator_distribution.len(), 2);
+        assert_eq!(transport.calls("getProgramAccounts"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_exchange_rates_fetched_concurrently() {
+        let registry = crate::PoolRegistry::default();
+        let (jito, jpool) = (&registry.get("jito").unwrap().authority, &registry.get("jpool").unwrap().authority);
+        let transport = Arc::new(
+            MockTransport::new()
+                .program_accounts(jito, json!([delegated_account("acct1", jito, "vote1", 5_000_000_000)]))
+                .program_accounts(jpool, json!([delegated_account("acct2", jpool, "vote1", 3_000_000_000)]))
+                .reply(
+                    "getTokenSupply",
+                    MockReply::Result(json!({ "context": { "slot": 0 }, "value": { "amount": "4000000000", "decimals": 9 } })),
+                )
+                .delay("getTokenSupply", Duration::from_millis(300)),
+        );
+        let client = mock_client_with(Arc::clone(&transport), |builder| builder.include_exchange_rates(true));
+
+        let started = std::time::Instant::now();
+        let result = client.fetch_pools_debug(&["jito", "jpool"]).await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(600));
+        assert_eq!(transport.calls("getTokenSupply"), 2);
+        assert!(result.successful.values().all(|pool| pool.exchange_rate.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_batched_fetch() {
+        let registry = crate::PoolRegistry::default();
+        let names = ["jito", "blazestake", "jupiter"];
+        let mock = || {
+            names.iter().enumerate().fold(MockTransport::new(), |transport, (i, name)| {
+                let authority = registry.get(name).unwrap().authority.clone();
+                let account = delegated_account(&format!("acct{i}"), &authority, "vote1", 1_000_000_000);
+                transport.program_accounts(&authority, json!([account]))
+            })
+        };
+        let transport = Arc::new(mock());
+        let batched = |transport: &Arc<MockTransport>| mock_client_with(Arc::clone(transport), |builder| builder.batch_size(2));
+
+        // Two pools share one batch, the third is fetched on its own; each fetch also reads the epoch
+        let pools = batched(&transport).fetch_pools(&names).await.unwrap();
+        assert_eq!(pools.len(), 3);
+        assert!(names.iter().all(|name| pools[*name].stake_accounts.len() == 1));
+        assert_eq!(transport.calls("getProgramAccounts"), 3);
+        assert_eq!(transport.calls("getEpochInfo"), 1);
+        assert_eq!(transport.sends(), 3);
+
+        // A provider rejecting batches is queried one pool at a time
+        let rejecting = Arc::new(mock().reject_batches());
+        let client = batched(&rejecting);
+        assert_eq!(client.fetch_pools(&names).await.unwrap().len(), 3);
+        assert_eq!(rejecting.calls("getProgramAccounts"), 3);
+        assert_eq!(rejecting.sends(), 5);
+
+        // The rejection is remembered: no further batches are sent
+        client.fetch_pools(&names).await.unwrap();
+        assert_eq!(rejecting.sends(), 9);
+
+        // A transient error object is retried and keeps batching enabled
+        let transport = Arc::new(mock());
+        let rate_limited = json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32005, "message": "Too many requests" } });
+        transport.push_reply("getProgramAccounts", MockReply::Raw(rate_limited.to_string()));
+        let client = batched(&transport);
+        assert_eq!(client.fetch_pools(&names).await.unwrap().len(), 3);
+        assert_eq!(transport.sends(), 4);
+        client.fetch_pools(&names).await.unwrap();
+        assert_eq!(transport.sends(), 7);
+
+        // An HTTP-level rejection (413 Payload Too Large) falls back at once
+        let transport = Arc::new(mock());
+        let too_large = PoolsDataError::NetworkError {
+            message: format!("{HTTP_ERROR_PREFIX}413 Payload Too Large"),
+        };
+        transport.push_reply("getProgramAccounts", MockReply::Fail(too_large));
+        let client = batched(&transport);
+        assert_eq!(client.fetch_pools(&names).await.unwrap().len(), 3);
+        assert_eq!(transport.sends(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_sweep_fetch() {
+        use crate::decode::tests::{encode_stake_account, pubkey_bytes};
+        use crate::pools::{AuthorityRole, PoolInfo};
+        use crate::{FetchMode, PoolRegistry};
+
+        let pubkey = |seed| bs58::encode(pubkey_bytes(seed)).into_string();
+        let encoded = |staker: u8| {
+            let mut data = encode_stake_account(2);
+            data[12..44].copy_from_slice(&pubkey_bytes(staker));
+            let data = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, data);
+            json!({ "data": [data, "base64"], "executable": false, "lamports": 5_002_282_880u64, "owner": "Stake11111111111111111111111111111111111111" })
+        };
+        // Staker 1 or 5, withdrawer always 2
+        let transport = Arc::new(MockTransport::new().program_accounts(
+            "sweep",
+            json!([
+                { "pubkey": pubkey(10), "account": encoded(1) },
+                { "pubkey": pubkey(11), "account": encoded(5) },
+            ]),
+        ));
+
+        let mut registry = PoolRegistry::empty();
+        registry.insert(PoolInfo::new("by_staker", pubkey(1))).unwrap();
+        registry.insert(PoolInfo::new("by_withdrawer", pubkey(2)).with_authority_role(AuthorityRole::Both)).unwrap();
+        registry.insert(PoolInfo::new("empty", pubkey(9))).unwrap();
+
+        for data_slice in [false, true] {
+            let client = mock_client_with(Arc::clone(&transport), |builder| {
+                builder.registry(registry.clone()).fetch_mode(FetchMode::Sweep { data_slice })
+            });
+
+            let result = client.fetch_pools_debug(&["by_staker", "by_withdrawer", "empty"]).await.unwrap();
+            let by_staker = &result.successful["by_staker"];
+            assert_eq!(by_staker.stake_accounts.len(), 1);
+            assert_eq!(by_staker.stake_accounts[0].pubkey, pubkey(10));
+            assert_eq!(by_staker.statistics.total_accounts, 1);
+
+            let by_withdrawer = &result.successful["by_withdrawer"].stake_accounts;
+            assert_eq!(by_withdrawer.len(), 2);
+            assert!(by_withdrawer.iter().all(|account| account.matched_authority == AuthorityRole::Withdrawer));
+            assert!(matches!(result.failed["empty"].error, PoolsDataError::NoStakeAccounts { .. }));
+        }
+
+        // One scan, then one scan plus one getMultipleAccounts
+        assert_eq!(transport.calls("getProgramAccounts"), 2);
+        assert_eq!(transport.calls("getMultipleAccounts"), 1);
+        let requests = transport.requests();
+        let sliced = requests.iter().find(|request| !request["params"][1]["dataSlice"].is_null()).unwrap();
+        assert_eq!(sliced["params"][1]["dataSlice"]["length"], 64);
+    }
+
+    #[tokio::test]
+    async fn test_injected_failures() {
+        let transport = Arc::new(MockTransport::new().delay("getEpochInfo", Duration::from_millis(20)));
+        let client = client(&transport);
+
+        // A network failure is retried, a malformed payload is not
+        transport.push_reply("getHealth", MockReply::Fail(PoolsDataError::NetworkError { message: "reset".to_string() }));
+        client.test_connection().await.unwrap();
+        assert_eq!(transport.calls("getHealth"), 2);
+
+        transport.push_reply("getEpochInfo", MockReply::Raw("{ truncated".to_string()));
+        assert!(matches!(client.fetch_epoch_info().await, Err(PoolsDataError::ParseError { .. })));
+        assert_eq!(transport.calls("getEpochInfo"), 1);
+
+        // No canned epoch info: "Method not found"
+        assert!(matches!(client.fetch_epoch_info().await, Err(PoolsDataError::RpcError { code: -32601, .. })));
+    }
+}
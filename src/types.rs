@@ -1,4 +1,9 @@
 use crate::error::PoolError;
+use crate::exchange_rate::ExchangeRate;
+use crate::marinade::MarinadeState;
+use crate::pools::AuthorityRole;
+use crate::spl_stake_pool::SplStakePoolState;
+use crate::stake_math::StakeHistory;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -58,6 +63,50 @@ pub fn calculate_pool_statistics(stake_accounts: &[StakeAccountInfo], current_ep
         validator_count: validator_set.len(),
     }
 }
+/// Calculate pool statistics using runtime warmup/cooldown math
+///
+/// Unlike [`calculate_pool_statistics`], partially warmed up or cooled down accounts
+/// are split across states by lamports, matching what explorers report mid-epoch.
+/// Accounts are counted in the first matching state: activating, deactivating,
+/// active, deactivated.
+#[must_use]
+pub fn calculate_effective_pool_statistics(
+    stake_accounts: &[StakeAccountInfo],
+    current_epoch: u64,
+    stake_history: &StakeHistory,
+    new_rate_activation_epoch: Option<u64>,
+) -> PoolStatistics {
+    let mut statistics = PoolStatistics::default();
+    let mut validator_set = std::collections::HashSet::new();
+
+    for account in stake_accounts {
+        statistics.total_lamports += account.lamports;
+        if let Some(delegation) = &account.delegation {
+            statistics.total_accounts += 1;
+            validator_set.insert(&delegation.voter);
+
+            let status = delegation.activation_status(current_epoch, stake_history, new_rate_activation_epoch);
+            statistics.activating_stake_lamports += status.activating;
+            statistics.active_stake_lamports += status.active();
+            statistics.deactivating_stake_lamports += status.deactivating;
+            statistics.deactivated_stake_lamports += status.inactive(delegation.stake);
+
+            if status.activating > 0 {
+                statistics.activating_accounts += 1;
+            } else if status.deactivating > 0 {
+                statistics.deactivating_accounts += 1;
+            } else if status.effective > 0 {
+                statistics.active_accounts += 1;
+            } else {
+                statistics.deactivated_accounts += 1;
+            }
+        }
+    }
+
+    statistics.validator_count = validator_set.len();
+    statistics
+}
+
 /// Complete result from fetching multiple pools (debug format) data types for stake pool information.
 /// Complete result from fetching multiple pools (debug format)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -127,6 +176,14 @@ impl PoolsDataResult {
             .map(|(name, _)| name.clone())
             .collect()
     }
+
+    /// Successful pools in production format, sorted by name
+    #[must_use]
+    pub fn production_pools(&self) -> Vec<ProductionPoolData> {
+        let mut pools: Vec<ProductionPoolData> = self.successful.values().map(Into::into).collect();
+        pools.sort_by(|a, b| a.pool_name.cmp(&b.pool_name));
+        pools
+    }
 }
 
 /// Complete debug data for a single stake pool (ALL fields)
@@ -155,6 +212,21 @@ pub struct PoolData {
     pub statistics: PoolStatistics,
     /// When this data was fetched
     pub fetched_at: DateTime<Utc>,
+    /// Epoch when this data was fetched, if it could be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u64>,
+    /// RPC endpoint(s) that served the stake account queries
+    #[serde(default)]
+    pub served_by: Vec<String>,
+    /// On-chain SPL stake pool state, when requested and the pool is SPL-based
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spl_stake_pool: Option<SplStakePoolState>,
+    /// On-chain Marinade state, when requested and the pool is Marinade liquid staking
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marinade_state: Option<MarinadeState>,
+    /// SOL/LST exchange rate, when requested and the pool has an LST mint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exchange_rate: Option<ExchangeRate>,
 }
 
 impl PoolData {
@@ -168,6 +240,11 @@ impl PoolData {
             validator_distribution: HashMap::new(),
             statistics: PoolStatistics::default(),
             fetched_at: Utc::now(),
+            epoch: None,
+            served_by: Vec::new(),
+            spl_stake_pool: None,
+            marinade_state: None,
+            exchange_rate: None,
         }
     }
 
@@ -208,6 +285,21 @@ pub struct ProductionPoolData {
     pub statistics: PoolStatistics,
     /// When this data was fetched
     pub fetched_at: DateTime<Utc>,
+    /// Epoch when this data was fetched, if it could be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u64>,
+    /// RPC endpoint(s) that served the stake account queries
+    #[serde(default)]
+    pub served_by: Vec<String>,
+    /// On-chain SPL stake pool state, when requested and the pool is SPL-based
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spl_stake_pool: Option<SplStakePoolState>,
+    /// On-chain Marinade state, when requested and the pool is Marinade liquid staking
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marinade_state: Option<MarinadeState>,
+    /// SOL/LST exchange rate, when requested and the pool has an LST mint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exchange_rate: Option<ExchangeRate>,
 }
 
 impl From<&PoolData> for ProductionPoolData {
@@ -219,6 +311,11 @@ impl From<&PoolData> for ProductionPoolData {
             validator_distribution: pool.validator_distribution.clone(),
             statistics: pool.statistics.clone(),
             fetched_at: pool.fetched_at,
+            epoch: pool.epoch,
+            served_by: pool.served_by.clone(),
+            spl_stake_pool: pool.spl_stake_pool.clone(),
+            marinade_state: pool.marinade_state.clone(),
+            exchange_rate: pool.exchange_rate.clone(),
         }
     }
 }
@@ -238,6 +335,9 @@ pub struct StakeAccountInfo {
     pub authorized: StakeAuthorized,
     /// Lockup configuration
     pub lockup: StakeLockup,
+    /// Which pool authority role matched this account
+    #[serde(default)]
+    pub matched_authority: AuthorityRole,
 }
 
 /// Production stake account
@@ -255,6 +355,9 @@ pub struct ProductionStakeAccountInfo {
     pub authority: ProductionStakeAuthority,
     /// Lockup configuration
     pub lockup: ProductionStakeLockup,
+    /// Which pool authority role matched this account
+    #[serde(default)]
+    pub matched_authority: AuthorityRole,
 }
 
 impl From<&StakeAccountInfo> for ProductionStakeAccountInfo {
@@ -294,6 +397,7 @@ impl From<&StakeAccountInfo> for ProductionStakeAccountInfo {
             delegation,
             authority,
             lockup,
+            matched_authority: account.matched_authority,
         }
     }
 }
@@ -456,6 +560,71 @@ pub struct PoolsDataSummary {
     pub total_processing_time_ms: u64,
 }
 
+/// Current epoch information (from `getEpochInfo`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochInfo {
+    /// Current epoch
+    pub epoch: u64,
+    /// Current slot relative to the start of the epoch
+    pub slot_index: u64,
+    /// Number of slots in this epoch
+    pub slots_in_epoch: u64,
+    /// Current absolute slot
+    pub absolute_slot: u64,
+    /// Current block height
+    pub block_height: u64,
+    /// Total transaction count since genesis (not returned by every node)
+    #[serde(default)]
+    pub transaction_count: Option<u64>,
+}
+
+impl EpochInfo {
+    /// Slots remaining until the next epoch boundary
+    #[must_use]
+    pub const fn slots_remaining(&self) -> u64 {
+        self.slots_in_epoch.saturating_sub(self.slot_index)
+    }
+}
+
+/// Cluster epoch schedule (from `getEpochSchedule`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochSchedule {
+    /// Maximum number of slots in each epoch
+    pub slots_per_epoch: u64,
+    /// Number of slots before the epoch start to calculate the leader schedule
+    pub leader_schedule_slot_offset: u64,
+    /// Whether epochs start short and grow
+    pub warmup: bool,
+    /// First epoch with `slots_per_epoch` slots
+    pub first_normal_epoch: u64,
+    /// First slot of `first_normal_epoch`
+    pub first_normal_slot: u64,
+}
+
+impl EpochSchedule {
+    /// Length of the first epoch when `warmup` is enabled
+    const MINIMUM_SLOTS_PER_EPOCH: u64 = 32;
+
+    /// Epoch containing `slot`
+    ///
+    /// Warmup epochs double in length from `MINIMUM_SLOTS_PER_EPOCH`, as in the runtime.
+    #[must_use]
+    pub const fn get_epoch(&self, slot: u64) -> u64 {
+        if slot < self.first_normal_slot {
+            let epoch = (slot + Self::MINIMUM_SLOTS_PER_EPOCH + 1).next_power_of_two().trailing_zeros()
+                - Self::MINIMUM_SLOTS_PER_EPOCH.trailing_zeros()
+                - 1;
+            return epoch as u64;
+        }
+        match (slot - self.first_normal_slot).checked_div(self.slots_per_epoch) {
+            Some(normal_epochs) => self.first_normal_epoch + normal_epochs,
+            None => self.first_normal_epoch,
+        }
+    }
+}
+
 /// Field analysis for understanding static vs dynamic fields
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldAnalysis {
@@ -558,6 +727,7 @@ mod tests {
                     epoch: 0, 
                     custodian: "".to_string() 
                 },
+                matched_authority: AuthorityRole::Staker,
             },
             StakeAccountInfo {
                 pubkey: "active_account".to_string(),
@@ -580,6 +750,7 @@ mod tests {
                     epoch: 0, 
                     custodian: "".to_string() 
                 },
+                matched_authority: AuthorityRole::Staker,
             },
             StakeAccountInfo {
                 pubkey: "deactivating_account".to_string(),
@@ -602,6 +773,7 @@ mod tests {
                     epoch: 0, 
                     custodian: "".to_string() 
                 },
+                matched_authority: AuthorityRole::Staker,
             },
             StakeAccountInfo {
                 pubkey: "deactivated_account".to_string(),
@@ -624,6 +796,7 @@ mod tests {
                     epoch: 0, 
                     custodian: "".to_string() 
                 },
+                matched_authority: AuthorityRole::Staker,
             },
         ];
 
@@ -647,4 +820,98 @@ mod tests {
         // Verify validator count
         assert_eq!(stats.validator_count, 4);
     }
+
+    #[test]
+    fn test_calculate_effective_pool_statistics() {
+        use crate::stake_math::StakeHistoryEntry;
+
+        let account = |pubkey: &str, stake: u64, activation_epoch: u64, deactivation_epoch: u64| StakeAccountInfo {
+            pubkey: pubkey.to_string(),
+            lamports: stake,
+            rent_exempt_reserve: 0,
+            delegation: Some(StakeDelegation {
+                voter: format!("{pubkey}_validator"),
+                stake,
+                activation_epoch,
+                deactivation_epoch,
+                last_epoch_credits_cumulative: 0,
+                warmup_cooldown_rate: 0.25,
+            }),
+            authorized: StakeAuthorized {
+                staker: "staker".to_string(),
+                withdrawer: "withdrawer".to_string(),
+            },
+            lockup: StakeLockup {
+                unix_timestamp: 0,
+                epoch: 0,
+                custodian: String::new(),
+            },
+            matched_authority: AuthorityRole::Staker,
+        };
+
+        let stake_accounts = vec![
+            account("warming", 1000, 99, u64::MAX),
+            account("cooling", 1000, 10, 99),
+            account("active", 3000, 10, u64::MAX),
+            account("closed", 500, 10, 50),
+        ];
+
+        // Epoch 99: 1000 activating, 1000 deactivating; 25% of 4000 effective moves each way
+        let history = StakeHistory::new(vec![(
+            99,
+            StakeHistoryEntry {
+                effective: 4000,
+                activating: 1000,
+                deactivating: 1000,
+            },
+        )]);
+
+        let stats = calculate_effective_pool_statistics(&stake_accounts, 100, &history, None);
+
+        assert_eq!(stats.total_accounts, 4);
+        assert_eq!(stats.activating_accounts, 0);
+        assert_eq!(stats.active_accounts, 2);
+        assert_eq!(stats.deactivating_accounts, 0);
+        assert_eq!(stats.deactivated_accounts, 2);
+        assert_eq!(stats.active_stake_lamports, 4000);
+        assert_eq!(stats.deactivated_stake_lamports, 1500);
+        assert_eq!(stats.total_lamports, 5500);
+
+        // One epoch earlier both transitions are in flight
+        let stats = calculate_effective_pool_statistics(&stake_accounts, 99, &history, None);
+        assert_eq!(stats.activating_accounts, 1);
+        assert_eq!(stats.deactivating_accounts, 1);
+        assert_eq!(stats.activating_stake_lamports, 1000);
+        assert_eq!(stats.deactivating_stake_lamports, 1000);
+        assert_eq!(stats.active_stake_lamports, 3000);
+        assert_eq!(stats.validator_count, 4);
+    }
+
+    #[test]
+    fn test_epoch_schedule_get_epoch() {
+        let mainnet = EpochSchedule {
+            slots_per_epoch: 432_000,
+            leader_schedule_slot_offset: 432_000,
+            warmup: false,
+            first_normal_epoch: 0,
+            first_normal_slot: 0,
+        };
+        assert_eq!(mainnet.get_epoch(0), 0);
+        assert_eq!(mainnet.get_epoch(431_999), 0);
+        assert_eq!(mainnet.get_epoch(432_000 * 600 + 5), 600);
+
+        // Warmup epochs of 32, 64, ... 4096 slots precede the first normal epoch
+        let warmup = EpochSchedule {
+            slots_per_epoch: 8192,
+            leader_schedule_slot_offset: 8192,
+            warmup: true,
+            first_normal_epoch: 8,
+            first_normal_slot: 8160,
+        };
+        assert_eq!(warmup.get_epoch(31), 0);
+        assert_eq!(warmup.get_epoch(32), 1);
+        assert_eq!(warmup.get_epoch(8159), 7);
+        assert_eq!(warmup.get_epoch(8160), 8);
+        assert_eq!(warmup.get_epoch(8160 + 8192), 9);
+    }
 }
@@ -0,0 +1,471 @@
+//! Long-running pool watch mode.
+//!
+//! `PoolWatcher` polls selected pools, compares consecutive snapshots and publishes
+//! typed `PoolEvent`s over a `tokio::sync::broadcast` channel (or a `Stream`).
+
+use crate::client::PoolsDataClient;
+use crate::diff::PoolDiff;
+use crate::types::{PoolStatistics, ProductionPoolData, ProductionStakeAccountInfo};
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Default capacity of the event channel
+const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
+
+/// When the watcher polls its pools
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchTrigger {
+    /// Poll at a fixed interval
+    Interval(Duration),
+    /// Poll when a new epoch starts, checking the epoch every `check_every`
+    EpochBoundary {
+        /// How often to check the current epoch
+        check_every: Duration,
+    },
+    /// Poll every `slots` slots, checking the slot every `check_every`
+    Slots {
+        /// Slots between polls
+        slots: u64,
+        /// How often to check the current slot
+        check_every: Duration,
+    },
+}
+
+impl Default for WatchTrigger {
+    fn default() -> Self {
+        Self::Interval(Duration::from_secs(60))
+    }
+}
+
+/// Change observed between two polls of a pool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PoolEvent {
+    /// A pool was fetched; emitted on every poll, before its change events
+    Snapshot {
+        pool_name: String,
+        fetched_at: DateTime<Utc>,
+        total_accounts: usize,
+        total_lamports: u64,
+        active_stake_lamports: u64,
+    },
+    /// Stake was delegated: a new delegated account, or an account delegated to a new validator
+    StakeDelegated {
+        pool_name: String,
+        pubkey: String,
+        validator: String,
+        stake_lamports: u64,
+    },
+    /// An account started deactivating
+    DeactivationStarted {
+        pool_name: String,
+        pubkey: String,
+        validator: String,
+        stake_lamports: u64,
+        deactivation_epoch: u64,
+    },
+    /// The pool delegates to a validator it did not delegate to before
+    ValidatorAdded { pool_name: String, validator: String },
+    /// The pool no longer delegates to a validator
+    ValidatorRemoved { pool_name: String, validator: String },
+    /// A stake account disappeared (withdrawn, merged or authority moved away)
+    AccountClosed { pool_name: String, pubkey: String, lamports: u64 },
+    /// A poll of the pool failed; the previous snapshot is kept
+    FetchFailed { pool_name: String, error: String },
+}
+
+impl PoolEvent {
+    /// Name of the pool the event belongs to
+    #[must_use]
+    pub fn pool_name(&self) -> &str {
+        match self {
+            Self::Snapshot { pool_name, .. }
+            | Self::StakeDelegated { pool_name, .. }
+            | Self::DeactivationStarted { pool_name, .. }
+            | Self::ValidatorAdded { pool_name, .. }
+            | Self::ValidatorRemoved { pool_name, .. }
+            | Self::AccountClosed { pool_name, .. }
+            | Self::FetchFailed { pool_name, .. } => pool_name,
+        }
+    }
+
+    fn snapshot(pool: &ProductionPoolData) -> Self {
+        let PoolStatistics {
+            total_accounts,
+            total_lamports,
+            active_stake_lamports,
+            ..
+        } = pool.statistics;
+        Self::Snapshot {
+            pool_name: pool.pool_name.clone(),
+            fetched_at: pool.fetched_at,
+            total_accounts,
+            total_lamports,
+            active_stake_lamports,
+        }
+    }
+}
+
+/// Change events between two snapshots of a pool (without `Snapshot`)
+#[must_use]
+pub fn pool_events(previous: &ProductionPoolData, current: &ProductionPoolData) -> Vec<PoolEvent> {
+    let diff = PoolDiff::between(previous, current);
+    let pool_name = &current.pool_name;
+    let mut events = Vec::new();
+
+    for account in &diff.added_accounts {
+        if let Some(delegation) = &account.delegation {
+            events.push(PoolEvent::StakeDelegated {
+                pool_name: pool_name.clone(),
+                pubkey: account.pubkey.clone(),
+                validator: delegation.validator.clone(),
+                stake_lamports: delegation.stake_lamports,
+            });
+        }
+    }
+    for moved in &diff.moved_delegations {
+        if let Some(validator) = &moved.to_validator {
+            events.push(PoolEvent::StakeDelegated {
+                pool_name: pool_name.clone(),
+                pubkey: moved.pubkey.clone(),
+                validator: validator.clone(),
+                stake_lamports: moved.stake_lamports,
+            });
+        }
+    }
+
+    let before: HashMap<&str, &ProductionStakeAccountInfo> =
+        previous.stake_accounts.iter().map(|account| (account.pubkey.as_str(), account)).collect();
+    for account in &current.stake_accounts {
+        let Some(delegation) = &account.delegation else {
+            continue;
+        };
+        let was_active = before
+            .get(account.pubkey.as_str())
+            .and_then(|old| old.delegation.as_ref())
+            .is_some_and(|old| old.deactivation_epoch == u64::MAX);
+        if was_active && delegation.deactivation_epoch != u64::MAX {
+            events.push(PoolEvent::DeactivationStarted {
+                pool_name: pool_name.clone(),
+                pubkey: account.pubkey.clone(),
+                validator: delegation.validator.clone(),
+                stake_lamports: delegation.stake_lamports,
+                deactivation_epoch: delegation.deactivation_epoch,
+            });
+        }
+    }
+
+    events.extend(diff.validators_entered.into_iter().map(|validator| PoolEvent::ValidatorAdded {
+        pool_name: pool_name.clone(),
+        validator,
+    }));
+    events.extend(diff.validators_left.into_iter().map(|validator| PoolEvent::ValidatorRemoved {
+        pool_name: pool_name.clone(),
+        validator,
+    }));
+    events.extend(diff.removed_accounts.into_iter().map(|account| PoolEvent::AccountClosed {
+        pool_name: pool_name.clone(),
+        pubkey: account.pubkey,
+        lamports: account.lamports,
+    }));
+
+    events
+}
+
+/// Polls pools and publishes change events
+pub struct PoolWatcher {
+    client: Arc<PoolsDataClient>,
+    pool_names: Vec<String>,
+    trigger: WatchTrigger,
+    sender: broadcast::Sender<PoolEvent>,
+    snapshots: HashMap<String, ProductionPoolData>,
+}
+
+impl PoolWatcher {
+    /// Create a watcher for the given pools, polling every minute by default
+    #[must_use]
+    pub fn new(client: Arc<PoolsDataClient>, pool_names: &[&str]) -> Self {
+        Self::with_channel_capacity(client, pool_names, DEFAULT_CHANNEL_CAPACITY)
+    }
+
+    /// Create a watcher with the given event channel capacity (slow subscribers lag beyond it)
+    #[must_use]
+    pub fn with_channel_capacity(client: Arc<PoolsDataClient>, pool_names: &[&str], capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            client,
+            pool_names: pool_names.iter().map(ToString::to_string).collect(),
+            trigger: WatchTrigger::default(),
+            sender,
+            snapshots: HashMap::new(),
+        }
+    }
+
+    /// Set when to poll
+    #[must_use]
+    pub const fn trigger(mut self, trigger: WatchTrigger) -> Self {
+        self.trigger = trigger;
+        self
+    }
+
+    /// Subscribe to events
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<PoolEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Latest snapshot of a pool
+    #[must_use]
+    pub fn snapshot(&self, pool_name: &str) -> Option<&ProductionPoolData> {
+        self.snapshots.get(pool_name)
+    }
+
+    /// Poll all pools once, publish and return the events
+    ///
+    /// The first poll of a pool only emits `Snapshot`.
+    pub async fn poll_once(&mut self) -> Vec<PoolEvent> {
+        let names: Vec<&str> = self.pool_names.iter().map(String::as_str).collect();
+        let mut events = Vec::new();
+
+        match self.client.fetch_pools_debug(&names).await {
+            Ok(result) => {
+                for (pool_name, error) in result.failed {
+                    events.push(PoolEvent::FetchFailed {
+                        pool_name,
+                        error: error.error.to_string(),
+                    });
+                }
+                for pool in result.successful.values() {
+                    let current = ProductionPoolData::from(pool);
+                    events.extend(self.record(current));
+                }
+            }
+            Err(e) => {
+                events.extend(names.iter().map(|name| PoolEvent::FetchFailed {
+                    pool_name: (*name).to_string(),
+                    error: e.to_string(),
+                }));
+            }
+        }
+
+        for event in &events {
+            // No subscribers is not an error
+            let _ = self.sender.send(event.clone());
+        }
+        events
+    }
+
+    /// Store a new snapshot, returning `Snapshot` followed by its change events
+    fn record(&mut self, current: ProductionPoolData) -> Vec<PoolEvent> {
+        let mut events = vec![PoolEvent::snapshot(&current)];
+        if let Some(previous) = self.snapshots.get(&current.pool_name) {
+            events.extend(pool_events(previous, &current));
+        }
+        self.snapshots.insert(current.pool_name.clone(), current);
+        events
+    }
+
+    /// Poll forever according to the trigger
+    ///
+    /// The first poll happens immediately; epoch and slot triggers count from
+    /// the epoch info read when the watcher starts.
+    pub async fn run(mut self) {
+        let baseline = match self.trigger {
+            WatchTrigger::Interval(_) => None,
+            WatchTrigger::EpochBoundary { .. } | WatchTrigger::Slots { .. } => match self.client.fetch_epoch_info().await {
+                Ok(info) => Some(info),
+                Err(e) => {
+                    log::warn!("Watcher failed to fetch epoch info: {e}");
+                    None
+                }
+            },
+        };
+        let mut last_epoch = baseline.as_ref().map(|info| info.epoch);
+        let mut last_slot = baseline.map(|info| info.absolute_slot);
+        loop {
+            self.poll_once().await;
+
+            match self.trigger {
+                WatchTrigger::Interval(interval) => tokio::time::sleep(interval).await,
+                WatchTrigger::EpochBoundary { check_every } => loop {
+                    tokio::time::sleep(check_every).await;
+                    match self.client.fetch_epoch_info().await {
+                        Ok(info) if last_epoch.is_none() => last_epoch = Some(info.epoch),
+                        Ok(info) if last_epoch != Some(info.epoch) => {
+                            last_epoch = Some(info.epoch);
+                            break;
+                        }
+                        Ok(_) => {}
+                        Err(e) => log::warn!("Watcher failed to fetch epoch info: {e}"),
+                    }
+                },
+                WatchTrigger::Slots { slots, check_every } => loop {
+                    tokio::time::sleep(check_every).await;
+                    match self.client.fetch_epoch_info().await {
+                        Ok(info) => {
+                            let start = *last_slot.get_or_insert(info.absolute_slot);
+                            if info.absolute_slot.saturating_sub(start) >= slots {
+                                last_slot = Some(info.absolute_slot);
+                                break;
+                            }
+                        }
+                        Err(e) => log::warn!("Watcher failed to fetch epoch info: {e}"),
+                    }
+                },
+            }
+        }
+    }
+
+    /// Run in a background task
+    #[must_use]
+    pub fn spawn(self) -> WatchHandle {
+        let sender = self.sender.clone();
+        WatchHandle {
+            sender,
+            task: tokio::spawn(self.run()),
+        }
+    }
+}
+
+/// Handle to a spawned `PoolWatcher`; dropping it does not stop the watcher
+pub struct WatchHandle {
+    sender: broadcast::Sender<PoolEvent>,
+    task: JoinHandle<()>,
+}
+
+impl WatchHandle {
+    /// Subscribe to events
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<PoolEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Subscribe to events as a `Stream` (see [`event_stream`])
+    pub fn stream(&self) -> impl Stream<Item = PoolEvent> {
+        event_stream(self.subscribe())
+    }
+
+    /// Stop the watcher; subscribed streams end once the task is gone
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+/// Turn an event receiver into a `Stream`
+///
+/// Events missed by a lagging receiver are skipped with a warning; the stream
+/// ends when the watcher stops.
+pub fn event_stream(receiver: broadcast::Receiver<PoolEvent>) -> impl Stream<Item = PoolEvent> {
+    futures::stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Pool event subscriber lagged, skipped {skipped} events");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+/// Pools with events in a batch
+#[must_use]
+pub fn changed_pools(events: &[PoolEvent]) -> HashSet<&str> {
+    events
+        .iter()
+        .filter(|event| !matches!(event, PoolEvent::Snapshot { .. }))
+        .map(PoolEvent::pool_name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{offline_client, pool, stake_account};
+    use futures::StreamExt;
+
+    #[test]
+    fn test_pool_events() {
+        let previous = pool(
+            "jito",
+            vec![stake_account("a", "v1", u64::MAX), stake_account("b", "v2", u64::MAX), stake_account("c", "v1", u64::MAX)],
+        );
+        let current = pool(
+            "jito",
+            vec![stake_account("a", "v1", 620), stake_account("c", "v3", u64::MAX), stake_account("d", "v1", u64::MAX)],
+        );
+
+        let events = pool_events(&previous, &current);
+        let delegated: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match event {
+                PoolEvent::StakeDelegated { pubkey, .. } => Some(pubkey.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(delegated, vec!["d", "c"]);
+        assert!(events.contains(&PoolEvent::DeactivationStarted {
+            pool_name: "jito".to_string(),
+            pubkey: "a".to_string(),
+            validator: "v1".to_string(),
+            stake_lamports: 1_000_000_000,
+            deactivation_epoch: 620,
+        }));
+        assert!(events.contains(&PoolEvent::ValidatorAdded { pool_name: "jito".to_string(), validator: "v3".to_string() }));
+        assert!(events.contains(&PoolEvent::ValidatorRemoved { pool_name: "jito".to_string(), validator: "v2".to_string() }));
+        assert!(events.contains(&PoolEvent::AccountClosed {
+            pool_name: "jito".to_string(),
+            pubkey: "b".to_string(),
+            lamports: 1_002_282_880,
+        }));
+
+        // Already deactivating accounts are not reported again
+        assert!(pool_events(&current, &current).is_empty());
+
+        // An account first seen while deactivating is only reported as delegated
+        let mut late = current.clone();
+        late.stake_accounts.push(stake_account("e", "v1", 621));
+        let events = pool_events(&current, &late);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], PoolEvent::StakeDelegated { pubkey, .. } if pubkey == "e"));
+        assert_eq!(changed_pools(&events).len(), 1);
+
+        let json = serde_json::to_value(&events[0]).unwrap();
+        assert_eq!(json["type"], "stake_delegated");
+    }
+
+    #[tokio::test]
+    async fn test_record_and_stream() {
+        let mut watcher = PoolWatcher::new(Arc::new(offline_client()), &["jito"]);
+        let stream = event_stream(watcher.subscribe());
+
+        let first = watcher.record(pool("jito", vec![stake_account("a", "v1", u64::MAX)]));
+        assert_eq!(first.len(), 1);
+        let second = watcher.record(pool("jito", vec![]));
+        assert!(second.iter().any(|event| matches!(event, PoolEvent::AccountClosed { .. })));
+        assert!(watcher.snapshot("jito").unwrap().stake_accounts.is_empty());
+
+        for event in first.iter().chain(&second) {
+            watcher.sender.send(event.clone()).unwrap();
+        }
+        drop(watcher);
+        let received: Vec<PoolEvent> = stream.collect().await;
+        assert_eq!(received.len(), first.len() + second.len());
+    }
+
+    #[tokio::test]
+    async fn test_stop_ends_stream() {
+        let handle = PoolWatcher::new(Arc::new(offline_client()), &["jito"]).spawn();
+        let stream = handle.stream();
+
+        handle.stop();
+        tokio::time::timeout(Duration::from_secs(5), stream.collect::<Vec<_>>()).await.unwrap();
+    }
+}
@@ -0,0 +1,447 @@
+//! WebSocket `programSubscribe` transport (`websocket` feature).
+//!
+//! `StakeAccountSubscriber` subscribes to the stake program with the same memcmp authority
+//! filters as `getProgramAccounts` and keeps a `LivePoolView` of each pool's stake accounts.
+//! Accounts whose authority moves away from a pool no longer match the filter and get no
+//! notification, so the view is periodically reconciled with a full fetch. The fetch runs
+//! beside the notification stream, and accounts notified since it started are kept.
+
+use crate::client::PoolsDataClient;
+use crate::error::{PoolsDataError, Result};
+use crate::pools::{AuthorityRole, PoolInfo};
+use crate::rpc::{parse_stake_account_notification, program_subscribe_stake_message};
+use crate::types::StakeAccountInfo;
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Default interval between full reconciliation fetches
+const DEFAULT_RECONCILE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Default delay before reconnecting after the socket closes or fails
+const DEFAULT_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Default)]
+struct ViewState {
+    pools: HashMap<String, HashMap<String, StakeAccountInfo>>,
+    /// Slot of each account's latest notification (including removals), per pool
+    notified_slots: HashMap<String, HashMap<String, u64>>,
+    last_slot: Option<u64>,
+    notifications: u64,
+    reconciled_at: Option<DateTime<Utc>>,
+}
+
+/// Live, in-memory stake accounts of subscribed pools
+#[derive(Debug, Default)]
+pub struct LivePoolView {
+    state: RwLock<ViewState>,
+}
+
+impl LivePoolView {
+    fn read(&self) -> RwLockReadGuard<'_, ViewState> {
+        self.state.read().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, ViewState> {
+        self.state.write().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Names of pools with at least one known account
+    #[must_use]
+    pub fn pool_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.read().pools.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Stake accounts of a pool, sorted by pubkey
+    #[must_use]
+    pub fn accounts(&self, pool_name: &str) -> Vec<StakeAccountInfo> {
+        let mut accounts: Vec<StakeAccountInfo> =
+            self.read().pools.get(pool_name).map(|accounts| accounts.values().cloned().collect()).unwrap_or_default();
+        accounts.sort_by(|a, b| a.pubkey.cmp(&b.pubkey));
+        accounts
+    }
+
+    /// Number of stake accounts of a pool
+    #[must_use]
+    pub fn account_count(&self, pool_name: &str) -> usize {
+        self.read().pools.get(pool_name).map_or(0, HashMap::len)
+    }
+
+    /// Total lamports of a pool's stake accounts
+    #[must_use]
+    pub fn total_lamports(&self, pool_name: &str) -> u64 {
+        self.read().pools.get(pool_name).map_or(0, |accounts| accounts.values().map(|account| account.lamports).sum())
+    }
+
+    /// Slot of the latest notification
+    #[must_use]
+    pub fn last_slot(&self) -> Option<u64> {
+        self.read().last_slot
+    }
+
+    /// Number of account notifications applied
+    #[must_use]
+    pub fn notification_count(&self) -> u64 {
+        self.read().notifications
+    }
+
+    /// When the view was last reconciled with a full fetch
+    #[must_use]
+    pub fn reconciled_at(&self) -> Option<DateTime<Utc>> {
+        self.read().reconciled_at
+    }
+
+    /// Apply one account notification (`None` removes the account)
+    fn apply(&self, pool_name: &str, role: AuthorityRole, pubkey: String, account: Option<StakeAccountInfo>, slot: Option<u64>) {
+        let mut state = self.write();
+        state.notifications += 1;
+        if slot.is_some() {
+            state.last_slot = slot.max(state.last_slot);
+        }
+
+        if let Some(slot) = slot {
+            state.notified_slots.entry(pool_name.to_string()).or_default().insert(pubkey.clone(), slot);
+        }
+
+        let accounts = state.pools.entry(pool_name.to_string()).or_default();
+        match account {
+            Some(mut account) => {
+                account.matched_authority = accounts.get(&pubkey).map_or(role, |known| known.matched_authority.merge(role));
+                accounts.insert(pubkey, account);
+            }
+            None => {
+                accounts.remove(&pubkey);
+            }
+        }
+    }
+
+    /// Replace a pool's accounts with a full fetch started at `since_slot`
+    ///
+    /// Accounts notified at or after `since_slot` may be newer than the fetch, so
+    /// their live state (including removal) wins.
+    fn replace(&self, pool_name: &str, accounts: Vec<StakeAccountInfo>, since_slot: u64) {
+        let mut accounts: HashMap<String, StakeAccountInfo> =
+            accounts.into_iter().map(|account| (account.pubkey.clone(), account)).collect();
+        let mut state = self.write();
+        let ViewState { pools, notified_slots, .. } = &mut *state;
+        let live = pools.remove(pool_name).unwrap_or_default();
+        if let Some(slots) = notified_slots.get_mut(pool_name) {
+            slots.retain(|_, slot| *slot >= since_slot);
+            for pubkey in slots.keys() {
+                match live.get(pubkey) {
+                    Some(account) => accounts.insert(pubkey.clone(), account.clone()),
+                    None => accounts.remove(pubkey),
+                };
+            }
+        }
+        pools.insert(pool_name.to_string(), accounts);
+        state.reconciled_at = Some(Utc::now());
+    }
+}
+
+/// Streams stake account updates of pools over WebSocket into a `LivePoolView`
+pub struct StakeAccountSubscriber {
+    ws_url: String,
+    client: Arc<PoolsDataClient>,
+    pools: Vec<PoolInfo>,
+    reconcile_interval: Duration,
+    reconnect_delay: Duration,
+    view: Arc<LivePoolView>,
+}
+
+impl StakeAccountSubscriber {
+    /// Create a subscriber for pools known to `client`'s registry
+    ///
+    /// `client` is used for reconciliation fetches.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolsDataError::PoolNotFound` if none of the pools are known.
+    pub fn new(ws_url: impl Into<String>, client: Arc<PoolsDataClient>, pool_names: &[&str]) -> Result<Self> {
+        let pools = client.registry().get_many(pool_names);
+        if pools.is_empty() {
+            return Err(PoolsDataError::PoolNotFound {
+                pool_name: format!("None of the requested pools found: {pool_names:?}"),
+            });
+        }
+
+        Ok(Self {
+            ws_url: ws_url.into(),
+            client,
+            pools,
+            reconcile_interval: DEFAULT_RECONCILE_INTERVAL,
+            reconnect_delay: DEFAULT_RECONNECT_DELAY,
+            view: Arc::new(LivePoolView::default()),
+        })
+    }
+
+    /// Set the interval between full reconciliation fetches
+    #[must_use]
+    pub const fn reconcile_interval(mut self, interval: Duration) -> Self {
+        self.reconcile_interval = interval;
+        self
+    }
+
+    /// Set the delay before reconnecting
+    #[must_use]
+    pub const fn reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        self
+    }
+
+    /// Live view updated by this subscriber
+    #[must_use]
+    pub fn view(&self) -> Arc<LivePoolView> {
+        Arc::clone(&self.view)
+    }
+
+    /// Stream updates forever, reconnecting after failures
+    pub async fn run(self) {
+        loop {
+            match self.session().await {
+                Ok(()) => log::warn!("WebSocket {} closed, reconnecting", self.ws_url),
+                Err(e) => log::warn!("WebSocket {} failed: {e}, reconnecting", self.ws_url),
+            }
+            tokio::time::sleep(self.reconnect_delay).await;
+        }
+    }
+
+    /// Run in a background task, returning the view and the task handle
+    #[must_use]
+    pub fn spawn(self) -> (Arc<LivePoolView>, JoinHandle<()>) {
+        (self.view(), tokio::spawn(self.run()))
+    }
+
+    /// One connection: subscribe, reconcile, then apply notifications until the socket closes
+    async fn session(&self) -> Result<()> {
+        let (mut socket, _) = tokio_tungstenite::connect_async(self.ws_url.as_str()).await.map_err(ws_error)?;
+
+        let mut pending: HashMap<u64, (String, AuthorityRole)> = HashMap::new();
+        for pool in &self.pools {
+            for &role in pool.authority_role.roles() {
+                let Some(offset) = role.memcmp_offset() else {
+                    continue;
+                };
+                let id = pending.len() as u64 + 1;
+                let request = program_subscribe_stake_message(id, &pool.authority, offset)?;
+                socket.send(Message::Text(request)).await.map_err(ws_error)?;
+                pending.insert(id, (pool.name.clone(), role));
+            }
+        }
+
+        let mut subscriptions: HashMap<u64, (String, AuthorityRole)> = HashMap::new();
+        let mut reconcile = tokio::time::interval(self.reconcile_interval);
+        let mut reconciling: Option<JoinHandle<()>> = None;
+        loop {
+            tokio::select! {
+                message = socket.next() => match message {
+                    None | Some(Ok(Message::Close(_))) => return Ok(()),
+                    Some(Err(e)) => return Err(ws_error(e)),
+                    Some(Ok(Message::Text(text))) => self.handle_message(&text, &mut pending, &mut subscriptions)?,
+                    Some(Ok(_)) => {}
+                },
+                // Reconcile in its own task so notifications keep flowing during the fetch
+                _ = reconcile.tick() => {
+                    if reconciling.as_ref().map_or(true, JoinHandle::is_finished) {
+                        let reconcile = Self::reconcile(Arc::clone(&self.client), Arc::clone(&self.view), self.pools.clone());
+                        reconciling = Some(tokio::spawn(reconcile));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Handle a subscription confirmation or account notification
+    fn handle_message(
+        &self,
+        text: &str,
+        pending: &mut HashMap<u64, (String, AuthorityRole)>,
+        subscriptions: &mut HashMap<u64, (String, AuthorityRole)>,
+    ) -> Result<()> {
+        let mut message: Value = serde_json::from_str(text)?;
+
+        if let Some(id) = message.get("id").and_then(Value::as_u64) {
+            if let Some(error) = message.get("error") {
+                return Err(PoolsDataError::RpcError {
+                    code: error.get("code").and_then(Value::as_i64).unwrap_or_default(),
+                    message: error.get("message").and_then(Value::as_str).unwrap_or("programSubscribe failed").to_string(),
+                });
+            }
+            if let (Some(target), Some(subscription)) = (pending.remove(&id), message.get("result").and_then(Value::as_u64)) {
+                log::debug!("Subscribed to {} ({:?}) as {subscription}", target.0, target.1);
+                subscriptions.insert(subscription, target);
+            }
+            return Ok(());
+        }
+
+        if message.get("method").and_then(Value::as_str) != Some("programNotification") {
+            return Ok(());
+        }
+        let params = message.get_mut("params").map(Value::take).unwrap_or_default();
+        let Some((pool_name, role)) = params.get("subscription").and_then(Value::as_u64).and_then(|id| subscriptions.get(&id)) else {
+            return Ok(());
+        };
+        let slot = params.pointer("/result/context/slot").and_then(Value::as_u64);
+        let value = params.pointer("/result/value").cloned().unwrap_or_default();
+
+        match parse_stake_account_notification(value) {
+            Ok((pubkey, account)) => self.view.apply(pool_name, *role, pubkey, account, slot),
+            Err(e) => log::warn!("Ignoring malformed stake account notification for {pool_name}: {e}"),
+        }
+        Ok(())
+    }
+
+    /// Replace each pool's accounts with a full fetch; failures keep the current view
+    async fn reconcile(client: Arc<PoolsDataClient>, view: Arc<LivePoolView>, pools: Vec<PoolInfo>) {
+        // getProgramAccounts has no context slot: the current slot bounds the fetch from below
+        let since_slot = match client.fetch_epoch_info().await {
+            Ok(epoch_info) => epoch_info.absolute_slot,
+            Err(e) => {
+                log::warn!("Reconciliation failed: {e}");
+                return;
+            }
+        };
+        let names: Vec<&str> = pools.iter().map(|pool| pool.name.as_str()).collect();
+        match client.fetch_pools_debug(&names).await {
+            Ok(result) => {
+                for (name, error) in &result.failed {
+                    log::warn!("Reconciliation of {name} failed: {}", error.error);
+                }
+                for (name, pool) in result.successful {
+                    view.replace(&name, pool.stake_accounts, since_slot);
+                }
+            }
+            Err(e) => log::warn!("Reconciliation failed: {e}"),
+        }
+    }
+}
+
+fn ws_error(error: tokio_tungstenite::tungstenite::Error) -> PoolsDataError {
+    PoolsDataError::NetworkError {
+        message: format!("WebSocket error: {error}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::tests::{encode_stake_account, pubkey_bytes};
+    use crate::test_support::{offline_client, offline_client_with};
+    use base64::Engine;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    const JITO_AUTHORITY: &str = "6iQKfEyhr3bZMotVkW6beNZz5CPAkiwvgV2CTje9pVSS";
+
+    fn account_value(pubkey: &str, lamports: u64, data: &[u8], owner: &str) -> Value {
+        json!({
+            "pubkey": pubkey,
+            "account": {
+                "lamports": lamports,
+                "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+                "owner": owner,
+                "executable": false,
+                "rentEpoch": 0,
+                "space": data.len()
+            }
+        })
+    }
+
+    fn notification(subscription: u64, pubkey: &str, lamports: u64, data: &[u8], owner: &str) -> Message {
+        let message = json!({
+            "jsonrpc": "2.0",
+            "method": "programNotification",
+            "params": {
+                "subscription": subscription,
+                "result": {
+                    "context": { "slot": 1000 + lamports % 7 },
+                    "value": account_value(pubkey, lamports, data, owner)
+                }
+            }
+        });
+        Message::Text(message.to_string())
+    }
+
+    /// Accept one connection, confirm subscriptions and send account notifications
+    async fn mock_server(listener: TcpListener) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+
+        let Some(Ok(Message::Text(request))) = socket.next().await else {
+            panic!("expected subscribe request");
+        };
+        let request: Value = serde_json::from_str(&request).unwrap();
+        assert_eq!(request["method"], "programSubscribe");
+        assert_eq!(request["params"][1]["filters"][0]["memcmp"]["offset"], 12);
+        assert_eq!(request["params"][1]["filters"][0]["memcmp"]["bytes"], JITO_AUTHORITY);
+        let confirmation = json!({ "jsonrpc": "2.0", "result": 42, "id": request["id"] });
+        socket.send(Message::Text(confirmation.to_string())).await.unwrap();
+
+        let stake = encode_stake_account(2);
+        let stake_program = "Stake11111111111111111111111111111111111111";
+        socket.send(notification(42, "acct1", 5_002_282_880, &stake, stake_program)).await.unwrap();
+        socket.send(notification(42, "acct2", 3_002_282_880, &stake, stake_program)).await.unwrap();
+        socket.send(notification(7, "unknown_subscription", 1, &stake, stake_program)).await.unwrap();
+        socket.send(notification(42, "acct2", 0, &[], "11111111111111111111111111111111")).await.unwrap();
+        socket.send(Message::Text("{\"not\": \"a notification\"}".to_string())).await.unwrap();
+        socket.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_live_view_from_mock_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(mock_server(listener));
+
+        // Reconciliation goes to an unreachable RPC and must leave the view alone
+        let client = offline_client_with(|builder| builder.timeout(1));
+        let subscriber = StakeAccountSubscriber::new(ws_url, Arc::new(client), &["jito"]).unwrap();
+        let view = subscriber.view();
+
+        subscriber.session().await.unwrap();
+        server.await.unwrap();
+
+        assert_eq!(view.notification_count(), 3);
+        assert_eq!(view.pool_names(), vec!["jito"]);
+        let accounts = view.accounts("jito");
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].pubkey, "acct1");
+        assert_eq!(accounts[0].authorized.staker, bs58::encode(pubkey_bytes(1)).into_string());
+        assert_eq!(view.total_lamports("jito"), 5_002_282_880);
+        assert!(view.last_slot().is_some());
+        assert!(view.reconciled_at().is_none());
+    }
+
+    #[test]
+    fn test_replace_keeps_newer_notifications() {
+        let stake = encode_stake_account(2);
+        let account = |pubkey: &str, lamports| {
+            let value = account_value(pubkey, lamports, &stake, "Stake11111111111111111111111111111111111111");
+            parse_stake_account_notification(value).unwrap().1.unwrap()
+        };
+        let view = LivePoolView::default();
+        view.apply("jito", AuthorityRole::Staker, "old".to_string(), Some(account("old", 1)), Some(90));
+        view.apply("jito", AuthorityRole::Staker, "new".to_string(), Some(account("new", 2)), Some(110));
+        view.apply("jito", AuthorityRole::Staker, "removed".to_string(), None, Some(120));
+
+        // The fetch started at slot 100 and saw stale versions of every account
+        view.replace("jito", vec![account("old", 10), account("new", 20), account("removed", 30)], 100);
+
+        let lamports: Vec<(String, u64)> = view.accounts("jito").into_iter().map(|a| (a.pubkey, a.lamports)).collect();
+        assert_eq!(lamports, vec![("new".to_string(), 2), ("old".to_string(), 10)]);
+        assert!(view.reconciled_at().is_some());
+    }
+
+    #[test]
+    fn test_unknown_pools_rejected() {
+        let client = offline_client();
+        assert!(StakeAccountSubscriber::new("ws://127.0.0.1:1", Arc::new(client), &["missing"]).is_err());
+    }
+}